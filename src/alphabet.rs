@@ -0,0 +1,104 @@
+/* The alphabet module gathers the facts about the alphabet the machine works on, so the rest of the crate does not repeat ASCII
+   codes. The cipher letters are the uppercase Roman letters, and the index of a letter counts from 0 at A. Anything else typed
+   passes through the machine unchanged. Other machines, such as the numeric machine, describe their symbols with an Alphabet. */
+
+// The first cipher letter, A
+pub const ALPHABET_BASE: u8 = b'A';
+
+// The number of cipher letters, which is also the number of contacts on each wheel
+pub const ALPHABET_LEN: usize = 26;
+
+/* An Alphabet is a run of consecutive ASCII symbols that a machine enciphers. It contains the following:
+     A u8 called base giving the first symbol
+     A usize called len giving the number of symbols
+   An Alphabet has the following functions available to it:
+     len is a function that returns the number of symbols
+     contains is a function that reports whether a char is one of the symbols
+     index is a function that returns the index of a symbol, counting from 0 at the first
+     symbol is a function that returns the symbol at an index, taken mod len */
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Alphabet {
+    base: u8,
+    len: usize
+}
+
+impl Alphabet {
+    // The uppercase letters A to Z enciphered by the EnigmaMachine
+    pub const LETTERS: Alphabet = Alphabet { base: ALPHABET_BASE, len: ALPHABET_LEN };
+
+    // The digits 0 to 9 enciphered by the NumericMachine
+    pub const DIGITS: Alphabet = Alphabet { base: b'0', len: 10 };
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn contains(&self, chr: char) -> bool {
+        (chr as u32).wrapping_sub(self.base as u32) < self.len as u32
+    }
+
+    /* function: index
+       input: char holding one of the symbols
+       output: usize giving the index of the symbol, where 0 is the first
+       limitations: the char must be one of the symbols, as contains checks. Chars before the first symbol panic in debug builds */
+    pub fn index(&self, chr: char) -> usize {
+        chr as usize - self.base as usize
+    }
+
+    pub fn symbol(&self, index: usize) -> char {
+        (self.base + (index % self.len) as u8) as char
+    }
+}
+
+/* function: is_cipher_letter
+   input: char to check
+   output: bool indicating whether the char is one of the letters the machine enciphers
+   limitations: none obvious at this time */
+pub fn is_cipher_letter(chr: char) -> bool {
+    Alphabet::LETTERS.contains(chr)
+}
+
+/* function: letter_index
+   input: char holding a cipher letter
+   output: usize giving the index of the letter, where 0 is A
+   limitations: the char must be a cipher letter, as is_cipher_letter checks. Chars before A panic in debug builds */
+pub fn letter_index(chr: char) -> usize {
+    Alphabet::LETTERS.index(chr)
+}
+
+/* function: index_letter
+   input: usize giving the index of a letter, taken mod ALPHABET_LEN
+   output: char holding the cipher letter at that index, where 0 is A
+   limitations: none obvious at this time */
+pub fn index_letter(index: usize) -> char {
+    Alphabet::LETTERS.symbol(index)
+}
+
+#[test]
+// Tests to see if the predicate and conversions agree with the ASCII codes they replace
+fn test_alphabet() {
+    for code in 0..0x300u32 {
+        let chr = char::from_u32(code).unwrap();
+        assert_eq!(code > 64 && code < 91, is_cipher_letter(chr));
+        if is_cipher_letter(chr) {
+            assert_eq!(code as usize - 65, letter_index(chr));
+            assert_eq!(chr, index_letter(letter_index(chr)));
+        }
+    }
+    assert_eq!('A', index_letter(ALPHABET_LEN));
+}
+
+#[test]
+// Tests to see if the digit alphabet finds and numbers the digits and nothing else
+fn test_digits() {
+    let digits = Alphabet::DIGITS;
+    assert_eq!(10, digits.len());
+    assert!(('0'..='9').all(|chr| digits.contains(chr)));
+    assert!(!digits.contains('A') && !digits.contains('/') && !digits.contains(':'));
+    assert_eq!(7, digits.index('7'));
+    assert_eq!('3', digits.symbol(13));
+}
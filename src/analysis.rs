@@ -0,0 +1,1068 @@
+/* The analysis module collects tools for studying Enigma traffic rather than producing it. Most of these work on plaintext and
+   ciphertext Strings directly, so they can be used on messages from any machine, including ones whose settings are unknown. */
+use std::cmp::Ordering;
+use std::time::Instant;
+use crate::alphabet::{index_letter, is_cipher_letter, letter_index};
+use crate::config::MachineConfig;
+use crate::enigma::enigma_machine::{EnigmaMachine, Position};
+use crate::presets::RotorId;
+use crate::random::{Rng, XorShift};
+
+/* function: estimate_turnovers
+   inputs: &str containing the plaintext of a message
+           &str containing the ciphertext the machine produced for that plaintext
+   output: Vec<usize> containing the index of each letter (counting letters only) at which the substitution was seen to jump
+   limitations: A jump is found by comparing each letter with the letters typed one full revolution of the fast rotor earlier, so the
+      substitution must have been steady for at least 26 letters before a step for the step to be seen. The index returned is the first
+      letter that proves the jump, which may be a few letters after the step itself if the letters around it do not overlap
+   algorithm: at any one position the machine's substitution swaps letters in pairs, so each plaintext/ciphertext pair is one of the
+      swaps of that position. Until the middle rotor steps, the letters typed 26 apart pass through the same substitution, so their
+      pairs must either be the same swap or share no letters at all. A pair that shares exactly one letter with an earlier pair at the
+      same fast rotor position shows the substitution has changed, which is reported as a turnover. Comparisons then start again from
+      that letter */
+pub fn estimate_turnovers(plaintext: &str, ciphertext: &str) -> Vec<usize> {
+    let pairs: Vec<(char, char)> = plaintext.chars().filter(|chr| is_cipher_letter(*chr))
+        .zip(ciphertext.chars().filter(|chr| is_cipher_letter(*chr)))
+        .collect();
+    let mut turnovers: Vec<usize> = vec![];
+    let mut segment_start: usize = 0;
+
+    for i in 0..pairs.len() {
+        let mut j = i;
+        while j >= segment_start + 26 {
+            j -= 26;
+            if swaps_conflict(pairs[i], pairs[j]) {
+                turnovers.push(i);
+                segment_start = i;
+                break;
+            }
+        }
+    }
+
+    turnovers
+}
+
+/* function: herivel_candidates
+   inputs: &[(char, char, char)] containing the indicators (the first rotor positions chosen by the operators) from a day's traffic
+   output: Vec<(char, char, char)> containing the rotor positions most likely to be the ring setting, best candidate first
+   limitations: The tip relies on lazy operators. If the day's indicators were chosen at random the candidates are meaningless, and if
+      fewer than two indicators fall near one another no candidates are returned. Indicators containing characters that are not
+      uppercase letters are ignored
+   algorithm: having set the rings, lazy operators left the rotors close to the ring setting letters and sent those positions as their
+      first indicator of the day. Each of the 17576 rotor positions is scored by the number of indicators that lie within three
+      letters of it on every rotor. The positions with the highest count are returned, ordered by their total distance from those
+      indicators so the centre of the cluster comes first. Repeated indicators are counted together, so each distinct one is only
+      compared with every position once */
+pub fn herivel_candidates(indicators: &[(char, char, char)]) -> Vec<(char, char, char)> {
+    const NEAR: u16 = 3;
+    let mut letters: Vec<[u16; 3]> = indicators.iter()
+        .filter(|(left, middle, right)| [*left, *middle, *right].iter().all(|chr| is_cipher_letter(*chr)))
+        .map(|(left, middle, right)| [letter_index(*left) as u16, letter_index(*middle) as u16, letter_index(*right) as u16])
+        .collect();
+    letters.sort();
+    let mut repeats: Vec<([u16; 3], usize)> = vec![];
+    for indicator in letters {
+        match repeats.last_mut() {
+            Some((last, times)) if *last == indicator => *times += 1,
+            _ => repeats.push((indicator, 1))
+        }
+    }
+    let mut best_count: usize = 2;
+    let mut candidates: Vec<(usize, [u16; 3])> = vec![];
+
+    for code in 0..26u16 * 26 * 26 {
+        let candidate = [code / 676, (code / 26) % 26, code % 26];
+        let mut count: usize = 0;
+        let mut distance: usize = 0;
+        for (indicator, times) in &repeats {
+            let offsets: [u16; 3] = std::array::from_fn(|i| circular_distance(candidate[i], indicator[i]));
+            if offsets.iter().all(|offset| *offset <= NEAR) {
+                count += times;
+                distance += times * offsets.iter().map(|offset| *offset as usize).sum::<usize>();
+            }
+        }
+        if count > best_count {
+            best_count = count;
+            candidates.clear();
+        }
+        if count == best_count {
+            candidates.push((distance, candidate));
+        }
+    }
+
+    candidates.sort();
+    candidates.iter()
+        .map(|(_, [left, middle, right])| (index_letter(*left as usize),
+            index_letter(*middle as usize),
+            index_letter(*right as usize)))
+        .collect()
+}
+
+/* function: find_females
+   inputs: &[(String, String)] containing the indicators of a day's traffic, each the ground setting sent in the clear, e.g.: "RTJ",
+           and the message key enciphered twice over from it, e.g.: "WAHWOH"
+   output: Vec<usize> containing the index of each indicator that is a female, in order
+   limitations: Only the enciphered indicator is examined, the ground setting is carried so the indices line up with the traffic
+      it came from and is needed later to place the female on the Zygalski sheets. Spaces in the indicator are ignored, and an
+      indicator that is not six uppercase letters once they are removed is never a female
+   algorithm: from 1938 the operator chose a ground setting, sent it in the clear, and enciphered the message key twice from it,
+      so the first and fourth letters, the second and fifth, and the third and sixth are each the same key letter enciphered three
+      presses apart. When one of these pairs is the same ciphertext letter the indicator is a female: the permutation three presses
+      on maps a letter to itself through the two rotor positions, which only happens at about 40 percent of positions. Zygalski's
+      sheets marked those positions, and stacking the sheets for a day's females left only the rotor settings consistent with all
+      of them */
+pub fn find_females(indicators: &[(String, String)]) -> Vec<usize> {
+    indicators.iter().enumerate()
+        .filter(|(_, (_, indicator))| {
+            let letters: Vec<char> = indicator.chars().filter(|chr| *chr != ' ').collect();
+            letters.len() == 6 && letters.iter().all(|chr| is_cipher_letter(*chr)) && (0..3).any(|i| letters[i] == letters[i + 3])
+        })
+        .map(|(index, _)| index)
+        .collect()
+}
+
+/* function: keyspace_size
+   inputs: usize giving the number of plugboard cables used
+           usize giving the number of rotors the operator could choose three from
+   output: f64 estimating the number of distinct daily keys
+   limitations: Only the rightmost two ring settings are counted, since the leftmost ring never affects a turnover. Rotor orders that
+      behave identically are not discounted, so this is an upper bound. An f64 is used since the count overflows a u64
+   algorithm: the key space is the product of the number of rotor orders (rotors_available choose three, in order), the 26^3 starting
+      positions, the 26^2 effective ring settings and the number of ways of placing the plugboard cables, which is
+      26! / ((26 - 2p)! * p! * 2^p) for p cables */
+pub fn keyspace_size(plug_pairs: usize, rotors_available: usize) -> f64 {
+    let rotor_orders: f64 = (0..3).map(|taken| rotors_available.saturating_sub(taken) as f64).product();
+    let positions: f64 = 26f64.powi(3);
+    let rings: f64 = 26f64.powi(2);
+    let mut plugboards: f64 = 1.0;
+    if plug_pairs > 13 {
+        plugboards = 0.0;
+    } else {
+        for cable in 0..plug_pairs {
+            // each cable chooses two of the remaining letters, and the order the cables are placed in does not matter
+            let remaining = (26 - 2 * cable) as f64;
+            plugboards *= remaining * (remaining - 1.0) / 2.0 / (cable + 1) as f64;
+        }
+    }
+
+    rotor_orders * positions * rings * plugboards
+}
+
+/* function: rotor_orders
+   inputs: &[RotorId] containing the rotors the operator could choose from
+           usize giving the number of rotor slots in the machine
+   output: Vec<Vec<RotorId>> containing every ordered arrangement of distinct rotors in the slots, leftmost slot first
+   limitations: The count grows quickly (n! / (n - slots)!), which is fine for the historical sets of five to eight rotors. A rotor
+      listed twice in available is treated as two separate rotors
+   algorithm: each rotor not yet used is placed in the next slot in turn, and the remaining slots are filled the same way */
+pub fn rotor_orders(available: &[RotorId], slots: usize) -> Vec<Vec<RotorId>> {
+    if slots == 0 {
+        return vec![vec![]];
+    }
+    let mut orders: Vec<Vec<RotorId>> = vec![];
+    for (index, rotor) in available.iter().enumerate() {
+        let mut remaining = available.to_vec();
+        remaining.remove(index);
+        for mut order in rotor_orders(&remaining, slots - 1) {
+            order.insert(0, *rotor);
+            orders.push(order);
+        }
+    }
+
+    orders
+}
+
+/* function: recover_plugboard
+   inputs: &EnigmaMachine set to the rotor order, ring settings and start positions the message was sent with
+           &str containing the ciphertext of the message
+           &str containing a crib, plaintext known to appear at the start of the message
+   output: Option containing the plugboard pairs the crib proves, in alphabetical order, or None if no plugboard fits the crib
+   limitations: The machine's own plugboard is ignored. Only letters that appear in the crib or the matching ciphertext can be
+      recovered, so a letter plugged to one outside the crib may be missed. A short crib may fit more than one plugboard, in which case
+      the first one found is returned, trying each letter unplugged before plugging it to anything else. Characters other than
+      uppercase letters are skipped in both texts
+   algorithm: each crib letter p enciphered to c by c = P(S(P(p))), where P is the plugboard and S the rotors and reflector at that
+      letter, so if p is plugged to x then c must be plugged to S(x). Like the Turing bombe, a guess at the partner of one crib letter
+      is followed through every crib pair it touches, and each new plug deduced is followed in turn. A deduction that plugs a letter to
+      two different partners disproves the guess. When the deductions run out with crib letters still unplugged, a guess is made for
+      one of them and the search backtracks if it fails */
+pub fn recover_plugboard(machine: &EnigmaMachine, ciphertext: &str, crib: &str) -> Option<Vec<(char, char)>> {
+    let pairs: Vec<(usize, usize)> = crib.chars().filter(|chr| is_cipher_letter(*chr))
+        .zip(ciphertext.chars().filter(|chr| is_cipher_letter(*chr)))
+        .map(|(plain, cipher)| (letter_index(plain), letter_index(cipher)))
+        .collect();
+    let mut scrambler = machine.clone();
+    let substitutions: Vec<[u8; 26]> = pairs.iter().map(|_| {
+        scrambler.step(1);
+        scrambler.scrambler()
+    }).collect();
+
+    let steckers = search_steckers([None; 26], &pairs, &substitutions)?;
+    Some((0..26).filter_map(|letter| match steckers[letter] {
+        Some(partner) if letter < partner => Some((index_letter(letter), index_letter(partner))),
+        _ => None
+    }).collect())
+}
+
+/* function: search_fast_rotor
+   inputs: &MachineConfig with the leftmost and middle rotors, ring settings, start positions and reflector the message was sent with
+           &[RotorId] containing the rotors that may have been in the rightmost (fast) slot
+           &str containing the ciphertext of the message
+           &str containing a crib, plaintext known to appear at the start of the message
+   output: Vec<RotorId> of the candidates the crib fits, in the order they were given
+   limitations: The config's plugboard and its rotor in the fast slot are ignored. A short crib may fit more than one candidate,
+      so a crib of twenty letters or more is best
+   algorithm: each candidate is put in the fast slot and the crib is checked as in recover_plugboard, which finds a plugboard that
+      turns the crib into the ciphertext if there is one. A wrong rotor gives substitutions no plugboard can reconcile with a long
+      enough crib */
+pub fn search_fast_rotor(config: &MachineConfig, candidates: &[RotorId], ciphertext: &str, crib: &str) -> Vec<RotorId> {
+    candidates.iter().copied().filter(|candidate| {
+        let mut trial = config.clone();
+        trial.rotors[2] = *candidate;
+        recover_plugboard(&trial.build(), ciphertext, crib).is_some()
+    }).collect()
+}
+
+// Guesses a partner for the first crib letter left unplugged, backtracking until every crib letter is plugged without contradiction
+fn search_steckers(steckers: [Option<usize>; 26], pairs: &[(usize, usize)], substitutions: &[[u8; 26]]) -> Option<[Option<usize>; 26]> {
+    let letter = match pairs.iter().flat_map(|(plain, cipher)| [*plain, *cipher]).find(|letter| steckers[*letter].is_none()) {
+        Some(letter) => letter,
+        None => return Some(steckers)
+    };
+    (0..26).map(|offset| (letter + offset) % 26).find_map(|partner| {
+        let mut guess = steckers;
+        if plug(&mut guess, letter, partner, pairs, substitutions) {
+            search_steckers(guess, pairs, substitutions)
+        } else {
+            None
+        }
+    })
+}
+
+// Plugs two letters together and follows every plug the crib then implies, returning false if any letter ends up with two partners
+fn plug(steckers: &mut [Option<usize>; 26], first: usize, second: usize, pairs: &[(usize, usize)], substitutions: &[[u8; 26]]) -> bool {
+    let mut pending = vec![(first, second)];
+    while let Some((letter, partner)) = pending.pop() {
+        match (steckers[letter], steckers[partner]) {
+            (Some(current), _) if current != partner => return false,
+            (_, Some(current)) if current != letter => return false,
+            (Some(_), _) => continue,
+            _ => {}
+        }
+        steckers[letter] = Some(partner);
+        steckers[partner] = Some(letter);
+        for (index, (plain, cipher)) in pairs.iter().enumerate() {
+            for (plugged, known) in [(letter, partner), (partner, letter)] {
+                if *plain == plugged {
+                    pending.push((*cipher, substitutions[index][known] as usize));
+                }
+                if *cipher == plugged {
+                    pending.push((*plain, substitutions[index][known] as usize));
+                }
+            }
+        }
+    }
+
+    true
+}
+
+/* function: recover_wiring
+   inputs: &MachineConfig with the rotors, ring settings, plugboard and reflector the pairs were enciphered with. The rotor it names
+              in the unknown slot and its start positions are ignored
+           usize giving the slot of the unknown rotor, 0 for the leftmost (slow) rotor to 2 for the rightmost (fast) one
+           &[(char, char, Position)] containing plaintext letters, the ciphertext letters they became and the Position showing in the
+              windows as each was enciphered, after the key press stepped the rotors
+   output: Option containing the unknown rotor's wiring as an enciphered alphabet, or None if the pairs allow more than one wiring,
+      or none at all
+   limitations: Pairs that are not two letters are ignored. Several hundred pairs at varied positions are usually needed, since
+      each pair only ties two contacts of the rotor together
+   algorithm: everything but the unknown rotor is known, so each plaintext and ciphertext letter can be traced from the keyboard to
+      the right side of the unknown rotor, and from there onto its wiring core at contacts u and v. The signal leaves the core at
+      W(u), passes through the known rotors to the left and the reflector, which together swap letters in pairs by some S, and comes
+      back into the core at W(v). So each pair gives W(v) = S(W(u)), and as S is its own inverse also W(u) = S(W(v)). As in
+      recover_plugboard a guess at W for one contact is followed through every pair it touches, and each new contact deduced is
+      followed in turn until the guess is disproved or the whole wiring is settled. The search stops at the second complete wiring,
+      since then the pairs do not decide between them */
+pub fn recover_wiring(known: &MachineConfig, unknown_slot: usize, pairs: &[(char, char, Position)]) -> Option<String> {
+    if unknown_slot > 2 {
+        return None;
+    }
+    let table = |wiring: &str| -> [u8; 26] {
+        let mut table = [0u8; 26];
+        for (contact, chr) in wiring.chars().take(26).enumerate() {
+            table[contact] = letter_index(chr) as u8;
+        }
+        table
+    };
+    let invert = |table: &[u8; 26]| -> [u8; 26] {
+        let mut inverse = [0u8; 26];
+        for (contact, output) in table.iter().enumerate() {
+            inverse[*output as usize] = contact as u8;
+        }
+        inverse
+    };
+    // A rotor whose core stands offset places round maps x to W(x + offset) - offset, going right to left
+    let through = |wiring: &[u8; 26], offset: usize, contact: usize| (wiring[(contact + offset) % 26] as usize + 26 - offset) % 26;
+
+    let mut plugboard: [u8; 26] = std::array::from_fn(|letter| letter as u8);
+    for (first, second) in known.plugboard.iter().filter(|(first, second)| is_cipher_letter(*first) && is_cipher_letter(*second)) {
+        plugboard[letter_index(*first)] = letter_index(*second) as u8;
+        plugboard[letter_index(*second)] = letter_index(*first) as u8;
+    }
+    let rotors: Vec<[u8; 26]> = known.rotors.iter().map(|rotor| table(rotor.wiring())).collect();
+    let inverses: Vec<[u8; 26]> = rotors.iter().map(invert).collect();
+    let reflector = table(known.reflector.wiring());
+    let greek = known.greek.map(|(rotor, ring, position)| {
+        let wiring = table(rotor.wiring());
+        (wiring, invert(&wiring), (position % 26 + 26 - ring % 26) as usize % 26)
+    });
+
+    let mut links = vec![];
+    let mut substitutions = vec![];
+    for (plain, cipher, (left, middle, right)) in pairs {
+        if !is_cipher_letter(*plain) || !is_cipher_letter(*cipher) {
+            continue;
+        }
+        let offsets: Vec<usize> = [*left, *middle, *right].iter().zip(known.rings)
+            .map(|(window, ring)| (letter_index(*window) + 26 - ring as usize % 26) % 26)
+            .collect();
+        let offset = offsets[unknown_slot];
+        let to_core = |letter: usize| {
+            let mut contact = plugboard[letter] as usize;
+            for slot in (unknown_slot + 1..3).rev() {
+                contact = through(&rotors[slot], offsets[slot], contact);
+            }
+            (contact + offset) % 26
+        };
+        links.push((to_core(letter_index(*plain)), to_core(letter_index(*cipher))));
+        substitutions.push(std::array::from_fn(|core| {
+            let mut contact = (core + 26 - offset) % 26;
+            for slot in (0..unknown_slot).rev() {
+                contact = through(&rotors[slot], offsets[slot], contact);
+            }
+            if let Some((wiring, _, greek_offset)) = &greek {
+                contact = through(wiring, *greek_offset, contact);
+            }
+            contact = reflector[contact] as usize;
+            if let Some((_, inverse, greek_offset)) = &greek {
+                contact = through(inverse, *greek_offset, contact);
+            }
+            for slot in 0..unknown_slot {
+                contact = through(&inverses[slot], offsets[slot], contact);
+            }
+            ((contact + offset) % 26) as u8
+        }));
+    }
+
+    let mut solutions = vec![];
+    search_wiring([None; 26], &links, &substitutions, &mut solutions);
+    match solutions.as_slice() {
+        [wiring] => Some(wiring.iter().map(|contact| index_letter(*contact as usize)).collect()),
+        _ => None
+    }
+}
+
+// Guesses the output of the first contact left unwired, backtracking through every guess until two complete wirings are found
+fn search_wiring(wiring: [Option<u8>; 26], links: &[(usize, usize)], substitutions: &[[u8; 26]], solutions: &mut Vec<[u8; 26]>) {
+    let contact = match wiring.iter().position(|output| output.is_none()) {
+        Some(contact) => contact,
+        None => {
+            solutions.push(wiring.map(|output| output.unwrap()));
+            return;
+        }
+    };
+    for output in 0..26 {
+        let mut guess = wiring;
+        if solutions.len() < 2 && wire(&mut guess, contact, output, links, substitutions) {
+            search_wiring(guess, links, substitutions, solutions);
+        }
+    }
+}
+
+// Wires a contact to an output and follows every contact the pairs then imply, returning false if two contacts share an output
+// or one contact ends up with two
+fn wire(wiring: &mut [Option<u8>; 26], first: usize, output: u8, links: &[(usize, usize)], substitutions: &[[u8; 26]]) -> bool {
+    let mut pending = vec![(first, output)];
+    while let Some((contact, output)) = pending.pop() {
+        match wiring[contact] {
+            Some(current) if current != output => return false,
+            Some(_) => continue,
+            None if wiring.contains(&Some(output)) => return false,
+            None => wiring[contact] = Some(output)
+        }
+        for (index, (entry, exit)) in links.iter().enumerate() {
+            if *entry == contact {
+                pending.push((*exit, substitutions[index][output as usize]));
+            }
+            if *exit == contact {
+                pending.push((*entry, substitutions[index][output as usize]));
+            }
+        }
+    }
+
+    true
+}
+
+/* function: permutation_cycles
+   inputs: &[u8; 26] containing a permutation of the letters, where the entry at each index is the letter (0 is A) it is sent to
+   output: Vec<usize> containing the length of each cycle of the permutation, shortest first, with fixed letters counted as cycles of
+      length 1 so the lengths always add up to 26
+   limitations: The entries must be a permutation of 0 to 25, or the result is meaningless
+   algorithm: each letter not yet visited starts a new cycle, which is followed around until it returns to that letter */
+pub fn permutation_cycles(perm: &[u8; 26]) -> Vec<usize> {
+    let mut visited = [false; 26];
+    let mut cycles: Vec<usize> = vec![];
+    for start in 0..26 {
+        let mut letter = start;
+        let mut length = 0;
+        while !visited[letter] {
+            visited[letter] = true;
+            letter = perm[letter] as usize % 26;
+            length += 1;
+        }
+        if length > 0 {
+            cycles.push(length);
+        }
+    }
+    cycles.sort_unstable();
+
+    cycles
+}
+
+/* function: compose_substitutions
+   inputs: &[u8; 26] containing the substitution applied first, e.g.: from EnigmaMachine::substitution
+           &[u8; 26] containing the substitution applied second
+   output: [u8; 26] containing the substitution that has the effect of applying the first and then the second
+   limitations: none obvious at this time
+   algorithm: for the indicator attack, the substitutions at the first and fourth letters of the doubled message key are composed.
+      Since the operator typed the same key letter at both, the composition takes each first enciphered letter to the fourth, and its
+      cycle lengths depend only on the rotor positions and not the plugboard */
+pub fn compose_substitutions(first: &[u8; 26], second: &[u8; 26]) -> [u8; 26] {
+    let mut composed = [0u8; 26];
+    for (index, entry) in composed.iter_mut().enumerate() {
+        *entry = second[first[index] as usize % 26];
+    }
+
+    composed
+}
+
+/* function: compare
+   inputs: &str containing one candidate plaintext
+           &str containing another candidate plaintext
+   output: Ordering that is Less when the first candidate looks more like English than the second, so candidates sorted with it put
+      the most English-like first, e.g.: candidates.sort_by(|a, b| compare(a, b))
+   limitations: Only uppercase letters are counted. A candidate with no letters sorts after every candidate that has some. Very short
+      candidates give noisy scores, and German plaintext scores worse than English plaintext of the same length
+   algorithm: each candidate is scored with the chi-squared statistic of its letter counts against the letter frequencies of English
+      text, the sum over the letters of (observed - expected)^2 / expected. The lower score is the closer match */
+pub fn compare(a: &str, b: &str) -> Ordering {
+    english_chi_squared(a).total_cmp(&english_chi_squared(b))
+}
+
+// The share of each letter, A to Z, in English text
+const ENGLISH_FREQUENCIES: [f64; 26] = [
+    0.0817, 0.0149, 0.0278, 0.0425, 0.1270, 0.0223, 0.0202, 0.0609, 0.0697, 0.0015, 0.0077, 0.0403, 0.0241,
+    0.0675, 0.0751, 0.0193, 0.0010, 0.0599, 0.0633, 0.0906, 0.0276, 0.0098, 0.0236, 0.0015, 0.0197, 0.0007
+];
+
+// The share of the most common bigrams in English text. The other 626 bigrams share what is left equally
+const ENGLISH_BIGRAMS: [(&str, f64); 50] = [
+    ("TH", 0.0356), ("HE", 0.0307), ("IN", 0.0243), ("ER", 0.0205), ("AN", 0.0199), ("RE", 0.0185), ("ON", 0.0176),
+    ("AT", 0.0149), ("EN", 0.0145), ("ND", 0.0135), ("TI", 0.0134), ("ES", 0.0134), ("OR", 0.0128), ("TE", 0.0120),
+    ("OF", 0.0117), ("ED", 0.0117), ("IS", 0.0113), ("IT", 0.0112), ("AL", 0.0109), ("AR", 0.0107), ("ST", 0.0105),
+    ("TO", 0.0104), ("NT", 0.0104), ("NG", 0.0095), ("SE", 0.0093), ("HA", 0.0093), ("AS", 0.0087), ("OU", 0.0087),
+    ("IO", 0.0083), ("LE", 0.0083), ("VE", 0.0083), ("CO", 0.0079), ("ME", 0.0079), ("DE", 0.0076), ("HI", 0.0076),
+    ("RI", 0.0073), ("RO", 0.0073), ("IC", 0.0070), ("NE", 0.0069), ("EA", 0.0069), ("RA", 0.0069), ("CE", 0.0065),
+    ("LI", 0.0062), ("CH", 0.0060), ("LL", 0.0058), ("BE", 0.0058), ("MA", 0.0057), ("SI", 0.0055), ("OM", 0.0055),
+    ("UR", 0.0054)
+];
+
+// The share of the most common trigrams in English text. The other 17546 trigrams share what is left equally
+const ENGLISH_TRIGRAMS: [(&str, f64); 30] = [
+    ("THE", 0.0181), ("AND", 0.0073), ("ING", 0.0072), ("ENT", 0.0042), ("ION", 0.0042), ("HER", 0.0036), ("FOR", 0.0034),
+    ("THA", 0.0033), ("NTH", 0.0033), ("INT", 0.0032), ("ERE", 0.0031), ("TIO", 0.0031), ("TER", 0.0030), ("EST", 0.0028),
+    ("ERS", 0.0028), ("ATI", 0.0026), ("HAT", 0.0026), ("ATE", 0.0025), ("ALL", 0.0025), ("ETH", 0.0024), ("HES", 0.0024),
+    ("VER", 0.0024), ("HIS", 0.0024), ("OFT", 0.0022), ("ITH", 0.0021), ("FTH", 0.0021), ("STH", 0.0021), ("OTH", 0.0021),
+    ("RES", 0.0021), ("ONT", 0.0020)
+];
+
+/* function: ngram_score
+   inputs: &str containing the text to score
+           usize giving the length of the n-grams to score it by, 1 for letters, 2 for bigrams or 3 for trigrams
+   output: f64 giving the log-likelihood of the text under a model of English, where a higher score is more English-like
+   limitations: Only uppercase letters are scored, and they are read as one run, so n-grams span word breaks as they do in
+      ciphertext. There is no table for other values of n, and text shorter than n has no n-grams, so both score 0. The tables only
+      hold the most common n-grams, so every rarer n-gram gets the same share of what remains. Scores only compare between texts
+      of the same length
+   algorithm: the sum over every n-gram of the text, overlapping, of the base 10 log of its share of English text. Unlike the index
+      of coincidence and letter counts, this sees the order of the letters, so it still ranks English above its own letters
+      shuffled, and it separates short messages better */
+pub fn ngram_score(text: &str, n: usize) -> f64 {
+    let letters: Vec<char> = text.chars().filter(|chr| is_cipher_letter(*chr)).collect();
+    if !(1..=3).contains(&n) || letters.len() < n {
+        return 0.0;
+    }
+    let (table, count): (&[(&str, f64)], usize) = match n {
+        2 => (&ENGLISH_BIGRAMS, 26 * 26),
+        3 => (&ENGLISH_TRIGRAMS, 26 * 26 * 26),
+        _ => (&[], 26)
+    };
+    let floor = (1.0 - table.iter().map(|(_, share)| share).sum::<f64>()) / (count - table.len()) as f64;
+
+    letters.windows(n).map(|gram| {
+        let share = if n == 1 {
+            ENGLISH_FREQUENCIES[letter_index(gram[0])]
+        } else {
+            let gram: String = gram.iter().collect();
+            table.iter().find(|(common, _)| *common == gram).map_or(floor, |(_, share)| *share)
+        };
+        share.log10()
+    }).sum()
+}
+
+/* function: sample_english
+   inputs: usize giving the number of letters to generate
+           &mut Rng supplying the random choices, so a seeded generator gives the same text each time
+   output: String of uppercase letters drawn with the letter frequencies of English
+   limitations: Only the single letter frequencies are matched. The letters are drawn independently, so the text has no words and
+      its bigrams and trigrams are not those of English, which ngram_score with n above 1 will notice
+   algorithm: each letter is drawn by picking a point at random along the English frequencies laid end to end and taking the
+      letter whose share it falls in */
+pub fn sample_english<R: Rng>(len: usize, rng: &mut R) -> String {
+    let total: f64 = ENGLISH_FREQUENCIES.iter().sum();
+    (0..len).map(|_| {
+        let mut point = rng.next_f64() * total;
+        let letter = ENGLISH_FREQUENCIES.iter().position(|share| {
+            point -= share;
+            point < 0.0
+        }).unwrap_or(25);
+        index_letter(letter)
+    }).collect()
+}
+
+/* function: behaviorally_equal
+   inputs: &mut EnigmaMachines to compare, which are both reset to their ground settings first
+           usize giving the number of letters to compare them over
+   output: bool that is true if both machines transform the sample to the same output
+   limitations: This is a spot check rather than a proof, so machines that differ only on presses beyond the sample, such as ones
+      whose leftmost rotors turn over at different letters, are reported equal. A sample of a few thousand letters passes every
+      turnover of the fast rotor and most of the middle rotor's. Both machines are left where the sample ends
+   algorithm: the sample is random letters from a fixed seed, so it is the same on every call and every letter is pressed at many
+      different rotor positions. Both machines are reset and transform it, and their outputs are compared */
+pub fn behaviorally_equal(a: &mut EnigmaMachine, b: &mut EnigmaMachine, sample_len: usize) -> bool {
+    let mut rng = XorShift::new(0x454E_4947_4D41);
+    let sample: String = (0..sample_len).map(|_| index_letter(rng.below(26) as usize)).collect();
+    a.reset();
+    b.reset();
+
+    a.transform(&sample) == b.transform(&sample)
+}
+
+// Scores text against the letter frequencies of English, where a lower score is more English-like and text with no letters is infinite
+fn english_chi_squared(text: &str) -> f64 {
+    let mut counts = [0usize; 26];
+    for letter in text.chars().filter(|chr| is_cipher_letter(*chr)) {
+        counts[letter_index(letter)] += 1;
+    }
+    let total: usize = counts.iter().sum();
+    if total == 0 {
+        return f64::INFINITY;
+    }
+
+    counts.iter().zip(ENGLISH_FREQUENCIES).map(|(count, frequency)| {
+        let expected = frequency * total as f64;
+        (*count as f64 - expected).powi(2) / expected
+    }).sum()
+}
+
+/* function: index_of_coincidence
+   inputs: &str containing the text to measure
+   output: f64 giving the chance that two letters picked from different places in the text are the same letter
+   limitations: Only uppercase letters are counted. Text with fewer than two letters returns 0
+   algorithm: the sum over the letters of n(n - 1), divided by N(N - 1), where n is the count of each letter and N is the count of all
+      of them. Random letters give about 1/26 = 0.038, and English about 0.066 */
+pub fn index_of_coincidence(text: &str) -> f64 {
+    let mut counts = [0usize; 26];
+    for letter in text.chars().filter(|chr| is_cipher_letter(*chr)) {
+        counts[letter_index(letter)] += 1;
+    }
+    let total: usize = counts.iter().sum();
+    if total < 2 {
+        return 0.0;
+    }
+
+    counts.iter().map(|count| (count * count.saturating_sub(1)) as f64).sum::<f64>() / (total * (total - 1)) as f64
+}
+
+/* function: looks_like_enigma
+   inputs: &str containing the text to check
+   output: bool that is true if the text could plausibly be Enigma ciphertext
+   limitations: This is a quick heuristic, not a proof. The text must have at least 26 letters, as shorter texts are too noisy to
+      judge and are always rejected. Other simple substitutions of random text would pass as well
+   algorithm: Enigma output has a near uniform letter distribution, so its index of coincidence is close to that of random letters,
+      about 0.038, while any natural language plaintext is well above it (English about 0.066, German about 0.076). The text passes
+      when its index of coincidence is below 0.050, which allows for the spread of random text of a hundred letters or so */
+pub fn looks_like_enigma(text: &str) -> bool {
+    const MIN_LETTERS: usize = 26;
+    const MAX_INDEX: f64 = 0.050;
+    text.chars().filter(|chr| is_cipher_letter(*chr)).count() >= MIN_LETTERS && index_of_coincidence(text) < MAX_INDEX
+}
+
+/* function: looks_like_enigma_with_crib
+   inputs: &str containing the text to check
+           &str containing a crib, plaintext believed to appear at the start of the text
+   output: bool that is true if the text could plausibly be Enigma ciphertext of the crib
+   limitations: As for looks_like_enigma. Characters other than uppercase letters are skipped in both texts
+   algorithm: as well as the checks of looks_like_enigma, no letter of the crib may line up with the same letter of the text, since
+      the reflector means the Enigma never enciphers a letter to itself */
+pub fn looks_like_enigma_with_crib(text: &str, crib: &str) -> bool {
+    let fits = crib.chars().filter(|chr| is_cipher_letter(*chr))
+        .zip(text.chars().filter(|chr| is_cipher_letter(*chr)))
+        .all(|(plain, cipher)| plain != cipher);
+    fits && looks_like_enigma(text)
+}
+
+/* function: all_involutions
+   inputs: usize giving the length of a toy alphabet
+   output: Vec of every fixed-point-free involution of that alphabet, each a Vec<u8> where the entry at each index is the letter it is
+      swapped with, e.g.: [1, 0, 3, 2] for 4
+   limitations: An alphabet of odd length has none, since a letter would be left to swap with itself, and the empty alphabet has
+      exactly one. The count is (n - 1)(n - 3)...1, which is 105 for 8 but grows quickly past it, so this is meant for alphabets
+      of 8 letters or fewer
+   algorithm: these are the possible reflector wirings of a machine with n letters. The lowest letter not yet wired is paired with
+      each of the letters still free in turn, and the rest are wired the same way for each choice */
+pub fn all_involutions(alphabet_len: usize) -> Vec<Vec<u8>> {
+    let mut involutions: Vec<Vec<u8>> = vec![];
+    if alphabet_len.is_multiple_of(2) {
+        pair_letters(&mut vec![None; alphabet_len], &mut involutions);
+    }
+
+    involutions
+}
+
+/* function: infer_ring_setting
+   inputs: &[(Position, Position)] holding observations of the rotors, each the letters showing in the windows and the letters the
+      wiring cores stood at, both leftmost rotor first, e.g.: (('S', 'X', 'T'), ('R', 'D', 'I')) for rings B, U and L
+   output: Option containing the ring settings of the leftmost, middle and rightmost rotors, where 0 is A, or None if there are no
+      observations or they disagree
+   limitations: The cores' positions must already be known, as they are once a message key has been broken and its indicator
+      read. Positions that are not uppercase letters are not checked
+   algorithm: turning the ring moves the letters against the wiring core, so the window shows the core's position plus the ring
+      setting. Each observation therefore gives the ring setting of each rotor as window - core mod 26, and every observation of the
+      same daily key must give the same settings */
+pub fn infer_ring_setting(observations: &[(Position, Position)]) -> Option<(u16, u16, u16)> {
+    let offset = |window: char, core: char| ((letter_index(window) + 26 - letter_index(core)) % 26) as u16;
+    let mut rings = observations.iter().map(|((left, middle, right), (left_core, middle_core, right_core))| {
+        (offset(*left, *left_core), offset(*middle, *middle_core), offset(*right, *right_core))
+    });
+    let first = rings.next()?;
+    if rings.all(|rings| rings == first) {
+        Some(first)
+    } else {
+        None
+    }
+}
+
+/* function: ring_position_equivalent
+   inputs: u16s giving the ring setting and window position of a rotor in one setting, where 0 is A
+           u16s giving the ring setting and window position of the same rotor in another setting
+           &str containing the window letters at which the rotor turns over, e.g.: "V" for rotor III
+   output: bool that is true if the two settings can only be told apart by when the rotor turns over its neighbour
+   limitations: This is meant for the rightmost rotor, where the turnovers come soonest. Two settings with different window letters
+      always turn over at different presses eventually, so equivalent only means the difference is not visible until then
+   algorithm: the wiring core stands at the window position less the ring setting, so turning ring and rotor the same number of
+      places gives the same substitution at every press. The rotor also turns over its neighbour as the window leaves a turnover
+      letter, so the two settings only stay in step if getting from one window letter to the other, the short way round the
+      rotor, crosses no turnover: then neither turns its neighbour over on the first presses without the other. When a notch
+      falls between them the one behind turns over first, within a few presses, and from then on the messages differ */
+pub fn ring_position_equivalent(ring_a: u16, pos_a: u16, ring_b: u16, pos_b: u16, notches: &str) -> bool {
+    let core = |ring: u16, position: u16| (position % 26 + 26 - ring % 26) % 26;
+    if core(ring_a, pos_a) != core(ring_b, pos_b) {
+        return false;
+    }
+    let forward = (pos_b % 26 + 26 - pos_a % 26) % 26;
+    let (behind, distance) = if forward <= 13 { (pos_a % 26, forward) } else { (pos_b % 26, 26 - forward) };
+    notches.chars().filter(|chr| is_cipher_letter(*chr)).all(|notch| {
+        (letter_index(notch) as u16 + 26 - behind) % 26 >= distance
+    })
+}
+
+/* function: distinguishing_input
+   inputs: &EnigmaMachines holding the two keys to tell apart, each at the position its message would start from
+           usize giving the longest input worth trying
+   output: Option containing the shortest run of one repeated letter that the two machines encipher differently, or None if no
+      run of up to max_len letters does
+   limitations: Only repeated letters are tried, so a shorter mixed input may exist. Neither machine is changed, the search runs
+      on copies of them
+   algorithm: an Enigma enciphers each letter as it is pressed, so a run of one letter first gives different output at the
+      press where the two keys first encipher it differently. Each letter is fed to copies of both machines one press at a time
+      until the outputs differ, and the earliest such press over all the letters gives the length of the run */
+pub fn distinguishing_input(a: &EnigmaMachine, b: &EnigmaMachine, max_len: usize) -> Option<String> {
+    let mut shortest: Option<(char, usize)> = None;
+    for letter in (0..26).map(index_letter) {
+        let limit = shortest.map_or(max_len, |(_, length)| length - 1);
+        let (mut first, mut second) = (a.clone(), b.clone());
+        let pressed = letter.to_string();
+        if let Some(length) = (1..=limit).find(|_| first.transform(&pressed) != second.transform(&pressed)) {
+            shortest = Some((letter, length));
+        }
+    }
+    shortest.map(|(letter, length)| letter.to_string().repeat(length))
+}
+
+/* function: letter_map_over_positions
+   inputs: &EnigmaMachine to examine
+           char holding the uppercase letter to follow
+   output: Vec of 17576 pairs, each a Position and the letter that the given letter lights with the rotors standing there, from
+      ('A', 'A', 'A') to ('Z', 'Z', 'Z') with the rightmost rotor changing fastest
+   limitations: The machine is not changed, the positions are set on a copy of it. Every combination of window letters is listed,
+      including the ones that double stepping means a machine never reaches by pressing keys
+   algorithm: the copy is set to each position in turn with set_message_key, and current_lamp gives the lamp without stepping, so
+      each entry is the substitution used by a key press that leaves the rotors at that position */
+pub fn letter_map_over_positions(machine: &EnigmaMachine, letter: char) -> Vec<(Position, char)> {
+    let mut copy = machine.clone();
+    let mut table: Vec<(Position, char)> = Vec::with_capacity(17576);
+    for index in 0..17576 {
+        let position = (index_letter(index / 676), index_letter(index / 26 % 26), index_letter(index % 26));
+        copy.set_message_key(position);
+        table.push((position, copy.current_lamp(letter)));
+    }
+    table
+}
+
+/* function: throughput
+   inputs: &mut EnigmaMachine to time, which is left at the positions it reaches after transforming the buffer
+           usize giving the number of letters to transform
+   output: f64 giving the letters transformed per second
+   limitations: This is a rough self-benchmark, timed once on whatever else the computer is doing, so repeat it and take the best
+      figure when comparing. Build in release mode for figures worth comparing. The clock is read to the nearest nanosecond, so very
+      small buffers give unreliable results
+   algorithm: a buffer of the given size is filled with the alphabet over and over, then timed through transform_bulk, the byte and
+      table path. Filling the buffer is not timed */
+pub fn throughput(machine: &mut EnigmaMachine, bytes: usize) -> f64 {
+    let input: Vec<u8> = (0..bytes).map(|index| index_letter(index % 26) as u8).collect();
+    let mut output = vec![0u8; bytes];
+    let start = Instant::now();
+    machine.transform_bulk(&input, &mut output);
+    let seconds = start.elapsed().as_secs_f64().max(1e-9);
+
+    bytes as f64 / seconds
+}
+
+// Pairs the lowest unpaired letter with each free letter in turn, saving each complete pairing
+fn pair_letters(pairing: &mut [Option<u8>], involutions: &mut Vec<Vec<u8>>) {
+    let first = match pairing.iter().position(|letter| letter.is_none()) {
+        Some(first) => first,
+        None => {
+            involutions.push(pairing.iter().map(|letter| letter.unwrap()).collect());
+            return;
+        }
+    };
+    for second in first + 1..pairing.len() {
+        if pairing[second].is_none() {
+            pairing[first] = Some(second as u8);
+            pairing[second] = Some(first as u8);
+            pair_letters(pairing, involutions);
+            pairing[second] = None;
+        }
+    }
+    pairing[first] = None;
+}
+
+// The number of steps between two rotor positions, going whichever way round the rotor is shorter
+fn circular_distance(first: u16, second: u16) -> u16 {
+    let difference = (first + 26 - second) % 26;
+    difference.min(26 - difference)
+}
+
+// Two swaps from the same substitution are either identical or share no letters
+fn swaps_conflict(first: (char, char), second: (char, char)) -> bool {
+    let shared = [first.0, first.1].iter().filter(|chr| **chr == second.0 || **chr == second.1).count();
+    shared == 1 || (shared == 2 && first.0 == first.1)
+}
+
+#[cfg(test)]
+use crate::config::MachineBuilder;
+#[cfg(test)]
+use crate::presets::ReflectorId;
+
+#[test]
+// Tests to see if a middle rotor step at a known letter is found from a plaintext/ciphertext pair
+fn test_estimate_turnovers() {
+    let mut my_enigma = EnigmaMachine::new("ABCDEFGHIJKLMNOPQRSTUVWXYZ".to_owned(),
+        "BDFHJLCPRTXVZNYEIWGAKMUSQO".to_owned(), 10, 0,
+        "AJDKSIRUXBLHWTMCQGZNPYFVOE".to_owned(), 2, 0,
+        "EKMFLGDQVZNTOWYHXUSPAIBRCJ".to_owned(), 12, 0,
+        "YRUHQSLDPXNGOKMIEBFZCWVJAT".to_owned()
+    );
+    let plaintext = "A".repeat(120);
+    let mut ciphertext = my_enigma.transform_message(plaintext[..60].to_owned());
+    // step the middle rotor by hand after the sixtieth letter, leaving the fast rotor where it is
+    my_enigma.set_rotor_positions(18, 3, 12);
+    ciphertext.push_str(&my_enigma.transform_message(plaintext[60..].to_owned()));
+    assert_eq!(vec![60], estimate_turnovers(&plaintext, &ciphertext));
+    assert!(estimate_turnovers(&plaintext[..60], &ciphertext[..60]).is_empty());
+}
+
+#[test]
+// Tests to see if indicators clustered around a ring setting suggest that setting first
+fn test_herivel_candidates() {
+    let indicators = vec![('G', 'K', 'S'), ('H', 'K', 'T'), ('F', 'J', 'S'), ('G', 'L', 'R'), ('H', 'J', 'S'),
+        ('G', 'K', 'U'), ('Q', 'B', 'X'), ('A', 'Z', 'M'), ('F', 'L', 'S'), ('N', 'W', 'D')];
+    let candidates = herivel_candidates(&indicators);
+    assert_eq!(('G', 'K', 'S'), candidates[0]);
+    assert!(herivel_candidates(&[('A', 'B', 'C'), ('N', 'O', 'P')]).is_empty());
+    // a large cluster sums more distance than a u16 holds at the positions three letters off every rotor
+    assert_eq!(('G', 'K', 'S'), herivel_candidates(&[('G', 'K', 'S'); 7500])[0]);
+}
+
+#[test]
+// Tests to see if indicators repeating a letter three places apart are found as females, and no others
+fn test_find_females() {
+    let indicators: Vec<(String, String)> = [("RTJ", "WAHWIK"), ("DQY", "PCOXMB"), ("HPN", "RJL WDL"), ("KTL", "ZXAMPQ"),
+        ("GYB", "AKLANV"), ("VUE", "EEXBYZ"), ("BNC", "wahwik"), ("MIA", "AAAAA")]
+        .iter()
+        .map(|(ground, indicator)| (ground.to_string(), indicator.to_string()))
+        .collect();
+    assert_eq!(vec![0, 2, 4], find_females(&indicators));
+    assert!(find_females(&[]).is_empty());
+}
+
+#[test]
+// Tests to see if the key space of the wartime Enigma I matches the commonly cited figure of about 10^23
+fn test_keyspace_size() {
+    let keyspace = keyspace_size(10, 5);
+    assert!(keyspace > 1.0e22 && keyspace < 1.0e24);
+    assert_eq!(150_738_274_937_250.0, keyspace_size(10, 3) / (6.0 * 26f64.powi(5)));
+    assert_eq!(0.0, keyspace_size(14, 5));
+}
+
+#[test]
+// Tests to see if choosing three of the five Enigma I rotors gives all 60 distinct rotor orders
+fn test_rotor_orders() {
+    let available = [RotorId::I, RotorId::II, RotorId::III, RotorId::IV, RotorId::V];
+    let orders = rotor_orders(&available, 3);
+    assert_eq!(60, orders.len());
+    let mut distinct = orders.clone();
+    distinct.sort();
+    distinct.dedup();
+    assert_eq!(60, distinct.len());
+    assert!(orders.iter().all(|order| order.len() == 3 && order[0] != order[1] && order[1] != order[2] && order[0] != order[2]));
+    assert!(rotor_orders(&available[..2], 3).is_empty());
+}
+
+#[test]
+// Tests to see if two plugboard cables are recovered from a crib given the right rotor settings
+fn test_recover_plugboard() {
+    let crib = "WETTERVORHERSAGEBISKAYAXNEBELXSICHTWEITEVIER";
+    let builder = MachineBuilder::new().rings([1, 12, 4]).positions([7, 20, 3]);
+    let mut sender = builder.clone().plugs(&[('E', 'Q'), ('R', 'W')]).build().unwrap();
+    let ciphertext = sender.transform_message(crib.to_owned());
+    let machine = builder.build().unwrap();
+    assert_eq!(Some(vec![('E', 'Q'), ('R', 'W')]), recover_plugboard(&machine, &ciphertext, crib));
+    assert_eq!(None, recover_plugboard(&machine, &ciphertext, "WETTERVORHERSAGEBISKAYAXNEBELXSICHTWEITEFUNF"));
+}
+
+#[test]
+// Tests to see if cycle lengths are found for a known permutation, and come in matching pairs for a composition of two positions
+fn test_permutation_cycles() {
+    let mut perm: [u8; 26] = core::array::from_fn(|index| index as u8);
+    perm[0] = 1;
+    perm[1] = 2;
+    perm[2] = 0;
+    perm[3] = 4;
+    perm[4] = 3;
+    let mut expected = vec![1; 21];
+    expected.extend([2, 3]);
+    assert_eq!(expected, permutation_cycles(&perm));
+
+    let mut my_enigma = MachineBuilder::new().positions([0, 5, 17]).plugs(&[('A', 'V'), ('B', 'S')]).build().unwrap();
+    my_enigma.step(1);
+    let first = my_enigma.substitution();
+    my_enigma.step(3);
+    let fourth = my_enigma.substitution();
+    let cycles = permutation_cycles(&compose_substitutions(&first, &fourth));
+    assert_eq!(26, cycles.iter().sum::<usize>());
+    for pair in cycles.chunks(2) {
+        assert_eq!(pair[0], pair[1]);
+    }
+}
+
+#[test]
+// Tests to see if genuine English sorts ahead of random letters and of text with no letters at all
+fn test_compare() {
+    let english = "IT WAS THE BEST OF TIMES IT WAS THE WORST OF TIMES IT WAS THE AGE OF WISDOM";
+    let random = "QXZJV KWPBQ ZJXVM QKZXW JVPQZ XKJWV BQZXJ MVKQP ZXJWQ VKZJX";
+    assert_eq!(Ordering::Less, compare(english, random));
+    assert_eq!(Ordering::Greater, compare(random, english));
+    let mut candidates = vec!["", random, english];
+    candidates.sort_by(|a, b| compare(a, b));
+    assert_eq!(vec![english, random, ""], candidates);
+}
+
+#[test]
+// Tests to see if English is rejected while random letters and real Enigma ciphertext pass, and a crib that lines up is refused
+fn test_looks_like_enigma() {
+    let english = "IT WAS THE BEST OF TIMES IT WAS THE WORST OF TIMES IT WAS THE AGE OF WISDOM IT WAS THE AGE OF FOOLISHNESS";
+    assert!(!looks_like_enigma(english));
+    let mut rng = XorShift::new(0x1234_5678);
+    let random: String = (0..200).map(|_| index_letter(rng.below(26) as usize)).collect();
+    assert!(looks_like_enigma(&random));
+    // the ciphertext of the example in the 1930 Enigma I operating manual
+    let ciphertext = "GCDSE AHUGW TQGRK VLFGX UCALX VYMIG MMNMF DXTGN VHVRM MEVOU YFZSL RHDRR XFJWC FHUHM UNZEF RDISI KBGPM YVXUZ";
+    assert!(looks_like_enigma(ciphertext));
+    assert!(looks_like_enigma_with_crib(ciphertext, "FEINDLIQEINFANTERIEKOLONNE"));
+    assert!(!looks_like_enigma_with_crib(ciphertext, "GC"));
+    assert!(!looks_like_enigma("QXZJV"));
+}
+
+#[test]
+// Tests to see if English scores well above the same letters scrambled under the trigram model, and if short text scores 0
+fn test_ngram_score() {
+    let english = "IT WAS THE BEST OF TIMES IT WAS THE WORST OF TIMES IT WAS THE AGE OF WISDOM";
+    let scrambled = "SGTHE TIAEE AEWOT IMFWI MSTFT SRTWO WETAA EHOSD HOTIM FIWSO TSSSB TEI";
+    assert!(ngram_score(english, 3) > ngram_score(scrambled, 3) + 10.0);
+    assert!(ngram_score(english, 2) > ngram_score(scrambled, 2));
+    assert!((ngram_score(english, 1) - ngram_score(scrambled, 1)).abs() < 1e-9);
+    assert_eq!(0.0, ngram_score("TH", 3));
+    assert_eq!(0.0, ngram_score(english, 4));
+}
+
+#[test]
+// Tests to see if the involutions of small alphabets number (n - 1)(n - 3)...1, and are each distinct and free of fixed points
+fn test_all_involutions() {
+    assert_eq!(vec![vec![1, 0, 3, 2], vec![2, 3, 0, 1], vec![3, 2, 1, 0]], all_involutions(4));
+    assert_eq!(15, all_involutions(6).len());
+    let involutions = all_involutions(8);
+    assert_eq!(105, involutions.len());
+    for (index, involution) in involutions.iter().enumerate() {
+        assert!(involution.iter().enumerate().all(|(letter, pair)| *pair as usize != letter && involution[*pair as usize] as usize == letter));
+        assert!(!involutions[index + 1..].contains(involution));
+    }
+    assert!(all_involutions(5).is_empty());
+    assert_eq!(vec![Vec::<u8>::new()], all_involutions(0));
+}
+
+#[test]
+// Tests to see if the ring setting is found from window and core positions, and if a machine at those cores enciphers the same
+fn test_infer_ring_setting() {
+    let observations = [(('S', 'X', 'T'), ('R', 'D', 'I')), (('A', 'A', 'A'), ('Z', 'G', 'P')), (('Q', 'E', 'V'), ('P', 'K', 'K'))];
+    assert_eq!(Some((1, 20, 11)), infer_ring_setting(&observations));
+    let windowed = MachineBuilder::new().rings([1, 20, 11]).positions([18, 23, 19]).build().unwrap();
+    let cored = MachineBuilder::new().positions([17, 3, 8]).build().unwrap();
+    assert_eq!(windowed.substitution(), cored.substitution());
+    assert_eq!(None, infer_ring_setting(&[observations[0], (('S', 'X', 'T'), ('R', 'D', 'J'))]));
+    assert_eq!(None, infer_ring_setting(&[]));
+}
+
+#[test]
+// Tests to see if turning ring and rightmost rotor together is equivalent until a notch falls between the two window letters
+fn test_ring_position_equivalent() {
+    let message = "THE TIME HAS COME";
+    let transform = |rings: [u16; 3], positions: [u16; 3]| MachineBuilder::new().rings(rings).positions(positions).build().unwrap().transform(message);
+    // rotor III turns over as its window leaves V, so moving from A to C crosses no notch
+    assert!(ring_position_equivalent(0, 0, 2, 2, "V"));
+    assert_eq!(transform([0, 0, 0], [0, 0, 0]), transform([0, 0, 2], [0, 0, 2]));
+    // moving from U to X carries the window past V
+    assert!(!ring_position_equivalent(0, 20, 3, 23, "V"));
+    assert_ne!(transform([0, 0, 0], [0, 0, 20]), transform([0, 0, 3], [0, 0, 23]));
+    assert!(!ring_position_equivalent(3, 23, 0, 20, "V"));
+    assert!(!ring_position_equivalent(0, 0, 1, 2, "V"));
+}
+
+#[test]
+// Tests to see if keys that differ only in ring setting are told apart once the fast rotor crosses its notch
+fn test_distinguishing_input() {
+    // rotor III is turned and set two places on, so the two keys agree until the second turns the middle rotor at its 20th press
+    let a = MachineBuilder::new().rings([0, 0, 0]).positions([0, 0, 0]).build().unwrap();
+    let b = MachineBuilder::new().rings([0, 0, 2]).positions([0, 0, 2]).build().unwrap();
+    assert_eq!(None, distinguishing_input(&a, &b, 19));
+    let input = distinguishing_input(&a, &b, 100).unwrap();
+    assert_eq!(20, input.len());
+    assert_ne!(a.clone().transform(&input), b.clone().transform(&input));
+    assert_eq!(None, distinguishing_input(&a, &a, 100));
+}
+
+#[test]
+// Tests to see if the table covers every position, never maps the letter to itself and agrees with the machine's own key presses
+fn test_letter_map_over_positions() {
+    let mut machine = MachineBuilder::new().rings([1, 12, 4]).positions([0, 5, 20]).plugs(&[('A', 'R'), ('G', 'K')]).build().unwrap();
+    let table = letter_map_over_positions(&machine, 'A');
+    assert_eq!(17576, table.len());
+    assert!(table.iter().all(|(_, output)| *output != 'A'));
+    assert_eq!(('A', 'A', 'A'), table[0].0);
+    assert_eq!(('Z', 'Z', 'Z'), table[17575].0);
+    assert_eq!(('A', 'F', 'U'), machine.window_position());
+    for _ in 0..100 {
+        let output = machine.press_key('A');
+        let (left, middle, right) = machine.window_position();
+        let index = letter_index(left) * 676 + letter_index(middle) * 26 + letter_index(right);
+        assert_eq!((machine.window_position(), output), table[index]);
+    }
+}
+
+#[test]
+// Tests to see if rotor III's wiring is recovered from enough pairs in the fast slot, and not guessed from too few
+fn test_recover_wiring() {
+    let mut machine = MachineBuilder::new().rotors(RotorId::I, RotorId::II, RotorId::III).rings([1, 12, 4]).positions([0, 5, 20])
+        .plugs(&[('A', 'R'), ('G', 'K'), ('O', 'X')]).build().unwrap();
+    let known = MachineConfig { rotors: [RotorId::I, RotorId::II, RotorId::III], rings: [1, 12, 4], positions: [0, 0, 0],
+        plugboard: vec![('A', 'R'), ('G', 'K'), ('O', 'X')], reflector: ReflectorId::B, greek: None };
+    let pairs: Vec<(char, char, Position)> = (0..400).map(|press| {
+        let plain = index_letter(press * 7 % 26 + press / 26 % 2);
+        let cipher = machine.transform(&plain.to_string()).chars().next().unwrap();
+        (plain, cipher, machine.window_position())
+    }).collect();
+    assert_eq!(Some(RotorId::III.wiring().to_owned()), recover_wiring(&known, 2, &pairs));
+    assert_eq!(None, recover_wiring(&known, 2, &pairs[..5]));
+}
+
+#[test]
+// Tests to see if the self-benchmark gives a sensible rate and moves the machine on as the transform would
+fn test_throughput() {
+    let mut machine = MachineBuilder::new().build().unwrap();
+    let rate = throughput(&mut machine, 100_000);
+    assert!(rate.is_finite() && rate > 0.0);
+    let mut expected = MachineBuilder::new().build().unwrap();
+    expected.step(100_000);
+    assert_eq!(expected.rotor_positions(), machine.rotor_positions());
+}
+
+#[test]
+// Tests to see if generated text has close to the English share of each letter, and the index of coincidence of English
+fn test_sample_english() {
+    let text = sample_english(20_000, &mut XorShift::new(26));
+    assert_eq!(20_000, text.len());
+    let mut counts = [0usize; 26];
+    for letter in text.chars() {
+        counts[letter_index(letter)] += 1;
+    }
+    for (count, frequency) in counts.iter().zip(ENGLISH_FREQUENCIES) {
+        assert!((*count as f64 / 20_000.0 - frequency).abs() < 0.006);
+    }
+    assert!((index_of_coincidence(&text) - 0.066).abs() < 0.003);
+    assert_eq!(text, sample_english(20_000, &mut XorShift::new(26)));
+}
+
+#[test]
+// Tests to see if a machine behaves the same as its clone, even when one has been used, but not as one with a ring turned
+fn test_behaviorally_equal() {
+    let mut machine = MachineBuilder::new().rings([7, 9, 2]).positions([12, 2, 10]).build().unwrap();
+    let mut copy = machine.clone();
+    copy.transform("THE TIME HAS COME");
+    assert!(behaviorally_equal(&mut machine, &mut copy, 2000));
+    let mut turned = MachineBuilder::new().rings([7, 9, 3]).positions([12, 2, 10]).build().unwrap();
+    assert!(!behaviorally_equal(&mut machine, &mut turned, 2000));
+}
+
+#[test]
+// Tests to see if only the rotor the message was sent with fits the crib in the fast slot
+fn test_search_fast_rotor() {
+    let builder = MachineBuilder::new().rotors(RotorId::II, RotorId::IV, RotorId::V).rings([1, 20, 11]).positions([0, 11, 4])
+        .plugs(&[('A', 'V'), ('B', 'S'), ('C', 'G'), ('D', 'L'), ('F', 'U'), ('H', 'Z')]);
+    let crib = "WETTERVORHERSAGEBISKAYA";
+    let ciphertext = builder.build().unwrap().transform(&format!("{}XKEINEAENDERUNG", crib));
+    let config = "II-IV-I BUL ALE UKW-B".parse::<MachineConfig>().unwrap();
+    let candidates = [RotorId::I, RotorId::III, RotorId::V, RotorId::VI, RotorId::VII, RotorId::VIII];
+    assert_eq!(vec![RotorId::V], search_fast_rotor(&config, &candidates, &ciphertext, crib));
+}
@@ -0,0 +1,70 @@
+/* The async_io module holds the traits EnigmaMachine::transform_async reads and writes through. They are this crate's own poll
+   traits, named PollRead and PollWrite so they are not taken for the AsyncRead and AsyncWrite of tokio or futures-io, which the
+   crate cannot depend on. They read into and write from plain byte slices, and are implemented here only for byte slices, Vec<u8>
+   and mutable references. A tokio stream cannot be passed in directly: it must be wrapped in a type of the caller's own that
+   implements PollRead or PollWrite by forwarding each poll. It is only built with the async feature. */
+use std::io;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+/* PollRead is implemented by sources of bytes that may not have them ready yet */
+pub trait PollRead {
+    /* function: poll_read
+       input: Context of the task waiting on the read, and a &mut [u8] to read into
+       output: Poll that is Ready with the number of bytes read, 0 at the end of the stream, or Pending after arranging for the task
+          to be woken when bytes arrive */
+    fn poll_read(self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &mut [u8]) -> Poll<io::Result<usize>>;
+}
+
+/* PollWrite is implemented by sinks of bytes that may not be able to take them yet */
+pub trait PollWrite {
+    /* function: poll_write
+       input: Context of the task waiting on the write, and a &[u8] to write
+       output: Poll that is Ready with the number of bytes written, or Pending after arranging for the task to be woken when the
+          sink can take more */
+    fn poll_write(self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &[u8]) -> Poll<io::Result<usize>>;
+
+    /* function: poll_flush
+       input: Context of the task waiting on the flush
+       output: Poll that is Ready once everything written has been passed on */
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>>;
+}
+
+/* A byte slice reads as a stream that is always ready, moving forward past the bytes read */
+impl PollRead for &[u8] {
+    fn poll_read(mut self: Pin<&mut Self>, _cx: &mut Context<'_>, buf: &mut [u8]) -> Poll<io::Result<usize>> {
+        let count = buf.len().min(self.len());
+        buf[..count].copy_from_slice(&self[..count]);
+        *self = &self[count..];
+        Poll::Ready(Ok(count))
+    }
+}
+
+/* A Vec<u8> writes as a sink that is always ready, appending the bytes written */
+impl PollWrite for Vec<u8> {
+    fn poll_write(self: Pin<&mut Self>, _cx: &mut Context<'_>, buf: &[u8]) -> Poll<io::Result<usize>> {
+        self.get_mut().extend_from_slice(buf);
+        Poll::Ready(Ok(buf.len()))
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Poll::Ready(Ok(()))
+    }
+}
+
+/* A mutable reference reads and writes through to the stream it refers to, so a stream can be lent rather than given away */
+impl<T: PollRead + Unpin + ?Sized> PollRead for &mut T {
+    fn poll_read(mut self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &mut [u8]) -> Poll<io::Result<usize>> {
+        Pin::new(&mut **self).poll_read(cx, buf)
+    }
+}
+
+impl<T: PollWrite + Unpin + ?Sized> PollWrite for &mut T {
+    fn poll_write(mut self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &[u8]) -> Poll<io::Result<usize>> {
+        Pin::new(&mut **self).poll_write(cx, buf)
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut **self).poll_flush(cx)
+    }
+}
@@ -0,0 +1,97 @@
+/* The base64 module encodes bytes as the standard base64 alphabet of RFC 4648, padded with '=', for sending ciphertext over
+   channels that only carry a limited set of characters. It is only built with the base64 feature. */
+use std::fmt;
+
+const SYMBOLS: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+/* A Base64Error describes why text could not be decoded:
+     InvalidLength holds the length of text that is not a whole number of four character blocks
+     InvalidCharacter holds a character that is not in the base64 alphabet, or padding that is not at the end
+     InvalidUtf8 means the decoded bytes were expected to be text but are not valid UTF-8 */
+#[derive(Debug, PartialEq, Eq)]
+pub enum Base64Error {
+    InvalidLength(usize),
+    InvalidCharacter(char),
+    InvalidUtf8
+}
+
+impl fmt::Display for Base64Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Base64Error::InvalidLength(length) => write!(f, "base64 text of length {} is not a multiple of 4", length),
+            Base64Error::InvalidCharacter(chr) => write!(f, "'{}' is not a base64 character here", chr),
+            Base64Error::InvalidUtf8 => write!(f, "decoded bytes are not valid UTF-8")
+        }
+    }
+}
+
+impl std::error::Error for Base64Error {}
+
+/* function: encode
+   input: &[u8] containing the bytes to encode
+   output: String containing the bytes in base64, e.g.: "TWFu" for b"Man"
+   limitations: none obvious at this time
+   algorithm: each block of three bytes is split into four six bit values, each written as one symbol. A final block of one or two
+      bytes is padded with zero bits and then '=' to four symbols */
+pub fn encode(bytes: &[u8]) -> String {
+    let mut encoded = String::with_capacity(bytes.len().div_ceil(3) * 4);
+    for block in bytes.chunks(3) {
+        let bits = block.iter().enumerate().fold(0u32, |bits, (index, byte)| bits | (*byte as u32) << (16 - 8 * index));
+        for index in 0..4 {
+            if index <= block.len() {
+                encoded.push(SYMBOLS[(bits >> (18 - 6 * index) & 0x3f) as usize] as char);
+            } else {
+                encoded.push('=');
+            }
+        }
+    }
+
+    encoded
+}
+
+/* function: decode
+   input: &str containing base64 text, with its padding
+   output: Result containing the decoded bytes, or a Base64Error describing the first problem found
+   limitations: Whitespace is not skipped, and unpadded text is rejected
+   algorithm: the reverse of encode. Each block of four symbols gives three bytes, less one for each '=' at the end of the text */
+pub fn decode(text: &str) -> Result<Vec<u8>, Base64Error> {
+    let symbols: Vec<char> = text.chars().collect();
+    if !symbols.len().is_multiple_of(4) {
+        return Err(Base64Error::InvalidLength(symbols.len()));
+    }
+    let padding = symbols.iter().rev().take(2).filter(|chr| **chr == '=').count();
+    let mut bytes: Vec<u8> = Vec::with_capacity(symbols.len() / 4 * 3);
+    for (index, chr) in symbols[..symbols.len() - padding].iter().enumerate() {
+        let value = SYMBOLS.iter().position(|symbol| *symbol as char == *chr).ok_or(Base64Error::InvalidCharacter(*chr))? as u8;
+        match index % 4 {
+            0 => bytes.push(value << 2),
+            1 => {
+                *bytes.last_mut().unwrap() |= value >> 4;
+                bytes.push(value << 4);
+            }
+            2 => {
+                *bytes.last_mut().unwrap() |= value >> 2;
+                bytes.push(value << 6);
+            }
+            _ => *bytes.last_mut().unwrap() |= value
+        }
+    }
+    if padding > 0 {
+        bytes.pop();
+    }
+
+    Ok(bytes)
+}
+
+#[test]
+// Tests to see if the examples of RFC 4648 encode and decode, and if text that is not base64 is rejected
+fn test_base64() {
+    let examples = [("", ""), ("f", "Zg=="), ("fo", "Zm8="), ("foo", "Zm9v"), ("foob", "Zm9vYg=="), ("fooba", "Zm9vYmE="), ("foobar", "Zm9vYmFy")];
+    for (plain, encoded) in examples {
+        assert_eq!(encoded, encode(plain.as_bytes()));
+        assert_eq!(Ok(plain.as_bytes().to_vec()), decode(encoded));
+    }
+    assert_eq!(Err(Base64Error::InvalidLength(3)), decode("Zm9"));
+    assert_eq!(Err(Base64Error::InvalidCharacter('!')), decode("Zm9!"));
+    assert_eq!(Err(Base64Error::InvalidCharacter('=')), decode("Z=9v"));
+}
@@ -0,0 +1,659 @@
+/* The config module describes a complete machine setting, the way a key sheet would list it, and builds EnigmaMachines from it. */
+use std::fmt;
+use std::str::FromStr;
+use crate::alphabet::{index_letter, is_cipher_letter, letter_index};
+use crate::enigma::enigma_machine::{EnigmaError, EnigmaMachine};
+use crate::enigma::enigma_wheel::{Enigma, EnigmaWheel};
+use crate::enigma::plugboard::Plugboard;
+use crate::enigma::reflector::Reflector;
+use crate::json::{self, JsonValue};
+use crate::presets::{Model, ReflectorId, RotorId};
+
+/* An ImportError describes why settings exported by another tool, or written out as a one line spec, could not be imported:
+     Json holds a description of a document that is not valid JSON
+     MissingField holds the name of a required field that was not present
+     InvalidField holds the name of a field whose value could not be understood, and that value
+     UnknownRotor holds a rotor name that does not match any of the preset rotors
+     UnknownReflector holds a reflector name that does not match any of the preset reflectors */
+#[derive(Debug, PartialEq, Eq)]
+pub enum ImportError {
+    Json(String),
+    MissingField(&'static str),
+    InvalidField { field: &'static str, value: String },
+    UnknownRotor(String),
+    UnknownReflector(String)
+}
+
+impl fmt::Display for ImportError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ImportError::Json(message) => write!(f, "invalid JSON: {}", message),
+            ImportError::MissingField(field) => write!(f, "missing field '{}'", field),
+            ImportError::InvalidField { field, value } => write!(f, "invalid value for '{}': {}", field, value),
+            ImportError::UnknownRotor(name) => write!(f, "unknown rotor '{}'", name),
+            ImportError::UnknownReflector(name) => write!(f, "unknown reflector '{}'", name)
+        }
+    }
+}
+
+impl std::error::Error for ImportError {}
+
+/* A MachineConfig is a complete setting for a three rotor Enigma machine, or a four rotor M4. It contains the following:
+     An array of three RotorIds called rotors giving the rotor order, leftmost (slow) rotor first
+     An array of three u16s called rings giving the ring settings of those rotors, where 0 is A
+     An array of three u16s called positions giving the starting window positions of those rotors, where 0 is A
+     A Vec of letter pairs called plugboard giving the plugboard cables
+     A ReflectorId called reflector naming the reflector
+     An Option called greek holding the Greek rotor of an M4 with its ring setting and window position, in that order
+   A MachineConfig has the following functions available to it:
+     build is a function that returns an EnigmaMachine set up as the config describes
+     validate is a function that checks the whole config and reports every problem found, before the config is used
+     from_key_sheet is a function that reads the rings, positions and plugboard written as letters, as the enigma! macro takes them
+     from_simulator_json is a function that imports the settings exported as JSON by online Enigma simulators */
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MachineConfig {
+    pub rotors: [RotorId; 3],
+    pub rings: [u16; 3],
+    pub positions: [u16; 3],
+    pub plugboard: Vec<(char, char)>,
+    pub reflector: ReflectorId,
+    pub greek: Option<(RotorId, u16, u16)>
+}
+
+impl MachineConfig {
+    /* function: build
+       input: none
+       output: EnigmaMachine with the rotors, rings, positions, plugboard and reflector of the config, and the rotors' turnovers set
+       limitations: The plugboard pairs are not checked, so a letter used in two pairs is connected to whichever comes last */
+    pub fn build(&self) -> EnigmaMachine {
+        self.build_with_entry_wheel(None)
+    }
+
+    /* function: validate
+       input: none
+       output: Result that is Ok if the config would build a working machine, or a Vec of every EnigmaError found
+       limitations: none obvious at this time
+       algorithm: every part is checked rather than stopping at the first problem, so a key sheet can be corrected in one pass. The
+          rotor wirings must be permutations of the alphabet, the reflector must pair every letter with another, no letter may be
+          plugged twice, and the rings and positions must each name a letter. When there is a Greek rotor its reflector is the thin
+          reflector it sits beside, which is checked the same way */
+    pub fn validate(&self) -> Result<(), Vec<EnigmaError>> {
+        let mut errors: Vec<EnigmaError> = vec![];
+        let mut wheels = vec![("leftmost rotor", self.rotors[0]), ("middle rotor", self.rotors[1]), ("rightmost rotor", self.rotors[2])];
+        if let Some((greek, _, _)) = self.greek {
+            wheels.push(("greek rotor", greek));
+        }
+        for (part, rotor) in wheels {
+            if let Err(error) = EnigmaWheel::check_wiring(rotor.wiring()) {
+                errors.push(EnigmaError::InvalidWheel { part, error });
+            }
+        }
+        if let Err(error) = Reflector::try_new(self.reflector.wiring()) {
+            errors.push(EnigmaError::InvalidReflector(error));
+        }
+        let pairs: Vec<String> = self.plugboard.iter().map(|(first, second)| [*first, *second].iter().collect()).collect();
+        if let Err(error) = Plugboard::from_pairs(&pairs.join(" ")) {
+            errors.push(EnigmaError::InvalidPlugboard(error));
+        }
+        errors.extend(self.settings_out_of_range());
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+
+    // Gives an OutOfRange error for each ring setting or position, the Greek rotor's included, that does not name a letter
+    fn settings_out_of_range(&self) -> Vec<EnigmaError> {
+        let mut settings: Vec<(&'static str, u16)> = self.rings.iter().map(|ring| ("ring", *ring))
+            .chain(self.positions.iter().map(|position| ("position", *position)))
+            .collect();
+        if let Some((_, ring, position)) = self.greek {
+            settings.extend([("ring", ring), ("position", position)]);
+        }
+        settings.into_iter()
+            .filter(|(_, value)| *value >= 26)
+            .map(|(field, value)| EnigmaError::OutOfRange { field, value })
+            .collect()
+    }
+
+    /* function: build_with_entry_wheel
+       input: Option containing the wiring of an entry wheel that is not wired in alphabetical order
+       output: EnigmaMachine as build describes, with the entry wheel wired between the plugboard and the rightmost rotor
+       limitations: none obvious at this time
+       algorithm: the Greek rotor never steps, so together with the thin reflector it acts as a single fixed reflector. The signal
+          is traced through the Greek rotor, the reflector and back for each contact to find that reflector's wiring. In the same way
+          the entry wheel never moves, so it is folded into the plugboard wiring */
+    fn build_with_entry_wheel(&self, entry_wheel: Option<&str>) -> EnigmaMachine {
+        let mut plugboard: Vec<char> = ('A'..='Z').collect();
+        for (first, second) in &self.plugboard {
+            plugboard[letter_index(*first)] = *second;
+            plugboard[letter_index(*second)] = *first;
+        }
+        if let Some(entry_wheel) = entry_wheel {
+            let contacts: Vec<char> = entry_wheel.chars().collect();
+            plugboard = plugboard.iter().map(|letter| contacts[letter_index(*letter)]).collect();
+        }
+        let reflector = match self.greek {
+            Some((greek, ring, position)) => {
+                let greek = EnigmaWheel::new(greek.wiring().to_owned(), position, ring);
+                let thin = EnigmaWheel::new(self.reflector.wiring().to_owned(), 0, 0);
+                (1..=26u16).map(|code| {
+                    let code = greek.left_to_right(thin.right_to_left(greek.right_to_left(code)));
+                    index_letter(code as usize + 25)
+                }).collect()
+            }
+            None => self.reflector.wiring().to_owned()
+        };
+        let [left, middle, right] = self.rotors;
+        let mut machine = EnigmaMachine::new(plugboard.into_iter().collect(),
+            right.wiring().to_owned(), self.positions[2], self.rings[2],
+            middle.wiring().to_owned(), self.positions[1], self.rings[1],
+            left.wiring().to_owned(), self.positions[0], self.rings[0],
+            reflector
+        );
+        machine.set_turnover_letters(right.turnovers(), middle.turnovers(), left.turnovers());
+
+        machine
+    }
+
+    /* function: from_key_sheet
+       inputs: [RotorId; 3] giving the rotor order, leftmost first
+               &str containing the ring settings as three letters, leftmost first, e.g.: "FVN"
+               &str containing the start positions as three letters, leftmost first, e.g.: "QEV"
+               &str containing the plugboard as space separated letter pairs, e.g.: "AB CD"; this may be empty if no plugs are used
+               ReflectorId naming the reflector
+       output: Result containing the MachineConfig, or an ImportError naming the first field that could not be read
+       limitations: Only three rotor machines are supported */
+    pub fn from_key_sheet(rotors: [RotorId; 3], rings: &str, positions: &str, plugs: &str, reflector: ReflectorId) -> Result<MachineConfig, ImportError> {
+        let letters = |field: &'static str, value: &str| {
+            let indices: Vec<u16> = value.chars().filter(|chr| is_cipher_letter(*chr)).map(|chr| letter_index(chr) as u16).collect();
+            match indices[..] {
+                [left, middle, right] if value.chars().count() == 3 => Ok([left, middle, right]),
+                _ => Err(ImportError::InvalidField { field, value: value.to_owned() })
+            }
+        };
+
+        Ok(MachineConfig {
+            rotors,
+            rings: letters("rings", rings)?,
+            positions: letters("positions", positions)?,
+            plugboard: plug_pairs(plugs.split_whitespace())?,
+            reflector,
+            greek: None
+        })
+    }
+
+    /* function: from_simulator_json
+       input: &str containing the settings exported by an online Enigma simulator
+       output: Result containing the imported MachineConfig, or an ImportError describing the first problem found
+       limitations: Only three rotor machines are supported. The document must be a JSON object with these fields, and any other
+          fields are ignored:
+            "rotors"     an array of three rotor numerals, leftmost first, e.g.: ["I", "II", "III"]
+            "rings"      an array of three ring settings counted from 1, leftmost first, e.g.: [1, 1, 1]
+            "positions"  a string of three window letters, leftmost first, e.g.: "MCK"
+            "plugboard"  a string of space separated letter pairs, e.g.: "AV BS CG"; this field may be left out if no plugs are used
+            "reflector"  a reflector name, with or without the UKW- prefix, e.g.: "UKW-B" */
+    pub fn from_simulator_json(text: &str) -> Result<MachineConfig, ImportError> {
+        let document = json::parse(text).map_err(ImportError::Json)?;
+
+        let names = match document.get("rotors") {
+            Some(JsonValue::Array(names)) if names.len() == 3 => names,
+            Some(value) => return Err(invalid("rotors", value)),
+            None => return Err(ImportError::MissingField("rotors"))
+        };
+        let mut rotors = [RotorId::I; 3];
+        for (slot, name) in names.iter().enumerate() {
+            rotors[slot] = match name {
+                JsonValue::Str(name) => RotorId::from_name(name).ok_or_else(|| ImportError::UnknownRotor(name.clone()))?,
+                value => return Err(invalid("rotors", value))
+            };
+        }
+
+        let settings = match document.get("rings") {
+            Some(JsonValue::Array(settings)) if settings.len() == 3 => settings,
+            Some(value) => return Err(invalid("rings", value)),
+            None => return Err(ImportError::MissingField("rings"))
+        };
+        let mut rings = [0u16; 3];
+        for (slot, setting) in settings.iter().enumerate() {
+            rings[slot] = match setting {
+                JsonValue::Number(number) if number.fract() == 0.0 && (1.0..=26.0).contains(number) => *number as u16 - 1,
+                value => return Err(invalid("rings", value))
+            };
+        }
+
+        let positions = match document.get("positions") {
+            Some(JsonValue::Str(letters)) if letters.len() == 3 && letters.chars().all(is_cipher_letter) => {
+                let codes: Vec<u16> = letters.chars().map(|chr| letter_index(chr) as u16).collect();
+                [codes[0], codes[1], codes[2]]
+            }
+            Some(value) => return Err(invalid("positions", value)),
+            None => return Err(ImportError::MissingField("positions"))
+        };
+
+        let plugboard = match document.get("plugboard") {
+            Some(JsonValue::Str(pairs)) => plug_pairs(pairs.split_whitespace())?,
+            Some(value) => return Err(invalid("plugboard", value)),
+            None => vec![]
+        };
+
+        let reflector = match document.get("reflector") {
+            Some(JsonValue::Str(name)) => ReflectorId::from_name(name).ok_or_else(|| ImportError::UnknownReflector(name.clone()))?,
+            Some(value) => return Err(invalid("reflector", value)),
+            None => return Err(ImportError::MissingField("reflector"))
+        };
+
+        Ok(MachineConfig { rotors, rings, positions, plugboard, reflector, greek: None })
+    }
+}
+
+/* MachineConfig implements FromStr for a one line spec giving the rotors, rings, positions, plugboard and reflector separated by
+   spaces, e.g.: "III-I-II FVN QEV AB.CD.EF UKW-B"
+     the rotors are named leftmost first and joined by dashes; a fourth rotor in front names the Greek rotor of an M4
+     the rings and positions are given as letters, leftmost first, with a letter for the Greek rotor if there is one
+     the plugboard pairs are joined by dots; this field may be left out if no plugs are used
+     the reflector is named with or without the UKW- prefix */
+impl FromStr for MachineConfig {
+    type Err = ImportError;
+
+    fn from_str(spec: &str) -> Result<MachineConfig, ImportError> {
+        let fields: Vec<&str> = spec.split_whitespace().collect();
+        let (rotor_field, ring_field, position_field, plug_field, reflector_field) = match fields[..] {
+            [rotors, rings, positions, plugs, reflector] => (rotors, rings, positions, plugs, reflector),
+            [rotors, rings, positions, reflector] => (rotors, rings, positions, "", reflector),
+            _ => return Err(ImportError::InvalidField { field: "spec", value: spec.to_owned() })
+        };
+
+        let mut rotors: Vec<RotorId> = vec![];
+        for name in rotor_field.split('-') {
+            rotors.push(RotorId::from_name(name).ok_or_else(|| ImportError::UnknownRotor(name.to_owned()))?);
+        }
+        if rotors.len() != 3 && rotors.len() != 4 {
+            return Err(ImportError::InvalidField { field: "rotors", value: rotor_field.to_owned() });
+        }
+        let letters = |field: &'static str, value: &str| {
+            if value.len() == rotors.len() && value.chars().all(is_cipher_letter) {
+                Ok(value.chars().map(|chr| letter_index(chr) as u16).collect::<Vec<u16>>())
+            } else {
+                Err(ImportError::InvalidField { field, value: value.to_owned() })
+            }
+        };
+        let rings = letters("rings", ring_field)?;
+        let positions = letters("positions", position_field)?;
+        let plugboard = plug_pairs(plug_field.split('.').filter(|pair| !pair.is_empty()))?;
+        let reflector = ReflectorId::from_name(reflector_field).ok_or_else(|| ImportError::UnknownReflector(reflector_field.to_owned()))?;
+
+        let first = rotors.len() - 3;
+        Ok(MachineConfig {
+            rotors: [rotors[first], rotors[first + 1], rotors[first + 2]],
+            rings: [rings[first], rings[first + 1], rings[first + 2]],
+            positions: [positions[first], positions[first + 1], positions[first + 2]],
+            plugboard,
+            reflector,
+            greek: if first == 1 { Some((rotors[0], rings[0], positions[0])) } else { None }
+        })
+    }
+}
+
+/* The enigma! macro declares a three rotor machine inline, with its settings laid out as on a key sheet, e.g.:
+     enigma!{ rotors: [III, I, II], rings: "FVN", pos: "QEV", plugs: "AB CD", reflector: B }
+   The rotors and reflector are named as the variants of RotorId and ReflectorId, so a name that does not exist fails to compile.
+   The letters are read as in MachineConfig::from_key_sheet, and the macro gives a Result containing the EnigmaMachine, or the
+   ImportError for letters that could not be read */
+#[macro_export]
+macro_rules! enigma {
+    { rotors: [$left:ident, $middle:ident, $right:ident], rings: $rings:expr, pos: $positions:expr, plugs: $plugs:expr, reflector: $reflector:ident $(,)? } => {
+        $crate::config::MachineConfig::from_key_sheet(
+            [$crate::presets::RotorId::$left, $crate::presets::RotorId::$middle, $crate::presets::RotorId::$right],
+            $rings,
+            $positions,
+            $plugs,
+            $crate::presets::ReflectorId::$reflector
+        ).map(|config| config.build())
+    };
+}
+
+/* EnigmaMachine implements FromStr for the same one line spec as MachineConfig, building the machine it describes */
+impl FromStr for EnigmaMachine {
+    type Err = ImportError;
+
+    fn from_str(spec: &str) -> Result<EnigmaMachine, ImportError> {
+        spec.parse::<MachineConfig>().map(|config| config.build())
+    }
+}
+
+/* A MachineBuilder assembles a MachineConfig one part at a time and builds the EnigmaMachine it describes. Parts that are not set
+   default to rotors I, II and III with rings and positions at A, no plugboard cables, no Greek rotor, and reflector B. A
+   MachineBuilder has the following functions available to it:
+     new is a constructor that returns a MachineBuilder holding the defaults
+     rotors, rings, positions, plugs, reflector and greek set the corresponding parts of the config, leftmost rotor first
+     max_plugs is a function that limits the number of plugboard cables the builder will accept
+     no_plugboard is a function that builds the machine without a plugboard, as on the commercial machines
+     model is a function that limits the rotors, reflector and plugboard to those the named model could be fitted with
+     build is a function that checks the parts against any limits and returns the EnigmaMachine they describe */
+#[derive(Clone, Debug)]
+pub struct MachineBuilder {
+    config: MachineConfig,
+    max_plugs: Option<usize>,
+    model: Option<Model>,
+    plugboard_fitted: bool
+}
+
+impl Default for MachineBuilder {
+    fn default() -> Self {
+        MachineBuilder::new()
+    }
+}
+
+impl MachineBuilder {
+    pub fn new() -> MachineBuilder {
+        MachineBuilder {
+            config: MachineConfig {
+                rotors: [RotorId::I, RotorId::II, RotorId::III],
+                rings: [0, 0, 0],
+                positions: [0, 0, 0],
+                plugboard: vec![],
+                reflector: ReflectorId::B,
+                greek: None
+            },
+            max_plugs: None,
+            model: None,
+            plugboard_fitted: true
+        }
+    }
+
+    pub fn rotors(mut self, left: RotorId, middle: RotorId, right: RotorId) -> MachineBuilder {
+        self.config.rotors = [left, middle, right];
+        self
+    }
+
+    pub fn rings(mut self, rings: [u16; 3]) -> MachineBuilder {
+        self.config.rings = rings;
+        self
+    }
+
+    pub fn positions(mut self, positions: [u16; 3]) -> MachineBuilder {
+        self.config.positions = positions;
+        self
+    }
+
+    pub fn plugs(mut self, pairs: &[(char, char)]) -> MachineBuilder {
+        self.config.plugboard = pairs.to_vec();
+        self
+    }
+
+    pub fn reflector(mut self, reflector: ReflectorId) -> MachineBuilder {
+        self.config.reflector = reflector;
+        self
+    }
+
+    /* function: greek
+       input: RotorId of the Greek rotor, followed by its ring setting and window position, where 0 is A
+       output: the MachineBuilder, so calls can be chained
+       limitations: the Greek rotor never steps, and is meant to be used with a thin reflector */
+    pub fn greek(mut self, rotor: RotorId, ring: u16, position: u16) -> MachineBuilder {
+        self.config.greek = Some((rotor, ring, position));
+        self
+    }
+
+    /* function: model
+       input: Model the machine is to be, e.g.: Model::M4
+       output: the MachineBuilder, so calls can be chained
+       limitations: the model is only checked when build is called */
+    pub fn model(mut self, model: Model) -> MachineBuilder {
+        self.model = Some(model);
+        self
+    }
+
+    /* function: max_plugs
+       input: usize giving the most plugboard cables the machine may use, e.g.: 10 for the standard wartime procedure
+       output: the MachineBuilder, so calls can be chained
+       limitations: the limit is only checked when build is called */
+    pub fn max_plugs(mut self, limit: usize) -> MachineBuilder {
+        self.max_plugs = Some(limit);
+        self
+    }
+
+    /* function: no_plugboard
+       input: none
+       output: the MachineBuilder, so calls can be chained
+       limitations: no plugboard cables may then be set, which is only checked when build is called
+       algorithm: the machine is built with no plugboard at all, rather than an identity plugboard, so the signal skips both
+          plugboard passes. A machine with an entry wheel keeps it, since its wiring is folded into the plugboard's place */
+    pub fn no_plugboard(mut self) -> MachineBuilder {
+        self.plugboard_fitted = false;
+        self
+    }
+
+    /* function: build
+       input: none
+       output: Result containing the EnigmaMachine described by the builder, or an EnigmaError if the parts break a limit or could
+          not be fitted to the chosen model
+       limitations: a ring setting or position of 26 or more is refused as OutOfRange, as validate reports it, rather than wrapped
+          round to a letter */
+    pub fn build(&self) -> Result<EnigmaMachine, EnigmaError> {
+        if let Some(error) = self.config.settings_out_of_range().into_iter().next() {
+            return Err(error);
+        }
+        let limit = if self.plugboard_fitted { self.max_plugs } else { Some(0) };
+        if let Some(limit) = limit {
+            if self.config.plugboard.len() > limit {
+                return Err(EnigmaError::TooManyPlugs { limit, found: self.config.plugboard.len() });
+            }
+        }
+        let model = match self.model {
+            Some(model) => model,
+            None => return Ok(self.fit_plugboard(self.config.build(), None))
+        };
+        let expected = if model.needs_greek_rotor() { 4 } else { 3 };
+        let found = if self.config.greek.is_some() { 4 } else { 3 };
+        if found != expected {
+            return Err(EnigmaError::WrongRotorCount { model, expected, found });
+        }
+        if let Some((greek, _, _)) = self.config.greek {
+            if greek != RotorId::Beta && greek != RotorId::Gamma {
+                return Err(EnigmaError::RotorNotInModel { model, rotor: greek });
+            }
+        }
+        if let Some(rotor) = self.config.rotors.iter().find(|rotor| !model.allows_rotor(**rotor)) {
+            return Err(EnigmaError::RotorNotInModel { model, rotor: *rotor });
+        }
+        if !model.allows_reflector(self.config.reflector) {
+            return Err(EnigmaError::ReflectorNotInModel { model, reflector: self.config.reflector });
+        }
+        if !model.has_plugboard() && !self.config.plugboard.is_empty() {
+            return Err(EnigmaError::PlugboardNotFitted(model));
+        }
+
+        Ok(self.fit_plugboard(self.config.build_with_entry_wheel(model.entry_wheel()), model.entry_wheel()))
+    }
+
+    // Takes the plugboard out of a built machine if none is to be fitted and no entry wheel needs its place
+    fn fit_plugboard(&self, mut machine: EnigmaMachine, entry_wheel: Option<&str>) -> EnigmaMachine {
+        if !self.plugboard_fitted && entry_wheel.is_none() {
+            machine.remove_plugboard();
+        }
+        machine
+    }
+}
+
+// Reads plugboard pairs such as "AV", rejecting any that are not two different letters or that reuse a letter
+fn plug_pairs<'a>(pairs: impl Iterator<Item = &'a str>) -> Result<Vec<(char, char)>, ImportError> {
+    let mut plugboard: Vec<(char, char)> = vec![];
+    for pair in pairs {
+        let letters: Vec<char> = pair.chars().collect();
+        let is_pair = letters.len() == 2 && letters[0] != letters[1] && letters.iter().all(|chr| is_cipher_letter(*chr));
+        let is_free = plugboard.iter().all(|(first, second)| !letters.contains(first) && !letters.contains(second));
+        if !is_pair || !is_free {
+            return Err(ImportError::InvalidField { field: "plugboard", value: pair.to_owned() });
+        }
+        plugboard.push((letters[0], letters[1]));
+    }
+
+    Ok(plugboard)
+}
+
+// Describes a field whose JSON value was not of the expected form
+fn invalid(field: &'static str, value: &JsonValue) -> ImportError {
+    ImportError::InvalidField { field, value: format!("{:?}", value) }
+}
+
+#[cfg(test)]
+use crate::enigma::plugboard::PlugboardError;
+
+#[test]
+// Tests to see if settings exported by a simulator import and encipher a message the same way the simulator does
+fn test_from_simulator_json() {
+    let exported = r#"{
+        "model": "Enigma I",
+        "rotors": ["I", "II", "III"],
+        "rings": [1, 1, 1],
+        "positions": "MCK",
+        "plugboard": "AV BS CG DL FU HZ IN KM OW RX",
+        "reflector": "UKW-B"
+    }"#;
+    let config = MachineConfig::from_simulator_json(exported).unwrap();
+    assert_eq!([RotorId::I, RotorId::II, RotorId::III], config.rotors);
+    assert_eq!([12, 2, 10], config.positions);
+    assert_eq!(10, config.plugboard.len());
+    let mut my_enigma = config.build();
+    assert_eq!('V', my_enigma.plugboard_mapping()[0]);
+    assert_eq!("VGH XBAY EOT KWGM", my_enigma.transform_message("THE TIME HAS COME".to_owned()));
+}
+
+#[test]
+// Tests to see if the enigma! macro builds the same machine as the builder, and rejects letters it cannot read
+fn test_enigma_macro() {
+    let mut declared = enigma!{ rotors: [III, I, II], rings: "FVN", pos: "QEV", plugs: "AB CD", reflector: B }.unwrap();
+    let mut built = MachineBuilder::new().rotors(RotorId::III, RotorId::I, RotorId::II).rings([5, 21, 13]).positions([16, 4, 21])
+        .plugs(&[('A', 'B'), ('C', 'D')]).reflector(ReflectorId::B).build().unwrap();
+    assert_eq!(built.transform("WETTERVORHERSAGE"), declared.transform("WETTERVORHERSAGE"));
+
+    let unplugged = enigma!{ rotors: [I, II, III], rings: "AAA", pos: "AAA", plugs: "", reflector: B };
+    assert!(unplugged.is_ok());
+    assert_eq!(Err(ImportError::InvalidField { field: "rings", value: "AA".to_owned() }),
+        enigma!{ rotors: [I, II, III], rings: "AA", pos: "AAA", plugs: "", reflector: B }.map(|_| ()));
+    assert_eq!(Err(ImportError::InvalidField { field: "plugboard", value: "AA".to_owned() }),
+        enigma!{ rotors: [I, II, III], rings: "AAA", pos: "AAA", plugs: "AA", reflector: C }.map(|_| ()));
+}
+
+#[test]
+// Tests to see if malformed settings are rejected with an error naming the problem
+fn test_from_simulator_json_errors() {
+    let missing = r#"{"rotors": ["I", "II", "III"], "rings": [1, 1, 1], "positions": "AAA"}"#;
+    assert_eq!(Err(ImportError::MissingField("reflector")), MachineConfig::from_simulator_json(missing));
+    let unknown = r#"{"rotors": ["I", "II", "IX"], "rings": [1, 1, 1], "positions": "AAA", "reflector": "B"}"#;
+    assert_eq!(Err(ImportError::UnknownRotor("IX".to_owned())), MachineConfig::from_simulator_json(unknown));
+    let plugged = r#"{"rotors": ["I", "II", "III"], "rings": [1, 1, 1], "positions": "AAA", "plugboard": "AB BC", "reflector": "B"}"#;
+    assert_eq!(Err(ImportError::InvalidField { field: "plugboard", value: "BC".to_owned() }), MachineConfig::from_simulator_json(plugged));
+    assert!(matches!(MachineConfig::from_simulator_json("{"), Err(ImportError::Json(_))));
+}
+
+#[test]
+// Tests to see if the builder enforces its plug limit and otherwise builds a working machine
+fn test_max_plugs() {
+    let pairs = [('A', 'V'), ('B', 'S'), ('C', 'G'), ('D', 'L'), ('F', 'U'), ('H', 'Z'), ('I', 'N'), ('K', 'M'), ('O', 'W'), ('R', 'X')];
+    let builder = MachineBuilder::new().positions([12, 2, 10]).plugs(&pairs);
+    let error = builder.clone().max_plugs(6).build().err().unwrap();
+    assert_eq!(EnigmaError::TooManyPlugs { limit: 6, found: 10 }, error);
+    let mut my_enigma = builder.max_plugs(10).build().unwrap();
+    assert_eq!("VGH XBAY EOT KWGM", my_enigma.transform_message("THE TIME HAS COME".to_owned()));
+}
+
+#[test]
+// Tests to see if a machine built without a plugboard matches one with an identity plugboard, and refuses plugboard cables
+fn test_no_plugboard() {
+    let builder = MachineBuilder::new().rotors(RotorId::II, RotorId::IV, RotorId::V).rings([1, 20, 11]).positions([0, 11, 4]);
+    let mut with_plugboard = builder.build().unwrap();
+    let mut without_plugboard = builder.clone().no_plugboard().build().unwrap();
+    let message = "THE QUICK BROWN FOX JUMPS OVER THE LAZY DOG";
+    assert_eq!(with_plugboard.transform(message), without_plugboard.transform(message));
+    let mut bulk = [0u8; 43];
+    without_plugboard.reset();
+    without_plugboard.transform_bulk(message.as_bytes(), &mut bulk);
+    with_plugboard.reset();
+    assert_eq!(with_plugboard.transform(message).as_bytes(), bulk);
+    assert_eq!(with_plugboard.plugboard_mapping(), without_plugboard.plugboard_mapping());
+    let error = builder.no_plugboard().plugs(&[('A', 'B')]).build().err();
+    assert_eq!(Some(EnigmaError::TooManyPlugs { limit: 0, found: 1 }), error);
+}
+
+#[test]
+// Tests to see if each model refuses rotors, reflectors and plugboards it could not be fitted with
+fn test_model_constraints() {
+    let error = MachineBuilder::new().rotors(RotorId::VI, RotorId::II, RotorId::III).model(Model::EnigmaI).build().err();
+    assert_eq!(Some(EnigmaError::RotorNotInModel { model: Model::EnigmaI, rotor: RotorId::VI }), error);
+    assert!(MachineBuilder::new().rotors(RotorId::VI, RotorId::II, RotorId::III).model(Model::M3).build().is_ok());
+    let error = MachineBuilder::new().reflector(ReflectorId::A).model(Model::M3).build().err();
+    assert_eq!(Some(EnigmaError::ReflectorNotInModel { model: Model::M3, reflector: ReflectorId::A }), error);
+    let error = MachineBuilder::new().reflector(ReflectorId::BThin).model(Model::M4).build().err();
+    assert_eq!(Some(EnigmaError::WrongRotorCount { model: Model::M4, expected: 4, found: 3 }), error);
+    let error = MachineBuilder::new().greek(RotorId::Beta, 0, 0).model(Model::M4).build().err();
+    assert_eq!(Some(EnigmaError::ReflectorNotInModel { model: Model::M4, reflector: ReflectorId::B }), error);
+    let error = MachineBuilder::new().greek(RotorId::Beta, 0, 0).model(Model::M3).build().err();
+    assert_eq!(Some(EnigmaError::WrongRotorCount { model: Model::M3, expected: 3, found: 4 }), error);
+    let swiss = MachineBuilder::new().rotors(RotorId::IIIK, RotorId::IK, RotorId::IIK).reflector(ReflectorId::K).model(Model::SwissK);
+    assert!(swiss.build().is_ok());
+    let error = swiss.plugs(&[('A', 'B')]).build().err();
+    assert_eq!(Some(EnigmaError::PlugboardNotFitted(Model::SwissK)), error);
+}
+
+#[test]
+// Tests to see if an M4 with the Beta rotor at A and the thin B reflector enciphers the same way as an M3 with reflector B
+fn test_m4_compatibility() {
+    let message = "THE TIME HAS COME".to_owned();
+    let mut m3 = MachineBuilder::new().positions([12, 2, 10]).model(Model::M3).build().unwrap();
+    let mut m4 = MachineBuilder::new().positions([12, 2, 10]).greek(RotorId::Beta, 0, 0).reflector(ReflectorId::BThin)
+        .model(Model::M4).build().unwrap();
+    assert_eq!(m3.transform_message(message.clone()), m4.transform_message(message));
+}
+
+#[test]
+// Tests to see if a one line spec builds the machine it describes
+fn test_from_str() {
+    let config: MachineConfig = "III-I-II FVN QEV AB.CD.EF UKW-B".parse().unwrap();
+    assert_eq!([RotorId::III, RotorId::I, RotorId::II], config.rotors);
+    assert_eq!([5, 21, 13], config.rings);
+    assert_eq!([16, 4, 21], config.positions);
+    assert_eq!(vec![('A', 'B'), ('C', 'D'), ('E', 'F')], config.plugboard);
+    assert_eq!(ReflectorId::B, config.reflector);
+    let mut parsed: EnigmaMachine = "I-II-III AAA MCK AV.BS.CG.DL.FU.HZ.IN.KM.OW.RX B".parse().unwrap();
+    assert_eq!("VGH XBAY EOT KWGM", parsed.transform_message("THE TIME HAS COME".to_owned()));
+    let m4: MachineConfig = "Beta-II-IV-I AAAV VJNA AT.BL B-thin".parse().unwrap();
+    assert_eq!(Some((RotorId::Beta, 0, 21)), m4.greek);
+    assert_eq!([0, 0, 21], m4.rings);
+}
+
+#[test]
+// Tests to see if malformed specs are rejected with an error naming the field at fault
+fn test_from_str_errors() {
+    assert_eq!(Err(ImportError::UnknownRotor("IX".to_owned())), "III-I-IX FVN QEV AB UKW-B".parse::<MachineConfig>());
+    assert_eq!(Err(ImportError::InvalidField { field: "rings", value: "FV".to_owned() }), "III-I-II FV QEV AB UKW-B".parse::<MachineConfig>());
+    assert_eq!(Err(ImportError::InvalidField { field: "positions", value: "Q3V".to_owned() }), "III-I-II FVN Q3V UKW-B".parse::<MachineConfig>());
+    assert_eq!(Err(ImportError::InvalidField { field: "plugboard", value: "BC".to_owned() }), "III-I-II FVN QEV AB.BC UKW-B".parse::<MachineConfig>());
+    assert_eq!(Err(ImportError::UnknownReflector("UKW-Z".to_owned())), "III-I-II FVN QEV AB UKW-Z".parse::<MachineConfig>());
+    assert_eq!(Err(ImportError::InvalidField { field: "rotors", value: "III-I".to_owned() }), "III-I FVN QEV UKW-B".parse::<MachineConfig>());
+    assert!(matches!("III-I-II FVN".parse::<EnigmaMachine>(), Err(ImportError::InvalidField { field: "spec", .. })));
+}
+
+#[test]
+// Tests to see if validate reports every problem in a config rather than only the first
+fn test_validate() {
+    let mut config: MachineConfig = "I-II-III AAA MCK AV.BS UKW-B".parse().unwrap();
+    assert_eq!(Ok(()), config.validate());
+    config.rings[1] = 30;
+    config.plugboard.push(('S', 'Q'));
+    let errors = config.validate().err().unwrap();
+    assert_eq!(2, errors.len());
+    assert!(errors.contains(&EnigmaError::OutOfRange { field: "ring", value: 30 }));
+    assert!(errors.contains(&EnigmaError::InvalidPlugboard(PlugboardError::DuplicateLetter {
+        letter: 'S', first_pair: "BS".to_owned(), second_pair: "SQ".to_owned()
+    })));
+
+    // the builder refuses the same out-of-range values rather than wrapping them
+    assert_eq!(Some(EnigmaError::OutOfRange { field: "ring", value: 30 }), MachineBuilder::new().rings([0, 30, 0]).build().err());
+    assert_eq!(Some(EnigmaError::OutOfRange { field: "position", value: 26 }),
+        MachineBuilder::new().greek(RotorId::Beta, 0, 26).reflector(ReflectorId::BThin).model(Model::M4).build().err());
+}
@@ -1,41 +1,109 @@
 pub mod enigma_wheel {
-    use enigma::lib::Cipher;
+    use std::fmt;
+    use crate::alphabet::{index_letter, is_cipher_letter, letter_index, ALPHABET_BASE, ALPHABET_LEN};
+    use crate::lib::Cipher;
 
-    // The Enigma Trait provides methods for rotating the offset, propogating the rotation as necessary; setting the rotor position;
-    // setting the trigger points which cause the next wheel to rotate; and simulating the path through the rotor in both the left-to-right and right-to-left directions 
+    // The Enigma Trait provides methods for rotating the offset, propogating the rotation as necessary; reading and setting the rotor position;
+    // checking whether the rotor is at a turnover; setting the trigger points which cause the next wheel to rotate; and simulating the path
+    // through the rotor in both the left-to-right and right-to-left directions 
     pub trait Enigma {
         fn rotate(&mut self) -> bool;
+        fn rotor_position(&self) -> u16;
         fn set_rotor_position(&mut self, rotor_position: u16);
+        fn at_turnover(&self) -> bool;
         fn set_triggers(&mut self, triggers: Vec<u16>);
         fn right_to_left(&self, position: u16) -> u16;
         fn left_to_right(&self, position: u16) -> u16;
     }
 
+    /* A WheelError describes why an EnigmaWheel could not be created from the settings supplied to it:
+         InvalidLength holds the number of characters in a cipher that was not exactly 26 characters long
+         InvalidLetter holds a character in a cipher that is not an uppercase letter
+         RepeatedLetter holds a letter that appears twice in a cipher, so the cipher is not a permutation of the alphabet
+         InvalidIndex holds an entry of a wiring given as indices that is not below 26
+         InvalidTrigger holds a trigger position that is not below 26, so the rotor could never reach it
+         RepeatedTrigger holds a trigger position that appears twice in a list of triggers */
+    #[derive(Debug, PartialEq, Eq)]
+    pub enum WheelError {
+        InvalidLength(usize),
+        InvalidLetter(char),
+        RepeatedLetter(char),
+        InvalidIndex(u8),
+        InvalidTrigger(u16),
+        RepeatedTrigger(u16)
+    }
+
+    impl fmt::Display for WheelError {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            match self {
+                WheelError::InvalidLength(length) => write!(f, "cipher must be exactly 26 characters long, found {}", length),
+                WheelError::InvalidLetter(chr) => write!(f, "cipher contains '{}', which is not an uppercase letter", chr),
+                WheelError::RepeatedLetter(letter) => write!(f, "letter {} appears more than once in the cipher", letter),
+                WheelError::InvalidIndex(index) => write!(f, "wiring contains {}, which is not a letter index below 26", index),
+                WheelError::InvalidTrigger(trigger) => write!(f, "trigger {} is not a rotor position below 26", trigger),
+                WheelError::RepeatedTrigger(trigger) => write!(f, "trigger {} appears more than once", trigger)
+            }
+        }
+    }
+
+    impl std::error::Error for WheelError {}
+
+    /* A NotchMount says what the turnover notches of a wheel are fixed to:
+         Ring, the default, fixes them to the lettered ring as on the service machines, so a rotor turns its neighbour over at the
+            same window letter whatever the ring setting
+         Core fixes them to the wiring core, so the triggers are the window positions at ring setting A and move with the ring */
+    #[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+    pub enum NotchMount {
+        #[default]
+        Ring,
+        Core
+    }
+
     /* An EnigmaWheel is a representation of a rotating offset substitution cipher. It contains the following:
          A String called cipher which represents the encoded result of the alphabet
          A u16 called rotor_position that represent the number of characters an input char is shifted prior to enciphering
           Shifts move baclwards in the alphabet (e.g.: C shifts by 2 to A), and are stored mod 26
          A u16 called ring_setting that represents the number of characters an output char is shifter after enciphering
           Shifts move forward in the aplhabet (e.g.: A shifts by 2 to A), and are stored mod 26
+         A NotchMount called notch_mount that says whether the triggers are fixed to the ring or to the core
+         Two [u8; 26] lookup tables called forward and inverse that cache the cipher with the ring setting applied, for the signal
+          passing right to left and left to right respectively. They depend on the ring setting but not the rotor position
        An EnigmaWheel has the following functions available to it:
          new is a constructor that returns a new EnigmaWheel object given a cipher String, offset u16 and setting u16 as above
+         try_new is a constructor like new that first checks the cipher is exactly 26 characters long
+         from_inverse is a constructor like try_new given the wiring traced from left to right instead
+         from_indices is a constructor like try_new given the wiring as letter indices, where 0 is A
+         inverse_wiring is a function that returns the wiring traced from left to right, with the wiring-access feature enabled
+         set_turnover_letters is a function that sets the triggers from the window letters at which the rotor turns over
+         try_set_triggers is a function like set_triggers that first checks the triggers are in range and not repeated
+         turnover_letters is a function that returns the window letters at which the rotor turns over
+         set_ring_setting is a function that changes the ring setting and rebuilds the lookup tables to match
+         set_notch_mount is a function that fixes the triggers to the ring or to the core
+         shift_for is a function that returns the Caesar shift the wheel applies to a letter at its current position
          encipher is a function that returns an enciphered String given a plaintext String using the encipherment provided in the 
             cipher variable
          decipher is a function that returns a plaintext String given an enciphered String using the encipherment provided in the
             cipher variable
          rotate is a function that increments the offset by one mod 26. This rotation is propagated to successive wheels if necessary
+         rotate_back is a function that undoes rotate, reporting whether the rotation undone had propagated
+         rotor_position is a function that returns the current position of the rotor
          set_rotor_position is a function the sets or resets the initial position of the rotor
+         at_turnover is a function that reports whether the rotor's next rotation will trigger the next wheel
          set_triggers is a function that sets the turnover points of the rotor
          right_to_left takes the position of an input signal on the right side of the rotor and returns the position of the output
             signal on the left hand side of the rotor
          left_to_right takes the position of an input signal on the left side of the rotor and returns the position of the output 
             signal on the right hand side of the rotor
        EnigmaWheel implements the traits Cipher and Enigma */
+    #[derive(Clone)]
     pub struct EnigmaWheel {
         cipher: String,
         rotor_position: u16,
         ring_setting: u16,
-        triggers: Vec<u16>
+        triggers: Vec<u16>,
+        notch_mount: NotchMount,
+        forward: [u8; 26],
+        inverse: [u8; 26]
     }
 
     /* The new method for EnigmaWheel allows us to create a EnigmaWheel without exposing the cipher to users. After initial creation
@@ -47,14 +115,369 @@ pub mod enigma_wheel {
                    u16 representing the shift applied to the original letter before ciphering
                    u16 representing the shift applied to the enciphered letter
            output: EnigmaWheel object containing the specified cipher and encipher and decipher methods for its use
-           limitations: The cipher and ring settings cannot be changed once they are initially set */
+           limitations: The cipher and ring settings cannot be changed once they are initially set. The cipher must be exactly 26
+              characters long; new does not check this, so use try_new for ciphers that come from an untrusted source */
         pub fn new(new_cipher: String, new_offset: u16, new_setting: u16) -> EnigmaWheel {
-            EnigmaWheel{
+            let mut wheel = EnigmaWheel{
                 cipher: new_cipher, 
                 rotor_position: new_offset.checked_rem(26).unwrap(), 
                 ring_setting: new_setting.checked_rem(26).unwrap(),
-                triggers: vec![]
+                triggers: vec![],
+                notch_mount: NotchMount::Ring,
+                forward: [0; 26],
+                inverse: [0; 26]
+            };
+            wheel.build_tables();
+
+            wheel
+        }
+
+        /* function: set_ring_setting
+           input: u16 representing the new ring setting, stored mod 26
+           output: none
+           limitations: none obvious at this time
+           algorithm: the lookup tables have the ring setting built in, so they are rebuilt whenever it changes */
+        pub fn set_ring_setting(&mut self, ring_setting: u16) {
+            self.ring_setting = ring_setting.checked_rem(26).unwrap();
+            self.build_tables();
+        }
+
+        /* function: set_notch_mount
+           input: NotchMount saying whether the triggers are fixed to the ring or to the core
+           output: none
+           limitations: none obvious at this time */
+        pub fn set_notch_mount(&mut self, notch_mount: NotchMount) {
+            self.notch_mount = notch_mount;
+        }
+
+        /* function: notch_mount
+           input: none
+           output: NotchMount saying whether the triggers are fixed to the ring or to the core
+           limitations: for EnigmaMachine::to_bytes, which saves the notch mount beside the wheel's own bytes */
+        pub(crate) fn notch_mount(&self) -> NotchMount {
+            self.notch_mount
+        }
+
+        /* function: is_trigger
+           input: u16 holding a rotor position mod 26
+           output: bool indicating whether reaching that position turns the next wheel over
+           limitations: none obvious at this time
+           algorithm: triggers are window positions. A ring mounted notch stays with the window letters, so the position is checked
+              as it is. A core mounted notch turns with the core, which sits ring_setting contacts behind the ring, so the position
+              is taken back by the ring setting before it is checked */
+        fn is_trigger(&self, position: u16) -> bool {
+            let position = match self.notch_mount {
+                NotchMount::Ring => position,
+                NotchMount::Core => (position + 26 - self.ring_setting) % 26
+            };
+            self.triggers.contains(&position)
+        }
+
+        /* function: build_tables
+           input: none
+           output: none
+           limitations: a cipher that is not a permutation of the uppercase letters gives meaningless tables rather than an error
+           algorithm: turning the ring moves the wiring core against the lettered ring, so contact i of the ring meets contact i - ring
+              of the core. forward[i] is where the signal entering ring contact i on the right leaves on the left, in ring contacts
+              with the rotor at position A. inverse undoes forward. At any other position p the signal simply enters at i + p and
+              leaves p contacts further back, so one pair of tables serves every position */
+        fn build_tables(&mut self) {
+            let wiring: Vec<u8> = self.cipher.chars().map(|chr| ((chr as u32).wrapping_sub(ALPHABET_BASE as u32) % ALPHABET_LEN as u32) as u8).collect();
+            let ring = self.ring_setting as usize;
+            for contact in 0..26 {
+                let core = wiring.get((contact + 26 - ring) % 26).copied().unwrap_or(((contact + 26 - ring) % 26) as u8);
+                self.forward[contact] = (core + ring as u8) % 26;
+            }
+            for contact in (0..26).rev() {
+                self.inverse[self.forward[contact] as usize] = contact as u8;
+            }
+        }
+
+        /* function: tables
+           input: none
+           output: tuple of the forward and inverse lookup tables, as build_tables describes them
+           limitations: for use by fast paths that trace many signals at once */
+        pub(crate) fn tables(&self) -> (&[u8; 26], &[u8; 26]) {
+            (&self.forward, &self.inverse)
+        }
+
+        /* function: turnover_mask
+           input: none
+           output: array of 26 bools where entry p is true when the rotor is at a turnover at position p, as at_turnover describes
+           limitations: for use by fast paths that step the rotors many times at once */
+        pub(crate) fn turnover_mask(&self) -> [bool; 26] {
+            let mut mask = [false; 26];
+            for (position, entry) in mask.iter_mut().enumerate() {
+                *entry = self.is_trigger((position as u16 + 1) % 26);
+            }
+            mask
+        }
+
+        /* function: try_new
+           inputs: String representing the enciphered alphabet, which must be exactly 26 characters long
+                   u16 representing the shift applied to the original letter before ciphering
+                   u16 representing the shift applied to the enciphered letter
+           output: Result containing the EnigmaWheel object, or a WheelError describing why the cipher was rejected
+           limitations: A cipher longer than 26 characters would let find locate letters beyond position 25 and corrupt the
+              results, so it is rejected along with any cipher that is too short */
+        pub fn try_new(new_cipher: String, new_offset: u16, new_setting: u16) -> Result<EnigmaWheel, WheelError> {
+            let length = new_cipher.chars().count();
+            if length != 26 {
+                return Err(WheelError::InvalidLength(length));
+            }
+
+            Ok(EnigmaWheel::new(new_cipher, new_offset, new_setting))
+        }
+
+        /* function: from_inverse
+           inputs: String representing the inverse wiring, where the letter at index i is the one that reaches the right hand side of
+                      the wheel at the ith letter when the signal passes from left to right
+                   u16 representing the shift applied to the original letter before ciphering
+                   u16 representing the shift applied to the enciphered letter
+           output: Result containing the EnigmaWheel object, or a WheelError describing why the wiring was rejected
+           limitations: The wiring must be a permutation of the alphabet, as check_wiring describes, since only then can it be inverted
+           algorithm: the inverse wiring sends letter i to letter w[i], so the forward wiring sends letter w[i] back to letter i. The
+              forward cipher is built from that and the wheel is made as in new */
+        pub fn from_inverse(inverse_cipher: String, new_offset: u16, new_setting: u16) -> Result<EnigmaWheel, WheelError> {
+            EnigmaWheel::check_wiring(&inverse_cipher)?;
+            let mut cipher = ['A'; 26];
+            for (index, chr) in inverse_cipher.chars().enumerate() {
+                cipher[letter_index(chr)] = index_letter(index);
+            }
+
+            Ok(EnigmaWheel::new(cipher.iter().collect(), new_offset, new_setting))
+        }
+
+        /* function: from_indices
+           inputs: [u8; 26] representing the wiring, where the entry at index i is the index of the letter i is wired to, 0 being A
+                   u16 representing the shift applied to the original letter before ciphering
+                   u16 representing the shift applied to the enciphered letter
+           output: Result containing the EnigmaWheel object, or a WheelError describing why the wiring was rejected
+           limitations: The wiring must be a permutation of 0 to 25. A repeated entry is reported as the letter it stands for
+           algorithm: each index is converted to its letter to give the cipher, which is checked as in check_wiring */
+        pub fn from_indices(indices: [u8; 26], new_offset: u16, new_setting: u16) -> Result<EnigmaWheel, WheelError> {
+            if let Some(index) = indices.iter().find(|index| **index >= 26) {
+                return Err(WheelError::InvalidIndex(*index));
+            }
+            let cipher: String = indices.iter().map(|index| index_letter(*index as usize)).collect();
+            EnigmaWheel::check_wiring(&cipher)?;
+
+            Ok(EnigmaWheel::new(cipher, new_offset, new_setting))
+        }
+
+        /* function: check_wiring
+           input: &str containing an enciphered alphabet
+           output: Result that is Ok if the cipher is a permutation of the alphabet, or a WheelError describing the first problem
+           limitations: try_new only checks the length, so this is the stricter check to use before trusting a wiring */
+        pub fn check_wiring(cipher: &str) -> Result<(), WheelError> {
+            let length = cipher.chars().count();
+            if length != 26 {
+                return Err(WheelError::InvalidLength(length));
+            }
+            let mut seen = [false; 26];
+            for chr in cipher.chars() {
+                if !is_cipher_letter(chr) {
+                    return Err(WheelError::InvalidLetter(chr));
+                }
+                if seen[letter_index(chr)] {
+                    return Err(WheelError::RepeatedLetter(chr));
+                }
+                seen[letter_index(chr)] = true;
+            }
+
+            Ok(())
+        }
+
+        /* function: set_turnover_letters
+           input: &str containing the window letters showing when the rotor turns over, e.g.: "Q" for rotor I or "ZM" for rotor VI
+           output: none
+           limitations: characters that are not uppercase letters are ignored
+           algorithm: the next wheel steps as this wheel moves on from the turnover letter, so each trigger is the position one past
+              the letter shown in the window */
+        pub fn set_turnover_letters(&mut self, letters: &str) {
+            self.set_triggers(letters.chars()
+                .filter(|chr| is_cipher_letter(*chr))
+                .map(|chr| (letter_index(chr) as u16 + 1).checked_rem(26).unwrap())
+                .collect());
+        }
+
+        /* function: try_set_triggers
+           input: Vec of u16s holding the positions which trigger the next wheel to rotate, where 0 is A
+           output: Result that is Ok once the triggers are set, or a WheelError naming the first trigger that is out of range or
+              repeated, in which case the triggers are left as they were
+           limitations: none obvious at this time
+           algorithm: set_triggers takes any list, but a trigger of 26 or more is never reached and a repeated one is easy to mistake
+              for two notches, so both are refused here. The triggers are stored in ascending order */
+        pub fn try_set_triggers(&mut self, mut triggers: Vec<u16>) -> Result<(), WheelError> {
+            if let Some(trigger) = triggers.iter().find(|trigger| **trigger >= 26) {
+                return Err(WheelError::InvalidTrigger(*trigger));
+            }
+            triggers.sort_unstable();
+            if let Some(pair) = triggers.windows(2).find(|pair| pair[0] == pair[1]) {
+                return Err(WheelError::RepeatedTrigger(pair[0]));
+            }
+            self.set_triggers(triggers);
+
+            Ok(())
+        }
+
+        /* function: turnover_letters
+           input: none
+           output: Vec<char> containing the window letter for each trigger, in the order the triggers are stored
+           limitations: none obvious at this time
+           algorithm: this is the inverse of set_turnover_letters, each trigger position is moved back one place to find the letter
+              that was showing in the window when the rotor turned over */
+        pub fn turnover_letters(&self) -> Vec<char> {
+            self.triggers.iter()
+                .map(|trigger| index_letter(*trigger as usize + 25))
+                .collect()
+        }
+
+        /* function: rotate_back
+           input: none
+           output: bool indicating whether the position the rotor is leaving was a trigger, so the rotation being undone turned the
+              next wheel and that should be undone too
+           limitations: none obvious at this time
+           algorithm: decrements the rotor position mod 26. This is the reverse of rotate, which reports a trigger at the position it
+              moves to, so the same trigger is reported here as the rotor moves back off it */
+        pub fn rotate_back(&mut self) -> bool {
+            let carried = self.is_trigger(self.rotor_position);
+            self.rotor_position = (self.rotor_position + 25).checked_rem(26).unwrap();
+
+            carried
+        }
+
+        /* function: shift_for
+           input: char containing the letter entering the wheel from the right
+           output: i16 holding the shift the wheel applies to that letter, output minus input mod 26, from 0 to 25
+           limitations: Characters that are not uppercase Roman letters pass through the machine unchanged and so give a shift of 0
+           algorithm: traces the letter through the wheel right to left at its current position and ring setting. For a fixed
+              input this is exactly a Caesar shift, but the shift differs from letter to letter and changes each time the wheel
+              rotates, which is what sets a wheel apart from a Caesar cipher */
+        pub fn shift_for(&self, input: char) -> i16 {
+            if !is_cipher_letter(input) {
+                return 0;
+            }
+            let index = letter_index(input) as i16;
+            let output = (self.right_to_left(index as u16 + 1) as i16 + 25) % 26;
+            (output - index).rem_euclid(26)
+        }
+
+        /* function: to_bytes
+           input: none
+           output: [u8; 32] containing the wheel's settings in the layout below
+           limitations: The order of the triggers is not kept, and a trigger repeated in the list is stored once. set_triggers takes
+              any position, but one of 26 or more is never reached, so it is left out of the mask, where from_bytes would refuse it.
+              The notch mount is not stored, so a wheel read back has its notches on the ring until EnigmaMachine::from_bytes sets
+              the mount it saved alongside
+           algorithm: bytes 0 to 25 hold the cipher as ASCII, byte 26 the rotor position, byte 27 the ring setting, and bytes 28 to 31
+              a little-endian bit mask with bit n set when position n is a trigger */
+        pub(crate) fn to_bytes(&self) -> [u8; 32] {
+            let mut bytes = [0u8; 32];
+            for (index, byte) in self.cipher.bytes().take(26).enumerate() {
+                bytes[index] = byte;
+            }
+            bytes[26] = self.rotor_position as u8;
+            bytes[27] = self.ring_setting as u8;
+            let mask: u32 = self.triggers.iter()
+                .filter_map(|trigger| 1u32.checked_shl(*trigger as u32).filter(|bit| *bit < 1 << 26))
+                .fold(0, |mask, bit| mask | bit);
+            bytes[28..32].copy_from_slice(&mask.to_le_bytes());
+
+            bytes
+        }
+
+        /* function: from_bytes
+           input: &[u8; 32] containing a wheel's settings in the layout written by to_bytes
+           output: Option containing the EnigmaWheel, or None if the cipher is not 26 uppercase letters or a setting is out of range
+           limitations: none obvious at this time */
+        pub(crate) fn from_bytes(bytes: &[u8; 32]) -> Option<EnigmaWheel> {
+            if !bytes[..26].iter().all(|byte| byte.is_ascii_uppercase()) || bytes[26] > 25 || bytes[27] > 25 {
+                return None;
+            }
+            let mask = u32::from_le_bytes([bytes[28], bytes[29], bytes[30], bytes[31]]);
+            if mask >= 1 << 26 {
+                return None;
+            }
+            let mut wheel = EnigmaWheel::new(String::from_utf8(bytes[..26].to_vec()).ok()?, bytes[26] as u16, bytes[27] as u16);
+            wheel.set_triggers((0..26).filter(|trigger| mask & 1 << trigger != 0).collect());
+
+            Some(wheel)
+        }
+    }
+
+    /* With the zeroize feature enabled, an EnigmaWheel wipes its wiring, positions and triggers from memory when it is dropped. An
+       EnigmaMachine is made up of EnigmaWheels along with its Plugboard and Reflector, which wipe themselves the same way, so
+       dropping a machine wipes the plugboard, rotors and reflector in turn. */
+    #[cfg(feature = "zeroize")]
+    impl Drop for EnigmaWheel {
+        fn drop(&mut self) {
+            self.wipe();
+        }
+    }
+
+    #[cfg(feature = "zeroize")]
+    impl EnigmaWheel {
+        /* function: wipe
+           input: none
+           output: none
+           limitations: The wheel is left with a cipher of NUL characters, so it must not be used after it has been wiped
+           algorithm: overwrites each byte of the cipher and each setting with zero using volatile writes, which the compiler may not
+              remove as dead stores, then fences so the writes are not moved past the deallocation that follows. The spare capacity of
+              the cipher and triggers is wiped too, as it may still hold what they held before they last shrank */
+        fn wipe(&mut self) {
+            // SAFETY: the bytes are only overwritten with NUL, which is valid UTF-8 on its own, so the String stays valid
+            wipe_vec(unsafe { self.cipher.as_mut_vec() });
+            wipe_vec(&mut self.triggers);
+            for entry in self.forward.iter_mut().chain(self.inverse.iter_mut()) {
+                // SAFETY: the pointer comes from a live &mut u8
+                unsafe { std::ptr::write_volatile(entry, 0) };
+            }
+            // SAFETY: both pointers come from live &mut u16s
+            unsafe {
+                std::ptr::write_volatile(&mut self.rotor_position, 0);
+                std::ptr::write_volatile(&mut self.ring_setting, 0);
+            }
+            std::sync::atomic::compiler_fence(std::sync::atomic::Ordering::SeqCst);
+        }
+    }
+
+    /* function: wipe_vec
+       input: &mut Vec<T> to be wiped
+       output: none
+       limitations: The length is kept, so the Vec holds that many default values afterwards
+       algorithm: writes the default value over every element and every slot of spare capacity with volatile writes, so nothing the
+          Vec once held is left in its allocation */
+    #[cfg(feature = "zeroize")]
+    pub(crate) fn wipe_vec<T: Copy + Default>(vec: &mut Vec<T>) {
+        for element in vec.iter_mut() {
+            // SAFETY: the pointer comes from a live &mut T
+            unsafe { std::ptr::write_volatile(element, T::default()) };
+        }
+        for slot in vec.spare_capacity_mut() {
+            // SAFETY: the pointer comes from a live &mut MaybeUninit<T>, which may be written with any value
+            unsafe { std::ptr::write_volatile(slot, std::mem::MaybeUninit::new(T::default())) };
+        }
+    }
+
+    /* With the wiring-access feature enabled, an EnigmaWheel can give out its wiring, so users can check the rotor data they typed
+       in. It is off by default, as the wiring is otherwise kept private to the wheel. */
+    #[cfg(feature = "wiring-access")]
+    impl EnigmaWheel {
+        /* function: inverse_wiring
+           input: none
+           output: String containing the wiring traced from left to right, in the form from_inverse takes, e.g.:
+              "UWYGADFPVZBECKMTHXSLRINQOJ" for rotor I
+           limitations: The wiring is read as the wheel was made, without the ring setting or rotor position. A wiring that is not a
+              permutation of the alphabet has no inverse, so the letters no contact is wired to are left as '?'
+           algorithm: the forward wiring sends letter i to letter w[i], so the inverse sends letter w[i] back to letter i. This undoes
+              from_inverse, so EnigmaWheel::from_inverse(wheel.inverse_wiring(), ..) gives back the same wheel */
+        pub fn inverse_wiring(&self) -> String {
+            let mut inverse = ['?'; 26];
+            for (index, chr) in self.cipher.chars().enumerate().filter(|(_, chr)| is_cipher_letter(*chr)) {
+                inverse[letter_index(chr)] = index_letter(index);
             }
+            inverse.iter().collect()
         }
     }
 
@@ -69,17 +492,17 @@ pub mod enigma_wheel {
             let mut enciphered_text: String = String::new();
 
             for mut code in message.encode_utf16() {
-                if code > 64 && code < 91 {
-                    // take the ascii code for the letter in the plaintext, subtract 65 to obtain a zero-based index
-                    code = code.checked_sub(65).unwrap();
+                if code >= ALPHABET_BASE as u16 && code < ALPHABET_BASE as u16 + ALPHABET_LEN as u16 {
+                    // take the ascii code for the letter in the plaintext, subtract the base to obtain a zero-based index
+                    code = code.checked_sub(ALPHABET_BASE as u16).unwrap();
                     // then add the offset to the index mod 26 to obtain shifted index of source character
                     code = code.checked_add(26-self.rotor_position).unwrap().checked_rem(26).unwrap();
                     // return the letter corresponding to that index from the cipher
                     let mut enciphered_code = self.cipher.chars().nth((code) as usize).unwrap() as u16;
                     // shift the letter obtained by the ring setting, adjusting to keep in range
                     enciphered_code += self.ring_setting;
-                    if enciphered_code > 90 {
-                        enciphered_code -= 26;
+                    if enciphered_code >= ALPHABET_BASE as u16 + ALPHABET_LEN as u16 {
+                        enciphered_code -= ALPHABET_LEN as u16;
                     }
                     let encoded = char::from_u32(enciphered_code as u32).unwrap();
                     enciphered_text.push(encoded);
@@ -102,18 +525,18 @@ pub mod enigma_wheel {
             let mut plain_text: String = String::new();
 
             for chr in message.chars() {
-                if chr > '@' && chr <'[' {
+                if is_cipher_letter(chr) {
                     // undo the shift caused by the ring setting adjusting to keep character in range
                     let mut code = chr as u32 - self.ring_setting as u32;
-                    if code < 65 {
-                        code += 26;
+                    if code < ALPHABET_BASE as u32 {
+                        code += ALPHABET_LEN as u32;
                     }
                     // find the character's position in the cipher key
                     let mut decoded = self.cipher.find(char::from_u32(code).unwrap()).unwrap() as u16;
                     // modify position based on current rotor_position
                     decoded = decoded.checked_add(self.rotor_position).unwrap().checked_rem(26).unwrap();
                     // convert the position to the ASCII code for the corresponding letter of the alphabet
-                    decoded = decoded.checked_add(65).unwrap();
+                    decoded = decoded.checked_add(ALPHABET_BASE as u16).unwrap();
                     plain_text.push(char::from_u32(decoded as u32).unwrap());
                 } else {
                     plain_text.push(chr);
@@ -130,29 +553,43 @@ pub mod enigma_wheel {
            input: none
            output: bool indicating whether the next wheel in the sequence should be rotated as well
            limitations: none obvious at this time
-           algorithm: increments the rotor position mod 26, then checks to see if any of the triggers for rotating the next wheel were hit */
+           algorithm: increments the rotor position mod 26, then checks to see if any of the triggers for rotating the next wheel were hit.
+              The lookup tables do not depend on the position, so they are left as they are */
         fn rotate(&mut self) -> bool {
-            let mut result = false;
             self.rotor_position += 1;
             self.rotor_position = self.rotor_position.checked_rem(26).unwrap();
-            for i in &self.triggers[..] {
-                if i == &self.rotor_position {
-                    result = true;
-                }
-            }
 
-            result
+            self.is_trigger(self.rotor_position)
         }
 
         /* function: set_rotor_position
            input: u16 representing the current rotor position mod 26
            output: none
            limitations: none obvious at this time
-           algorithm: sets the specified rotor position mod 26 */
+           algorithm: sets the specified rotor position mod 26. The lookup tables depend on the ring setting but not the position,
+              so they stay valid and nothing needs rebuilding */
         fn set_rotor_position(&mut self, rotor_position: u16) {
                 self.rotor_position = rotor_position.checked_rem(26).unwrap();
         }
 
+        /* function: rotor_position
+           input: none
+           output: u16 representing the current rotor position mod 26, where 0 shows A in the window
+           limitations: none obvious at this time */
+        fn rotor_position(&self) -> u16 {
+            self.rotor_position
+        }
+
+        /* function: at_turnover
+           input: none
+           output: bool indicating whether the rotor is showing one of its turnover letters
+           limitations: none obvious at this time
+           algorithm: a trigger is the position reached by the rotation that turns the next wheel over, so the rotor is at a turnover
+              when the position after its current one is a trigger. On the machine this is when the notch is under the pawl */
+        fn at_turnover(&self) -> bool {
+            self.is_trigger((self.rotor_position + 1).checked_rem(26).unwrap())
+        }
+
         /* function: set_triggers
            input: Vector of u16s representing the positions which trigger the next wheel to rotate
            output: none
@@ -168,9 +605,9 @@ pub mod enigma_wheel {
            limitations: none obvious at this time
            algorithm: traces the input through the wheel wiring to the output accounting for start position. While this has the final effect of a letter substitution cipher, that cipher is not obvious from the arrangements of the letters on the opposing sides of the rotor. Instead, the relative difference in the positions of the letter corresponding to the input position and the same letter's position on the output side determine the change in offset of the letter passing through the rotor. */
         fn right_to_left(&self, position: u16) -> u16 {
-            let index: u16 = (position.checked_add(25 +  self.rotor_position - self.ring_setting).unwrap()).checked_rem(26).unwrap();
-            let chr: char = self.cipher.chars().nth(index as usize).unwrap();
-            (26 - self.rotor_position + (chr as u16 - 64)).checked_rem(26).unwrap()
+            let index: u16 = (position.checked_add(25 + self.rotor_position).unwrap()).checked_rem(26).unwrap();
+            let output: u16 = self.forward[index as usize] as u16;
+            (27 - self.rotor_position + output).checked_rem(26).unwrap()
         }
 
         /* function: left_to_right
@@ -179,12 +616,9 @@ pub mod enigma_wheel {
            limitations: none obvious at this time
            algorithm: traces the input through the wheel wiring to the output accounting for start position. While this has the final effect of a letter substitution cipher, that cipher is not obvious from the arrangements of the letters on the opposing sides of the rotor. Instead, the relative difference in the positions of the letter corresponding to the input position and the same letter's position on the output side determine the change in offset of the letter passing through the rotor. */
         fn left_to_right(&self, position: u16) -> u16 {
-            let mut index: u16 = (position.checked_add(self.rotor_position).unwrap()).checked_rem(26).unwrap();
-            if index == 0 {
-                index = 26;
-            }
-            let decoded = self.cipher.find(char::from_u32(index as u32 + 64).unwrap()).unwrap() as u16;
-            let mut fin_pos: u16 = (26 - self.rotor_position + self.ring_setting + (decoded + 1)).checked_rem(26).unwrap();
+            let index: u16 = (position.checked_add(25 + self.rotor_position).unwrap()).checked_rem(26).unwrap();
+            let decoded: u16 = self.inverse[index as usize] as u16;
+            let mut fin_pos: u16 = (26 - self.rotor_position + (decoded + 1)).checked_rem(26).unwrap();
             if fin_pos == 0 {
                 fin_pos = 26u16;
             }
@@ -336,110 +770,2452 @@ pub mod enigma_wheel {
         let new_pos = wheel.left_to_right(20);
         assert_eq!(12, new_pos);
     }
-}
 
-pub mod enigma_machine {
-    use crate::enigma::enigma_wheel::EnigmaWheel;
-    use crate::enigma::enigma_wheel::Enigma;
+    #[test]
+    // Tests to see if changing the ring setting after construction matches constructing the wheel with that ring setting
+    fn test_set_ring_setting() {
+        let mut changed = EnigmaWheel::new("EKMFLGDQVZNTOWYHXUSPAIBRCJ".to_owned(), 7, 0);
+        changed.set_ring_setting(11);
+        let fresh = EnigmaWheel::new("EKMFLGDQVZNTOWYHXUSPAIBRCJ".to_owned(), 7, 11);
+        for position in 1..=26 {
+            assert_eq!(fresh.right_to_left(position), changed.right_to_left(position));
+            assert_eq!(fresh.left_to_right(position), changed.left_to_right(position));
+        }
+    }
 
-    /* An EnigmaStructure is a representation of a complete Enigma machine. It contains the following:
-         An EnigmaWheel representing the plugboard
-         An EnigmaWheel representing the rightmost Enigma wheel
-         An EnigmaWheel representing the middle Enigma wheel
-         An EnigmaWheel representing the leftmost Enigma wheel
-         An EnigmaWheel representing the reflector
-       An EnigmaWheel has the following functions available to it:
-         new is a constructor that returns a new EnigmaMachine object given the components' ciphers String, offsets u16 and settings u16 as above
-         set_triggers is a function that sets the rotating trigger points of the three wheels given their triggers Vec<u16>
-         transform is a function that returns a plaintext String given an enciphered String or an enciphered String given a plaintext String using the setting provided for the EnigmaMachine */
-    pub struct EnigmaMachine {
-        plugboard: EnigmaWheel,
-        right_wheel: EnigmaWheel,
-        middle_wheel: EnigmaWheel,
-        left_wheel: EnigmaWheel,
-        reflector: EnigmaWheel
+    #[test]
+    // Tests to see if moving a wheel by hand between lookups gives the same signals as a wheel built at that position
+    fn test_set_rotor_position_tables() {
+        let mut wheel = EnigmaWheel::new("ESOVPZJAYQUIRHXLNFTGKDCMWB".to_owned(), 0, 9);
+        for position in [5, 5, 0, 25, 13, 14, 2] {
+            wheel.set_rotor_position(position);
+            wheel.rotate();
+            let fresh = EnigmaWheel::new("ESOVPZJAYQUIRHXLNFTGKDCMWB".to_owned(), (position + 1) % 26, 9);
+            for contact in 1..=26 {
+                assert_eq!(fresh.right_to_left(contact), wheel.right_to_left(contact));
+                assert_eq!(fresh.left_to_right(contact), wheel.left_to_right(contact));
+            }
+        }
     }
 
-    impl EnigmaMachine {
-        /* function: new
-           inputs: String representing the wiring of the plugboard
-                   String, u16, u16  representing the wiring, offset and ring setting of the rightmost rotor
-                   String, u16, u16  representing the wiring, offset and ring setting of the middle rotor
-                   String, u16, u16  representing the wiring, offset and ring setting of the leftmost rotor
-                   String representing the wiring of the reflector
-           output: EnigmaMachine object containing the plugboard, rotors and reflector specified as above
-           limitations: The ciphers cannot be changed once it is initially set */
-           pub fn new(pb_cipher: String, 
-               rw_cipher: String, rw_offset: u16, rw_setting: u16,
-               mw_cipher: String, mw_offset: u16, mw_setting: u16,
-               lw_cipher: String, lw_offset: u16, lw_setting: u16,
-               rf_cipher: String
-            ) -> EnigmaMachine {
-                EnigmaMachine {
-                    plugboard: EnigmaWheel::new(pb_cipher, 0, 0),
-                    right_wheel: EnigmaWheel::new(rw_cipher, rw_offset, rw_setting),
-                    middle_wheel: EnigmaWheel::new(mw_cipher, mw_offset, mw_setting),
-                    left_wheel: EnigmaWheel::new(lw_cipher, lw_offset, lw_setting),
-                    reflector: EnigmaWheel::new(rf_cipher, 0, 0)
+    #[test]
+    // Tests to see if ring mounted notches move with the ring setting against the core, and core mounted notches do not
+    fn test_notch_mount() {
+        // Both wheels have the same core position, C, but the second shows it through a ring set to B, so its window reads D
+        let presses_to_carry = |mount: NotchMount, position: u16, ring: u16| {
+            let mut wheel = EnigmaWheel::new("EKMFLGDQVZNTOWYHXUSPAIBRCJ".to_owned(), position, ring);
+            wheel.set_turnover_letters("Q");
+            wheel.set_notch_mount(mount);
+            (1..=26).find(|_| wheel.rotate()).unwrap()
+        };
+        assert_eq!(15, presses_to_carry(NotchMount::Ring, 2, 0));
+        assert_eq!(14, presses_to_carry(NotchMount::Ring, 3, 1));
+        assert_eq!(15, presses_to_carry(NotchMount::Core, 2, 0));
+        assert_eq!(15, presses_to_carry(NotchMount::Core, 3, 1));
+
+        let mut wheel = EnigmaWheel::new("EKMFLGDQVZNTOWYHXUSPAIBRCJ".to_owned(), 17, 1);
+        wheel.set_turnover_letters("Q");
+        wheel.set_notch_mount(NotchMount::Core);
+        assert!(wheel.at_turnover());
+        assert!(wheel.turnover_mask()[17]);
+        assert!(wheel.rotate());
+        assert!(wheel.rotate_back());
+    }
+
+    #[test]
+    // Tests to see if a two notch set of triggers is accepted and sorted, while repeated and out of range triggers are refused
+    fn test_try_set_triggers() {
+        let mut wheel = EnigmaWheel::new("JPGVOUMFYQBENHZRDKASXLICTW".to_owned(), 0, 0);
+        assert_eq!(Ok(()), wheel.try_set_triggers(vec![13, 0]));
+        assert_eq!(vec!['Z', 'M'], wheel.turnover_letters());
+        assert_eq!(Err(WheelError::RepeatedTrigger(13)), wheel.try_set_triggers(vec![13, 4, 13]));
+        assert_eq!(Err(WheelError::InvalidTrigger(26)), wheel.try_set_triggers(vec![4, 26]));
+        assert_eq!(vec!['Z', 'M'], wheel.turnover_letters());
+    }
+
+    #[test]
+    // Tests to see if turnover letters round-trip through the trigger positions
+    fn test_turnover_letters() {
+        let mut wheel = EnigmaWheel::new("BDFHJLCPRTXVZNYEIWGAKMUSQO".to_owned(), 0, 0);
+        wheel.set_turnover_letters("V");
+        assert_eq!(vec!['V'], wheel.turnover_letters());
+        wheel.set_triggers(vec![22]);
+        assert_eq!(vec!['V'], wheel.turnover_letters());
+        wheel.set_turnover_letters("ZM");
+        assert_eq!(vec!['Z', 'M'], wheel.turnover_letters());
+        wheel.set_rotor_position(25);
+        assert!(wheel.rotate());
+    }
+
+    #[cfg(feature = "zeroize")]
+    #[test]
+    // Tests to see if wiping a wheel clears its wiring and settings
+    fn test_wipe() {
+        let mut wheel = EnigmaWheel::new("EKMFLGDQVZNTOWYHXUSPAIBRCJ".to_owned(), 5, 3);
+        wheel.set_triggers(vec![17]);
+        wheel.wipe();
+        assert!(wheel.cipher.bytes().all(|byte| byte == 0));
+        assert_eq!(26, wheel.cipher.len());
+        assert_eq!(0, wheel.rotor_position);
+        assert_eq!(0, wheel.ring_setting);
+        assert_eq!(vec![0], wheel.triggers);
+        assert_eq!([0; 26], wheel.forward);
+
+        // the spare capacity left behind when the triggers shrink is wiped as well
+        let mut triggers = vec![17, 5, 22];
+        triggers.truncate(1);
+        wipe_vec(&mut triggers);
+        assert_eq!(vec![0], triggers);
+        // SAFETY: the spare slots were all written by wipe_vec, so they are initialised
+        assert!(triggers.spare_capacity_mut().iter().all(|slot| unsafe { slot.assume_init() } == 0));
+    }
+
+    #[test]
+    // Tests to see if try_new rejects a cipher that is longer than the alphabet
+    fn test_try_new_rejects_long_cipher() {
+        let result = EnigmaWheel::try_new("EKMFLGDQVZNTOWYHXUSPAIBRCJABCD".to_owned(), 0, 0);
+        let error = result.err().unwrap();
+        assert_eq!(WheelError::InvalidLength(30), error);
+        assert_eq!("cipher must be exactly 26 characters long, found 30", error.to_string());
+        assert!(EnigmaWheel::try_new("EKMFLGDQVZNTOWYHXUSPAIBRCJ".to_owned(), 0, 0).is_ok());
+    }
+
+    #[test]
+    // Tests to see if check_wiring accepts rotor wirings and rejects ciphers that are not permutations of the alphabet
+    fn test_check_wiring() {
+        assert_eq!(Ok(()), EnigmaWheel::check_wiring("EKMFLGDQVZNTOWYHXUSPAIBRCJ"));
+        assert_eq!(Err(WheelError::RepeatedLetter('E')), EnigmaWheel::check_wiring("EKMFLGDQVZNTOWYHXUSPAIBRCE"));
+        assert_eq!(Err(WheelError::InvalidLetter('k')), EnigmaWheel::check_wiring("EkMFLGDQVZNTOWYHXUSPAIBRCJ"));
+    }
+
+    #[test]
+    // Tests to see if letters at both ends of the alphabet are substituted and the characters around them are passed through
+    fn test_alphabet_edges() {
+        let wheel = EnigmaWheel::new("EKMFLGDQVZNTOWYHXUSPAIBRCJ".to_owned(), 7, 19);
+        let text = "HELLO, World! ZAQ [@] 123 é";
+        assert_eq!("XKEEJ, Aorld! LIS [@] 123 é", wheel.encipher(text));
+        assert_eq!("TLZZP, Norld! MWX [@] 123 é", wheel.decipher(text));
+    }
+
+    #[test]
+    // Tests to see if shift_for gives the Caesar shift of a letter through wheel I, following the rotor position and ring setting
+    fn test_shift_for() {
+        let mut enigma = EnigmaWheel::new("EKMFLGDQVZNTOWYHXUSPAIBRCJ".to_owned(), 0, 0);
+        assert_eq!(4, enigma.shift_for('A'));
+        assert_eq!(10, enigma.shift_for('C'));
+        enigma.set_rotor_position(1);
+        assert_eq!(9, enigma.shift_for('A'));
+        enigma.set_ring_setting(1);
+        assert_eq!(4, enigma.shift_for('A'));
+        assert_eq!(0, enigma.shift_for('?'));
+    }
+
+    #[test]
+    // Tests to see if a wheel built from rotor I's inverse wiring matches one built from its forward wiring, and bad wirings fail
+    fn test_from_inverse() {
+        let forward = EnigmaWheel::new("EKMFLGDQVZNTOWYHXUSPAIBRCJ".to_owned(), 3, 5);
+        let inverse = EnigmaWheel::from_inverse("UWYGADFPVZBECKMTHXSLRINQOJ".to_owned(), 3, 5).unwrap();
+        assert_eq!(forward.to_bytes(), inverse.to_bytes());
+        assert_eq!(forward.encipher("HELLO"), inverse.encipher("HELLO"));
+        assert_eq!(Some(WheelError::RepeatedLetter('U')), EnigmaWheel::from_inverse("UUYGADFPVZBECKMTHXSLRINQOJ".to_owned(), 0, 0).err());
+    }
+
+    #[cfg(feature = "wiring-access")]
+    #[test]
+    // Tests to see if the inverse wiring composed with the forward wiring is the identity, and from_inverse undoes it
+    fn test_inverse_wiring() {
+        let wheel = EnigmaWheel::new("EKMFLGDQVZNTOWYHXUSPAIBRCJ".to_owned(), 3, 5);
+        let inverse = wheel.inverse_wiring();
+        assert_eq!("UWYGADFPVZBECKMTHXSLRINQOJ", inverse);
+        let inverse: Vec<char> = inverse.chars().collect();
+        let identity: String = wheel.cipher.chars().map(|chr| inverse[letter_index(chr)]).collect();
+        assert_eq!("ABCDEFGHIJKLMNOPQRSTUVWXYZ", identity);
+        let rebuilt = EnigmaWheel::from_inverse(wheel.inverse_wiring(), 3, 5).unwrap();
+        assert_eq!(wheel.to_bytes(), rebuilt.to_bytes());
+    }
+
+    #[test]
+    // Tests to see if tracing a signal through a wheel and back returns it, for every position, ring setting and contact
+    fn test_round_trip_every_setting() {
+        let mut enigma = EnigmaWheel::new("EKMFLGDQVZNTOWYHXUSPAIBRCJ".to_owned(), 0, 0);
+        for ring in 0..26 {
+            enigma.set_ring_setting(ring);
+            for position in 0..26 {
+                enigma.set_rotor_position(position);
+                for contact in 1..=26u16 {
+                    assert_eq!(contact % 26, enigma.left_to_right(enigma.right_to_left(contact)) % 26, "ring {} position {}", ring, position);
+                    assert_eq!(contact % 26, enigma.right_to_left(enigma.left_to_right(contact)) % 26, "ring {} position {}", ring, position);
                 }
             }
-        
-        /*  function: set_triggers
-            inputs: Vec<u16>s representing the turnover positions of the rightmost, middle and leftmost rotors respectively
-            output: none
-            limitations: None obvious as this time */
-            pub fn set_triggers(&mut self, rw_triggers: Vec<u16>, mw_triggers: Vec<u16>, lw_triggers: Vec<u16>) {
-                self.right_wheel.set_triggers(rw_triggers);
-                self.middle_wheel.set_triggers(mw_triggers);
-                self.left_wheel.set_triggers(lw_triggers);
+        }
+    }
+
+    #[test]
+    // Tests to see if the identity indices make a wheel that passes letters through, and indices that are not a permutation fail
+    fn test_from_indices() {
+        let mut identity = [0u8; 26];
+        for (index, entry) in identity.iter_mut().enumerate() {
+            *entry = index as u8;
+        }
+        let enigma = EnigmaWheel::from_indices(identity, 0, 0).unwrap();
+        assert_eq!("THE QUICK BROWN FOX", enigma.encipher("THE QUICK BROWN FOX"));
+        assert_eq!(0, enigma.shift_for('Q'));
+        identity[3] = 26;
+        assert_eq!(Some(WheelError::InvalidIndex(26)), EnigmaWheel::from_indices(identity, 0, 0).err());
+        identity[3] = 4;
+        assert_eq!(Some(WheelError::RepeatedLetter('E')), EnigmaWheel::from_indices(identity, 0, 0).err());
+    }
+}
+
+pub mod plugboard {
+    use std::fmt;
+    use crate::alphabet::{index_letter, is_cipher_letter, letter_index};
+
+    /* A PlugboardError describes why a set of plugboard cables could not be connected:
+         InvalidPair holds a pair that is not two different uppercase letters
+         DuplicateLetter holds a letter that appears in two pairs, which is impossible since each socket takes only one cable, along
+            with the two pairs it appears in */
+    #[derive(Debug, PartialEq, Eq)]
+    pub enum PlugboardError {
+        InvalidPair(String),
+        DuplicateLetter { letter: char, first_pair: String, second_pair: String }
+    }
+
+    impl fmt::Display for PlugboardError {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            match self {
+                PlugboardError::InvalidPair(pair) => write!(f, "'{}' is not a pair of two different letters", pair),
+                PlugboardError::DuplicateLetter { letter, first_pair, second_pair } =>
+                    write!(f, "letter {} is plugged twice, in '{}' and '{}'", letter, first_pair, second_pair)
             }
-    
-        /*  function: set_rotor_positions
-            inputs: u16s representing the initial positions of the rightmost, middle and leftmost rotors respectively
-            output: none
-            limitations: None obvious as this time */
-            pub fn set_rotor_positions(&mut self, rw_position: u16, mw_position: u16, lw_position: u16) {
-                self.right_wheel.set_rotor_position(rw_position);
-                self.middle_wheel.set_rotor_position(mw_position);
-                self.left_wheel.set_rotor_position(lw_position);
+        }
+    }
+
+    impl std::error::Error for PlugboardError {}
+
+    /* A Plugboard is a representation of the Steckerbrett, which swaps pairs of letters joined by a cable. It contains the following:
+         An array of 26 chars called mapping giving the letter each letter of the alphabet is connected to
+         An array of 26 chars called inverse that undoes mapping, which is the same as mapping unless the wiring was given whole
+       A Plugboard has the following functions available to it:
+         new is a constructor that returns a Plugboard with no cables, so every letter is connected to itself
+         from_pairs is a constructor that returns a Plugboard with a cable for each pair in a String such as "AB CD"
+         mapping is a function that returns the letter each letter of the alphabet is connected to
+         cipher is a function that returns the mapping as a String, suitable for EnigmaMachine::new
+         right_to_left and left_to_right trace a signal through the plugboard toward the rotors and back toward the lamps
+       A Plugboard never moves, so unlike an EnigmaWheel it has no position, ring setting or triggers and cannot be rotated */
+    #[derive(Clone, Debug, PartialEq, Eq)]
+    pub struct Plugboard {
+        mapping: [char; 26],
+        inverse: [char; 26]
+    }
+
+    impl Default for Plugboard {
+        fn default() -> Self {
+            Plugboard::new()
+        }
+    }
+
+    impl Plugboard {
+        pub fn new() -> Plugboard {
+            let mut mapping = ['A'; 26];
+            for (index, letter) in ('A'..='Z').enumerate() {
+                mapping[index] = letter;
             }
-        /*  function: transform_message
-            inputs: A String containing the message to be transformed, either by encoding or decoding 
-            output: A string containing the message after encoding or decoding
-            limitations: None obvious as this time */
-            pub fn transform_message(&mut self, message: String) -> String{
-            let mut enciphered: String = String::new();
 
-            for chr in message.chars() {
-                if chr > '@' && chr < '[' {
-                    if self.right_wheel.rotate() {
-                        if self.middle_wheel.rotate() {
-                            self.left_wheel.rotate();
-                        }
+            Plugboard { mapping, inverse: mapping }
+        }
+
+        /* function: from_cipher
+           input: &str containing the whole wiring as an enciphered alphabet
+           output: Plugboard wired as given
+           limitations: For EnigmaMachine::new, which takes the plugboard as a cipher, and for entry wheels folded into the plugboard,
+              so the wiring need not swap letters in pairs. It is not checked, and a character that is not an uppercase letter
+              leaves its letter connected to itself */
+        pub(crate) fn from_cipher(cipher: &str) -> Plugboard {
+            let mut plugboard = Plugboard::new();
+            for (index, letter) in cipher.chars().take(26).enumerate() {
+                if is_cipher_letter(letter) {
+                    plugboard.mapping[index] = letter;
+                }
+            }
+            for (index, letter) in plugboard.mapping.iter().enumerate().rev() {
+                plugboard.inverse[letter_index(*letter)] = index_letter(index);
+            }
+
+            plugboard
+        }
+
+        /* function: from_pairs
+           input: &str containing whitespace separated pairs of letters, e.g.: "AV BS CG"
+           output: Result containing the Plugboard with a cable joining each pair, or a PlugboardError naming the first bad pair
+           limitations: none obvious at this time
+           algorithm: the pair each letter was first plugged in is remembered, so a letter that turns up again can be reported along
+              with both of the pairs that use it */
+        pub fn from_pairs(pairs: &str) -> Result<Plugboard, PlugboardError> {
+            let mut plugboard = Plugboard::new();
+            let mut plugged_in: Vec<Option<&str>> = vec![None; 26];
+            for pair in pairs.split_whitespace() {
+                let letters: Vec<char> = pair.chars().collect();
+                if letters.len() != 2 || letters[0] == letters[1] || !letters.iter().all(|chr| is_cipher_letter(*chr)) {
+                    return Err(PlugboardError::InvalidPair(pair.to_owned()));
+                }
+                for letter in &letters {
+                    if let Some(first_pair) = plugged_in[letter_index(*letter)] {
+                        return Err(PlugboardError::DuplicateLetter {
+                            letter: *letter,
+                            first_pair: first_pair.to_owned(),
+                            second_pair: pair.to_owned()
+                        });
                     }
-                    let code: u16 = (chr as u16) - 64;
-                    let pos = &self.plugboard.right_to_left(code);
-                    let pos = &self.right_wheel.right_to_left(*pos);
-                    let pos = &self.middle_wheel.right_to_left(*pos);
-                    let pos = &self.left_wheel.right_to_left(*pos);
-                    let pos = &self.reflector.right_to_left(*pos);
-                    let pos = &self.left_wheel.left_to_right(*pos);
-                    let pos = &self.middle_wheel.left_to_right(*pos);
-                    let pos = &self.right_wheel.left_to_right(*pos);
-                    let pos = &self.plugboard.left_to_right(*pos);
-        
-                    enciphered.push(char::from_u32(*pos as u32 + 64).unwrap());
-                } else {
-                    enciphered.push(chr);
+                    plugged_in[letter_index(*letter)] = Some(pair);
                 }
+                plugboard.mapping[letter_index(letters[0])] = letters[1];
+                plugboard.mapping[letter_index(letters[1])] = letters[0];
             }
-        
-            enciphered
+            plugboard.inverse = plugboard.mapping;
+
+            Ok(plugboard)
         }
-    }
 
-    #[test]
-    // This deciphers a known message with known machine settings to ensure the EnigmaMachine is working properlsy
+        /* function: mapping
+           input: none
+           output: [char; 26] where the entry at each index is the letter that letter of the alphabet is connected to (index 0 is A)
+           limitations: none obvious at this time */
+        pub fn mapping(&self) -> [char; 26] {
+            self.mapping
+        }
+
+        /* function: cipher
+           input: none
+           output: String containing the mapping as an enciphered alphabet
+           limitations: none obvious at this time */
+        pub fn cipher(&self) -> String {
+            self.mapping.iter().collect()
+        }
+
+        /* function: right_to_left
+           input: u16 representing the contact a signal from the keyboard enters at, counted from 1 and taken mod 26
+           output: u16 representing the contact it leaves toward the rotors at, counted the same way
+           limitations: none obvious at this time
+           algorithm: the same numbering as EnigmaWheel::right_to_left, so the machine can trace a signal through either */
+        pub fn right_to_left(&self, position: u16) -> u16 {
+            (letter_index(self.mapping[(position as usize + 25) % 26]) as u16 + 1) % 26
+        }
+
+        /* function: left_to_right
+           input: u16 representing the contact a signal from the rotors enters at, counted from 1 and taken mod 26
+           output: u16 representing the contact it leaves toward the lamps at, counted the same way
+           limitations: none obvious at this time */
+        pub fn left_to_right(&self, position: u16) -> u16 {
+            (letter_index(self.inverse[(position as usize + 25) % 26]) as u16 + 1) % 26
+        }
+
+        /* function: tables
+           input: none
+           output: tuple of [u8; 26]s giving the letter (0 is A) each letter is sent to toward the rotors, and toward the lamps
+           limitations: for use by fast paths that trace many signals at once */
+        pub(crate) fn tables(&self) -> ([u8; 26], [u8; 26]) {
+            (self.mapping.map(|letter| letter_index(letter) as u8), self.inverse.map(|letter| letter_index(letter) as u8))
+        }
+    }
+
+    /* With the zeroize feature enabled, a Plugboard wipes its wiring from memory when it is dropped, as an EnigmaWheel does */
+    #[cfg(feature = "zeroize")]
+    impl Drop for Plugboard {
+        fn drop(&mut self) {
+            for letter in self.mapping.iter_mut().chain(self.inverse.iter_mut()) {
+                // SAFETY: the pointer comes from a live &mut char, and NUL is a valid char
+                unsafe { std::ptr::write_volatile(letter, '\0') };
+            }
+            std::sync::atomic::compiler_fence(std::sync::atomic::Ordering::SeqCst);
+        }
+    }
+
+    #[test]
+    // Tests to see if a single cable swaps its two letters and leaves the rest connected to themselves
+    fn test_mapping() {
+        let plugboard = Plugboard::from_pairs("AZ").unwrap();
+        let mapping = plugboard.mapping();
+        assert_eq!('Z', mapping[0]);
+        assert_eq!('A', mapping[25]);
+        for (index, letter) in ('B'..='Y').enumerate() {
+            assert_eq!(letter, mapping[index + 1]);
+        }
+        assert_eq!("ZBCDEFGHIJKLMNOPQRSTUVWXYA", plugboard.cipher());
+        assert_eq!(Err(PlugboardError::InvalidPair("A1".to_owned())), Plugboard::from_pairs("AZ A1"));
+    }
+
+    #[test]
+    // Tests to see if the plugboard swaps a signal's letter on the way in and back on the way out, and cannot be rotated
+    fn test_plugboard_signal() {
+        use std::marker::PhantomData;
+        use crate::enigma::enigma_wheel::{Enigma, EnigmaWheel};
+
+        let plugboard = Plugboard::from_pairs("AZ CD").unwrap();
+        assert_eq!(0, plugboard.right_to_left(1));
+        assert_eq!(1, plugboard.left_to_right(26));
+        assert_eq!(4, plugboard.right_to_left(3));
+        assert_eq!(2, plugboard.right_to_left(2));
+        let folded = Plugboard::from_cipher("BCDEFGHIJKLMNOPQRSTUVWXYZA");
+        assert_eq!(2, folded.right_to_left(1));
+        assert_eq!(1, folded.left_to_right(2));
+
+        // only a type with the Enigma trait, which holds rotate, finds the inherent method, the rest fall back to the trait's
+        struct Probe<T>(PhantomData<T>);
+        trait Fallback {
+            fn can_rotate(&self) -> bool {
+                false
+            }
+        }
+        impl<T> Fallback for Probe<T> {}
+        impl<T: Enigma> Probe<T> {
+            fn can_rotate(&self) -> bool {
+                true
+            }
+        }
+        assert!(Probe::<EnigmaWheel>(PhantomData).can_rotate());
+        assert!(!Probe::<Plugboard>(PhantomData).can_rotate());
+    }
+
+    #[test]
+    // Tests to see if a letter plugged into two cables is reported along with both cables
+    fn test_duplicate_letter() {
+        let error = Plugboard::from_pairs("AB BC").err().unwrap();
+        assert_eq!(PlugboardError::DuplicateLetter { letter: 'B', first_pair: "AB".to_owned(), second_pair: "BC".to_owned() }, error);
+        assert_eq!("letter B is plugged twice, in 'AB' and 'BC'", error.to_string());
+        assert!(Plugboard::from_pairs("AB CD").is_ok());
+    }
+}
+
+pub mod reflector {
+    use std::fmt;
+    use crate::alphabet::{index_letter, is_cipher_letter, letter_index};
+
+    /* A ReflectorError describes why a wiring could not be used for a reflector:
+         InvalidWiring holds a wiring that is not 26 uppercase letters
+         NotReciprocal holds a letter that is wired to a second letter which is not wired back to it
+         SelfWired holds a letter that is wired to itself, which would let a letter encipher to itself */
+    #[derive(Debug, PartialEq, Eq)]
+    pub enum ReflectorError {
+        InvalidWiring(String),
+        NotReciprocal(char),
+        SelfWired(char)
+    }
+
+    impl fmt::Display for ReflectorError {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            match self {
+                ReflectorError::InvalidWiring(wiring) => write!(f, "'{}' is not a wiring of 26 uppercase letters", wiring),
+                ReflectorError::NotReciprocal(letter) => write!(f, "letter {} is not wired back to the letter it is wired to", letter),
+                ReflectorError::SelfWired(letter) => write!(f, "letter {} is wired to itself", letter)
+            }
+        }
+    }
+
+    impl std::error::Error for ReflectorError {}
+
+    /* A Reflector is a representation of the Umkehrwalze, which joins the letters in 13 pairs and sends the signal back through
+       the rotors. It contains the following:
+         An array of 26 chars called mapping giving the letter each letter of the alphabet is wired to
+         A u16 called position giving the place the reflector is set to, where 0 is A, as a settable UKW-D could be
+       A Reflector has the following functions available to it:
+         try_new is a constructor that returns a Reflector with the given wiring, once the wiring is checked
+         from_pairs is a constructor like try_new given the 13 pairs of letters to join, as a rewireable UKW-D is wired in the field
+         wiring is a function that returns the mapping as a String, suitable for EnigmaMachine::new
+         pairs is a function that returns the 13 pairs of letters the reflector joins
+         set_position and position set and return the place the reflector is set to
+         right_to_left traces a signal from the rotors through the reflector and back to the rotors
+       A Reflector never steps, so unlike an EnigmaWheel it has no triggers and cannot be rotated */
+    #[derive(Clone, Debug, PartialEq, Eq)]
+    pub struct Reflector {
+        mapping: [char; 26],
+        position: u16
+    }
+
+    impl Reflector {
+        /* function: try_new
+           input: &str containing the wiring as an enciphered alphabet, e.g.: "YRUHQSLDPXNGOKMIEBFZCWVJAT" for reflector B
+           output: Result containing the Reflector, or a ReflectorError describing the first problem with the wiring
+           limitations: none obvious at this time */
+        pub fn try_new(wiring: &str) -> Result<Reflector, ReflectorError> {
+            let letters: Vec<char> = wiring.chars().collect();
+            if letters.len() != 26 || !letters.iter().all(|chr| is_cipher_letter(*chr)) {
+                return Err(ReflectorError::InvalidWiring(wiring.to_owned()));
+            }
+            let mut mapping = ['A'; 26];
+            for (index, letter) in ('A'..='Z').enumerate() {
+                let partner = letters[index];
+                if partner == letter {
+                    return Err(ReflectorError::SelfWired(letter));
+                }
+                if letters[letter_index(partner)] != letter {
+                    return Err(ReflectorError::NotReciprocal(letter));
+                }
+                mapping[index] = partner;
+            }
+
+            Ok(Reflector { mapping, position: 0 })
+        }
+
+        /* function: from_wiring
+           input: &str containing the wiring as an enciphered alphabet
+           output: Reflector wired as given, at position A
+           limitations: For EnigmaMachine::new, which does not check its wirings, so the reflector may not join letters in pairs and
+              the machine may then not be reciprocal. A character that is not an uppercase letter leaves its letter wired to itself */
+        pub(crate) fn from_wiring(wiring: &str) -> Reflector {
+            let mut mapping: [char; 26] = std::array::from_fn(index_letter);
+            for (index, letter) in wiring.chars().take(26).enumerate() {
+                if is_cipher_letter(letter) {
+                    mapping[index] = letter;
+                }
+            }
+
+            Reflector { mapping, position: 0 }
+        }
+
+        /* function: from_pairs
+           input: &str containing 13 space separated pairs of letters, e.g.: "AC BO DI EG FR HU JY KT LZ MX NW PQ SV"
+           output: Result containing the Reflector, or a ReflectorError describing the first problem with the pairs
+           limitations: The pairs must join every letter exactly once. A letter left out is reported as wired to itself, and a letter
+              used twice as not wired back
+           algorithm: each pair is written into a wiring both ways round, which is then checked by try_new */
+        pub fn from_pairs(pairs: &str) -> Result<Reflector, ReflectorError> {
+            let mut wiring: Vec<char> = ('A'..='Z').collect();
+            for pair in pairs.split_whitespace() {
+                let letters: Vec<char> = pair.chars().collect();
+                if letters.len() != 2 || !letters.iter().all(|chr| is_cipher_letter(*chr)) {
+                    return Err(ReflectorError::InvalidWiring(pairs.to_owned()));
+                }
+                wiring[letter_index(letters[0])] = letters[1];
+                wiring[letter_index(letters[1])] = letters[0];
+            }
+
+            Reflector::try_new(&wiring.into_iter().collect::<String>())
+        }
+
+        /* function: wiring
+           input: none
+           output: String containing the 26 letter mapping as an enciphered alphabet
+           limitations: none obvious at this time */
+        pub fn wiring(&self) -> String {
+            self.mapping.iter().collect()
+        }
+
+        /* function: pairs
+           input: none
+           output: Vec containing the 13 pairs of letters joined by the reflector, each with its first letter earlier in the alphabet,
+              in alphabetical order of those first letters
+           limitations: none obvious at this time */
+        pub fn pairs(&self) -> Vec<(char, char)> {
+            ('A'..='Z').zip(self.mapping).filter(|(letter, partner)| letter < partner).collect()
+        }
+
+        /* function: set_position
+           input: u16 giving the place to set the reflector to, where 0 is A, stored mod 26
+           output: none
+           limitations: none obvious at this time */
+        pub fn set_position(&mut self, position: u16) {
+            self.position = position % 26;
+        }
+
+        /* function: position
+           input: none
+           output: u16 giving the place the reflector is set to, where 0 is A
+           limitations: none obvious at this time */
+        pub fn position(&self) -> u16 {
+            self.position
+        }
+
+        /* function: right_to_left
+           input: u16 representing the contact the signal enters at, counted from 1 and taken mod 26 as in EnigmaWheel::right_to_left
+           output: u16 representing the contact the signal leaves at, counted the same way
+           limitations: none obvious at this time
+           algorithm: as for a rotor, the signal meets the wiring position contacts further round and leaves position contacts
+              further back. Setting the reflector only relabels its contacts, so it still joins letters in pairs */
+        pub fn right_to_left(&self, contact: u16) -> u16 {
+            let position = self.position as usize;
+            let wired = letter_index(self.mapping[(contact as usize + 25 + position) % 26]);
+            ((wired + 26 - position) % 26 + 1) as u16 % 26
+        }
+
+        /* function: table
+           input: none
+           output: [u8; 26] giving the letter (0 is A) each letter is sent back as, with the reflector at its position
+           limitations: for use by fast paths that trace many signals at once */
+        pub(crate) fn table(&self) -> [u8; 26] {
+            std::array::from_fn(|letter| ((self.right_to_left(letter as u16 + 1) + 25) % 26) as u8)
+        }
+    }
+
+    /* With the zeroize feature enabled, a Reflector wipes its wiring and position from memory when it is dropped, as an EnigmaWheel
+       does */
+    #[cfg(feature = "zeroize")]
+    impl Drop for Reflector {
+        fn drop(&mut self) {
+            for letter in self.mapping.iter_mut() {
+                // SAFETY: the pointer comes from a live &mut char, and NUL is a valid char
+                unsafe { std::ptr::write_volatile(letter, '\0') };
+            }
+            // SAFETY: as above, for a live &mut u16
+            unsafe { std::ptr::write_volatile(&mut self.position, 0) };
+            std::sync::atomic::compiler_fence(std::sync::atomic::Ordering::SeqCst);
+        }
+    }
+
+    #[test]
+    // Tests to see if the pairs of a reflector rebuild the same wiring through try_new
+    fn test_pairs() {
+        let reflector = Reflector::try_new("YRUHQSLDPXNGOKMIEBFZCWVJAT").unwrap();
+        let pairs = reflector.pairs();
+        assert_eq!(13, pairs.len());
+        assert_eq!(('A', 'Y'), pairs[0]);
+        let mut wiring = ['A'; 26];
+        for (first, second) in &pairs {
+            wiring[letter_index(*first)] = *second;
+            wiring[letter_index(*second)] = *first;
+        }
+        let rebuilt = Reflector::try_new(&wiring.iter().collect::<String>()).unwrap();
+        assert_eq!(reflector.wiring(), rebuilt.wiring());
+        assert_eq!("YRUHQSLDPXNGOKMIEBFZCWVJAT", rebuilt.wiring());
+    }
+
+    #[test]
+    // Tests to see if a reflector wired from pairs joins those letters, and pairs that miss or reuse a letter are rejected
+    fn test_from_pairs() {
+        let reflector = Reflector::from_pairs("AC BO DI EG FR HU JY KT LZ MX NW PQ SV").unwrap();
+        assert_eq!("COAIGRE", &reflector.wiring()[..7]);
+        assert_eq!(Err(ReflectorError::SelfWired('S')), Reflector::from_pairs("AC BO DI EG FR HU JY KT LZ MX NW PQ"));
+        assert_eq!(Err(ReflectorError::NotReciprocal('C')), Reflector::from_pairs("AC BO DI EG FR HU JY KT LZ MX NW PQ SV AB"));
+        assert!(matches!(Reflector::from_pairs("AC BOD"), Err(ReflectorError::InvalidWiring(_))));
+    }
+
+    #[test]
+    // Tests to see if a reflector sends every signal back to its partner at every position it is set to
+    fn test_right_to_left() {
+        let mut reflector = Reflector::try_new("YRUHQSLDPXNGOKMIEBFZCWVJAT").unwrap();
+        assert_eq!(25, reflector.right_to_left(1));
+        for position in 0..26 {
+            reflector.set_position(position);
+            for contact in 1..=26 {
+                let partner = reflector.right_to_left(contact);
+                assert_ne!(contact % 26, partner);
+                assert_eq!(contact % 26, reflector.right_to_left(partner));
+            }
+        }
+        reflector.set_position(27);
+        assert_eq!(1, reflector.position());
+    }
+
+    #[test]
+    // Tests to see if wirings that no reflector could have are rejected
+    fn test_try_new_errors() {
+        assert_eq!(Err(ReflectorError::SelfWired('A')), Reflector::try_new("ABCDEFGHIJKLMNOPQRSTUVWXYZ"));
+        assert_eq!(Err(ReflectorError::NotReciprocal('A')), Reflector::try_new("EKMFLGDQVZNTOWYHXUSPAIBRCJ"));
+        assert!(matches!(Reflector::try_new("YRU"), Err(ReflectorError::InvalidWiring(_))));
+    }
+}
+
+pub mod enigma_machine {
+    use std::borrow::Cow;
+    use std::fmt;
+    use std::fs;
+    use std::io::{self, Write};
+    use std::path::Path;
+    use std::sync::{Mutex, MutexGuard, PoisonError};
+    #[cfg(feature = "async")]
+    use std::pin::Pin;
+    use crate::alphabet::{index_letter, is_cipher_letter, letter_index, ALPHABET_BASE};
+    use crate::analysis::ngram_score;
+    #[cfg(feature = "async")]
+    use crate::async_io::{PollRead, PollWrite};
+    #[cfg(feature = "base64")]
+    use crate::base64::{self, Base64Error};
+    use crate::lib::Cipher;
+    use crate::enigma::enigma_wheel::{EnigmaWheel, NotchMount, WheelError};
+    use crate::enigma::enigma_wheel::Enigma;
+    #[cfg(feature = "zeroize")]
+    use crate::enigma::enigma_wheel::wipe_vec;
+    use crate::enigma::plugboard::{Plugboard, PlugboardError};
+    use crate::enigma::reflector::{Reflector, ReflectorError};
+    use crate::config::MachineBuilder;
+    use crate::numeric::NumericMachine;
+    use crate::presets::{Model, ReflectorId, RotorId};
+    use crate::random::Rng;
+    #[cfg(test)]
+    use crate::numeric::NumericWheel;
+    #[cfg(test)]
+    use crate::random::XorShift;
+
+    /* An EnigmaError describes why a machine could not be built from the settings supplied to it:
+         TooManyPlugs holds the maximum number of plugboard cables allowed, and the number that were supplied
+         InvalidShiftToggle holds a shift toggle character that is an uppercase letter, and so would be enciphered instead
+         RotorNotInModel holds a model and a rotor that model could not be fitted with
+         ReflectorNotInModel holds a model and a reflector that model could not be fitted with
+         WrongRotorCount holds a model, the number of rotors it takes, and the number that were supplied
+         PlugboardNotFitted holds a model that has no plugboard, when plugboard cables were supplied for it
+         InvalidWheel holds the name of a part whose wiring was rejected, and the reason
+         InvalidReflector holds the reason the reflector wiring was rejected, such as a rotor wiring passed in its place
+         InvalidPlugboard holds the reason the plugboard cables could not be connected
+         OutOfRange holds the name of a setting and a value for it that is not a letter of the alphabet, counting from 0
+         NonLetter holds the first character of a message that is not a letter, when the non-letter policy is Error
+         SelfEncipherment holds the index, counting letters only, and the letter where a message and the strict crib agree, which no
+            Enigma could produce since no letter is ever enciphered to itself
+         InvalidContact holds the number of a contact a signal left the machine at that has no lamp, counting from 1, which would
+            mean a fault in the wheel arithmetic */
+    #[derive(Debug, PartialEq, Eq)]
+    pub enum EnigmaError {
+        TooManyPlugs { limit: usize, found: usize },
+        InvalidShiftToggle(char),
+        RotorNotInModel { model: Model, rotor: RotorId },
+        ReflectorNotInModel { model: Model, reflector: ReflectorId },
+        WrongRotorCount { model: Model, expected: usize, found: usize },
+        PlugboardNotFitted(Model),
+        InvalidWheel { part: &'static str, error: WheelError },
+        InvalidReflector(ReflectorError),
+        InvalidPlugboard(PlugboardError),
+        OutOfRange { field: &'static str, value: u16 },
+        NonLetter(char),
+        SelfEncipherment { position: usize, letter: char },
+        InvalidContact(u16)
+    }
+
+    impl fmt::Display for EnigmaError {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            match self {
+                EnigmaError::TooManyPlugs { limit, found } => write!(f, "{} plugboard cables supplied but at most {} are allowed", found, limit),
+                EnigmaError::InvalidShiftToggle(toggle) => write!(f, "shift toggle '{}' must not be an uppercase letter", toggle),
+                EnigmaError::RotorNotInModel { model, rotor } => write!(f, "rotor {:?} cannot be fitted to the {:?}", rotor, model),
+                EnigmaError::ReflectorNotInModel { model, reflector } => write!(f, "reflector {:?} cannot be fitted to the {:?}", reflector, model),
+                EnigmaError::WrongRotorCount { model, expected, found } => write!(f, "the {:?} takes {} rotors but {} were supplied", model, expected, found),
+                EnigmaError::PlugboardNotFitted(model) => write!(f, "the {:?} has no plugboard", model),
+                EnigmaError::InvalidWheel { part, error } => write!(f, "invalid {}: {}", part, error),
+                EnigmaError::InvalidReflector(error) => write!(f, "invalid reflector: {}", error),
+                EnigmaError::InvalidPlugboard(error) => write!(f, "invalid plugboard: {}", error),
+                EnigmaError::OutOfRange { field, value } => write!(f, "{} setting {} is out of range, it must be below 26", field, value),
+                EnigmaError::NonLetter(chr) => write!(f, "'{}' is not a letter the machine can encipher", chr),
+                EnigmaError::SelfEncipherment { position, letter } => write!(f, "letter {} at position {} would be enciphered to itself", letter, position),
+                EnigmaError::InvalidContact(contact) => write!(f, "the signal left the machine at contact {}, which has no lamp", contact)
+            }
+        }
+    }
+
+    impl std::error::Error for EnigmaError {}
+
+    /* A DecodeError describes why a machine could not be restored from the bytes supplied to it:
+         WrongLength holds the number of bytes expected and the number supplied
+         BadMagic means the bytes do not start with the ENG marker written by to_bytes
+         UnsupportedVersion holds a layout version this library cannot read
+         InvalidPart holds the name of the part (e.g.: "middle rotor") whose wiring or settings are out of range */
+    #[derive(Debug, PartialEq, Eq)]
+    pub enum DecodeError {
+        WrongLength { expected: usize, found: usize },
+        BadMagic,
+        UnsupportedVersion(u8),
+        InvalidPart(&'static str)
+    }
+
+    impl fmt::Display for DecodeError {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            match self {
+                DecodeError::WrongLength { expected, found } => write!(f, "expected {} bytes but found {}", expected, found),
+                DecodeError::BadMagic => write!(f, "bytes do not hold a saved EnigmaMachine"),
+                DecodeError::UnsupportedVersion(version) => write!(f, "unsupported layout version {}", version),
+                DecodeError::InvalidPart(part) => write!(f, "the {} has invalid wiring or settings", part)
+            }
+        }
+    }
+
+    impl std::error::Error for DecodeError {}
+
+    /* A VerifyError describes why a decryption was not accepted as language:
+         LowScore holds the score the plaintext was given, the mean log-likelihood of its trigrams, and the lowest score accepted */
+    #[derive(Debug, PartialEq)]
+    pub enum VerifyError {
+        LowScore { score: f64, min_score: f64 }
+    }
+
+    impl fmt::Display for VerifyError {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            match self {
+                VerifyError::LowScore { score, min_score } =>
+                    write!(f, "decryption scored {:.3}, below the {:.3} expected of language", score, min_score)
+            }
+        }
+    }
+
+    impl std::error::Error for VerifyError {}
+
+    /* An Orientation describes which end of the machine holds the fast rotor:
+         RightFast is the standard arrangement, where the rightmost rotor steps on every key press and the signal enters it first
+         LeftFast is the mirror image, where the leftmost rotor steps on every key press and the signal enters it first */
+    #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+    pub enum Orientation {
+        RightFast,
+        LeftFast
+    }
+
+    /* A NonLetterPolicy describes what transform does with characters that have no key on the machine:
+         Pass copies them to the output unchanged, as the operator would write them by hand. This is the default
+         Drop leaves them out of the output
+         Error leaves them in, but makes try_transform_message refuse the whole message */
+    #[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+    pub enum NonLetterPolicy {
+        #[default]
+        Pass,
+        Drop,
+        Error
+    }
+
+    /* A Position is the three letters showing in the rotor windows, read left to right as the operator sees them */
+    pub type Position = (char, char, char);
+
+    /* function: contact_lamp
+       input: u16 representing the contact a signal leaves the machine at, counted from 1 as the wheels count them
+       output: Result containing the char of the lamp wired to that contact, or an EnigmaError::InvalidContact if there is none
+       limitations: none obvious at this time
+       algorithm: the wheels count contacts from 1 and reduce them mod 26, so contact 26, the Z contact, may arrive as 0. Contacts 1
+          to 26 are the lamps A to Z and 0 is Z again. Anything larger is refused rather than wrapped round onto a letter, since it
+          can only come from a fault in the wheel arithmetic and would otherwise light the wrong lamp without a sign of it */
+    fn contact_lamp(contact: u16) -> Result<char, EnigmaError> {
+        match contact {
+            0 => Ok('Z'),
+            1..=26 => Ok(index_letter(contact as usize - 1)),
+            _ => Err(EnigmaError::InvalidContact(contact))
+        }
+    }
+
+    /* A KeyResult describes one key press in detail, for displays that show the lamps and the rotors together. It contains the
+       following:
+         An Option<char> called output holding the lamp lit, or None if the key was not a letter and so lit no lamp
+         A Position called positions holding the window letters after the press
+         bools called left_stepped, middle_stepped and right_stepped showing which rotors moved on this press */
+    #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+    pub struct KeyResult {
+        pub output: Option<char>,
+        pub positions: Position,
+        pub left_stepped: bool,
+        pub middle_stepped: bool,
+        pub right_stepped: bool
+    }
+
+    /* A MachineState is the part of an EnigmaMachine that changes as keys are pressed, saved by state and restored by set_state.
+       It contains the following:
+         A tuple of u16s representing the positions of the rightmost, middle and leftmost rotors
+         A bool representing whether the shift is held
+         An Option holding the rotor positions of the shift layer, if the machine has one */
+    #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+    pub struct MachineState {
+        rotor_positions: (u16, u16, u16),
+        shifted: bool,
+        shift_layer: Option<(u16, u16, u16)>
+    }
+
+    /* An EnigmaStructure is a representation of a complete Enigma machine. It contains the following:
+         An Option<Plugboard> representing the wiring from the keys to the contacts, or None when the keyboard is in alphabetical order
+         An Option<Plugboard> representing the plugboard, or None when the machine has no plugboard and the signal skips it
+         An EnigmaWheel representing the rightmost Enigma wheel
+         An EnigmaWheel representing the middle Enigma wheel
+         An EnigmaWheel representing the leftmost Enigma wheel
+         A Reflector representing the reflector, which never steps but may be set to a position
+         An Option<usize> representing the number of five letter groups written on each line of grouped output
+         An Orientation representing which end of the machine holds the fast rotor
+         A tuple of bools representing whether the rightmost, middle and leftmost rotor slots step, as a static slot never moves
+         An optional second EnigmaMachine used while the shift is held, along with the character that toggles the shift
+         A bool representing whether the shift is currently held
+         An Option<NumericMachine> representing a separate set of wheels that enciphers digits, or None to pass digits through
+         An Option<char> representing the letter written in place of a space by encrypt, and read as a space by decrypt
+         A NonLetterPolicy representing what transform does with characters that are not letters
+         An Option<String> representing the crib try_transform_message checks messages against in strict mode
+         An Option<EnigmaWheel> representing a fixed substitution encrypt applies to its output, and decrypt takes off first
+         A tuple of u16s representing the ground setting, the rotor positions of the rightmost, middle and leftmost rotors that the
+            message starts from
+       An EnigmaWheel has the following functions available to it:
+         new is a constructor that returns a new EnigmaMachine object given the components' ciphers String, offsets u16 and settings u16 as above
+         set_triggers is a function that sets the rotating trigger points of the three wheels given their triggers Vec<u16>
+         transform is a function that returns a plaintext String given an enciphered String or an enciphered String given a plaintext String using the setting provided for the EnigmaMachine
+         transform_grouped is a function like transform that writes its output in five letter groups, wrapped according to the line policy
+         encrypt and decrypt are functions like transform that apply the space substitution, if one is set */
+    #[derive(Clone)]
+    pub struct EnigmaMachine {
+        keyboard: Option<Plugboard>,
+        plugboard: Option<Plugboard>,
+        right_wheel: EnigmaWheel,
+        middle_wheel: EnigmaWheel,
+        left_wheel: EnigmaWheel,
+        reflector: Reflector,
+        line_policy: Option<usize>,
+        orientation: Orientation,
+        stepping: (bool, bool, bool),
+        shift_layer: Option<(Box<EnigmaMachine>, char)>,
+        shifted: bool,
+        digit_layer: Option<NumericMachine>,
+        space_substitution: Option<char>,
+        nonletter_policy: NonLetterPolicy,
+        strict_crib: Option<String>,
+        output_stage: Option<EnigmaWheel>,
+        ground: (u16, u16, u16)
+    }
+
+    /* With the zeroize feature enabled, an EnigmaMachine wipes its ground setting and strict crib from memory when it is dropped. Its
+       wheels, plugboard and reflector then wipe themselves as they are dropped in turn. */
+    #[cfg(feature = "zeroize")]
+    impl Drop for EnigmaMachine {
+        fn drop(&mut self) {
+            self.wipe();
+        }
+    }
+
+    #[cfg(feature = "zeroize")]
+    impl EnigmaMachine {
+        /*  function: wipe
+            inputs: none
+            output: none
+            limitations: Only the settings the machine holds itself are wiped, so the machine must not be used after it has been wiped
+            algorithm: overwrites the strict crib and the ground setting with zero using volatile writes, as EnigmaWheel's wipe does */
+            fn wipe(&mut self) {
+                if let Some(crib) = &mut self.strict_crib {
+                    // SAFETY: the bytes are only overwritten with NUL, which is valid UTF-8 on its own, so the String stays valid
+                    wipe_vec(unsafe { crib.as_mut_vec() });
+                }
+                // SAFETY: the pointer comes from a live &mut to the tuple of u16s
+                unsafe { std::ptr::write_volatile(&mut self.ground, (0, 0, 0)) };
+                std::sync::atomic::compiler_fence(std::sync::atomic::Ordering::SeqCst);
+            }
+    }
+
+    impl EnigmaMachine {
+        /* function: new
+           inputs: String representing the wiring of the plugboard
+                   String, u16, u16  representing the wiring, offset and ring setting of the rightmost rotor
+                   String, u16, u16  representing the wiring, offset and ring setting of the middle rotor
+                   String, u16, u16  representing the wiring, offset and ring setting of the leftmost rotor
+                   String representing the wiring of the reflector
+           output: EnigmaMachine object containing the plugboard, rotors and reflector specified as above
+           limitations: The ciphers cannot be changed once it is initially set. The wirings are not checked, so use try_new for
+              wirings that come from an untrusted source */
+           #[allow(clippy::too_many_arguments)]
+           pub fn new(pb_cipher: String, 
+               rw_cipher: String, rw_offset: u16, rw_setting: u16,
+               mw_cipher: String, mw_offset: u16, mw_setting: u16,
+               lw_cipher: String, lw_offset: u16, lw_setting: u16,
+               rf_cipher: String
+            ) -> EnigmaMachine {
+                EnigmaMachine {
+                    keyboard: None,
+                    plugboard: Some(Plugboard::from_cipher(&pb_cipher)),
+                    right_wheel: EnigmaWheel::new(rw_cipher, rw_offset, rw_setting),
+                    middle_wheel: EnigmaWheel::new(mw_cipher, mw_offset, mw_setting),
+                    left_wheel: EnigmaWheel::new(lw_cipher, lw_offset, lw_setting),
+                    reflector: Reflector::from_wiring(&rf_cipher),
+                    line_policy: None,
+                    orientation: Orientation::RightFast,
+                    stepping: (true, true, true),
+                    shift_layer: None,
+                    shifted: false,
+                    digit_layer: None,
+                    space_substitution: None,
+                    nonletter_policy: NonLetterPolicy::Pass,
+                    strict_crib: None,
+                    output_stage: None,
+                    ground: (rw_offset % 26, mw_offset % 26, lw_offset % 26)
+                }
+            }
+        
+        /* function: default_wehrmacht
+           inputs: none
+           output: EnigmaMachine set up as a Wehrmacht Enigma I with rotors I, II and III from left to right, reflector B, ring
+              settings and positions all at A, and no plugboard cables
+           limitations: This is a starting point for trying the machine out, not a key anyone used. Use MachineBuilder or
+              MachineConfig to choose the parts and settings
+           algorithm: the parts are those MachineBuilder uses when none are set, so the rotors' turnovers are set as well */
+           pub fn default_wehrmacht() -> EnigmaMachine {
+                MachineBuilder::new().build().unwrap()
+            }
+
+        /* function: try_new
+           inputs: the same as new
+           output: Result containing the EnigmaMachine, or an EnigmaError describing the first part whose wiring was rejected
+           limitations: The rotor wirings are only checked for length
+           algorithm: each wiring is checked as EnigmaWheel::try_new does. The reflector must also pair every letter with a different
+              letter that is wired back to it, which a rotor wiring such as rotor I's never does. Without this the machine would
+              quietly stop being reciprocal, so a message could not be deciphered with the settings that enciphered it */
+           #[allow(clippy::too_many_arguments)]
+           pub fn try_new(pb_cipher: String,
+               rw_cipher: String, rw_offset: u16, rw_setting: u16,
+               mw_cipher: String, mw_offset: u16, mw_setting: u16,
+               lw_cipher: String, lw_offset: u16, lw_setting: u16,
+               rf_cipher: String
+            ) -> Result<EnigmaMachine, EnigmaError> {
+                for (part, cipher) in [("plugboard", &pb_cipher), ("rightmost rotor", &rw_cipher), ("middle rotor", &mw_cipher), ("leftmost rotor", &lw_cipher)] {
+                    EnigmaWheel::try_new(cipher.clone(), 0, 0).map_err(|error| EnigmaError::InvalidWheel { part, error })?;
+                }
+                Reflector::try_new(&rf_cipher).map_err(EnigmaError::InvalidReflector)?;
+
+                Ok(EnigmaMachine::new(pb_cipher, rw_cipher, rw_offset, rw_setting, mw_cipher, mw_offset, mw_setting,
+                    lw_cipher, lw_offset, lw_setting, rf_cipher))
+            }
+
+        /*  function: set_reflector
+            inputs: A &Reflector containing the wiring of the reflector to fit, e.g.: a UKW-D wired in the field with from_pairs
+                    A u16 giving the position the reflector is set to, where 0 is A, stored mod 26
+            output: none
+            limitations: The reflector replaces the one the machine was built with. On an M4 that is the Greek rotor and thin reflector
+               together, which the machine holds as a single reflector, so the Greek rotor is taken out as a UKW-D would have required
+            algorithm: the position is kept by the reflector and honoured by its right_to_left. Setting a reflector that joins letters
+               in pairs to another position only relabels its contacts, so it still joins letters in pairs and the machine stays
+               reciprocal */
+            pub fn set_reflector(&mut self, reflector: &Reflector, position: u16) {
+                let mut fitted = reflector.clone();
+                fitted.set_position(position);
+                self.reflector = fitted;
+            }
+
+        /*  function: set_triggers
+            inputs: Vec<u16>s representing the turnover positions of the rightmost, middle and leftmost rotors respectively
+            output: none
+            limitations: None obvious as this time */
+            pub fn set_triggers(&mut self, rw_triggers: Vec<u16>, mw_triggers: Vec<u16>, lw_triggers: Vec<u16>) {
+                self.right_wheel.set_triggers(rw_triggers);
+                self.middle_wheel.set_triggers(mw_triggers);
+                self.left_wheel.set_triggers(lw_triggers);
+            }
+
+        /*  function: try_set_triggers
+            inputs: Vec<u16>s representing the turnover positions of the rightmost, middle and leftmost rotors respectively
+            output: Result that is Ok once the triggers are set, or an EnigmaError naming the rotor whose triggers were refused
+            limitations: None obvious as this time
+            algorithm: each rotor's triggers are checked as in EnigmaWheel::try_set_triggers on a copy of the rotor, and only set
+               once all three have passed, so refused triggers leave the machine as it was */
+            pub fn try_set_triggers(&mut self, rw_triggers: Vec<u16>, mw_triggers: Vec<u16>, lw_triggers: Vec<u16>) -> Result<(), EnigmaError> {
+                let mut wheels = [self.right_wheel.clone(), self.middle_wheel.clone(), self.left_wheel.clone()];
+                let parts = ["rightmost rotor", "middle rotor", "leftmost rotor"];
+                for ((wheel, part), triggers) in wheels.iter_mut().zip(parts).zip([rw_triggers, mw_triggers, lw_triggers]) {
+                    wheel.try_set_triggers(triggers).map_err(|error| EnigmaError::InvalidWheel { part, error })?;
+                }
+                [self.right_wheel, self.middle_wheel, self.left_wheel] = wheels;
+
+                Ok(())
+            }
+    
+        /*  function: set_ring_settings
+            inputs: u16s representing the ring settings of the rightmost, middle and leftmost rotors respectively
+            output: none
+            limitations: None obvious as this time */
+            pub fn set_ring_settings(&mut self, rw_setting: u16, mw_setting: u16, lw_setting: u16) {
+                self.right_wheel.set_ring_setting(rw_setting);
+                self.middle_wheel.set_ring_setting(mw_setting);
+                self.left_wheel.set_ring_setting(lw_setting);
+            }
+
+        /*  function: set_notch_mount
+            input: NotchMount saying whether the turnover notches of all three rotors are fixed to the ring or to the core
+            output: none
+            limitations: None obvious as this time */
+            pub fn set_notch_mount(&mut self, notch_mount: NotchMount) {
+                self.right_wheel.set_notch_mount(notch_mount);
+                self.middle_wheel.set_notch_mount(notch_mount);
+                self.left_wheel.set_notch_mount(notch_mount);
+            }
+
+        /*  function: set_turnover_letters
+            inputs: &strs containing the window letters at which the rightmost, middle and leftmost rotors turn over respectively
+            output: none
+            limitations: None obvious as this time */
+            pub fn set_turnover_letters(&mut self, rw_letters: &str, mw_letters: &str, lw_letters: &str) {
+                self.right_wheel.set_turnover_letters(rw_letters);
+                self.middle_wheel.set_turnover_letters(mw_letters);
+                self.left_wheel.set_turnover_letters(lw_letters);
+            }
+
+        /*  function: set_rotor_positions
+            inputs: u16s representing the initial positions of the rightmost, middle and leftmost rotors respectively
+            output: none
+            limitations: The positions also become the ground setting that reset returns to. Use set_message_key to turn the rotors
+               without changing the ground */
+            pub fn set_rotor_positions(&mut self, rw_position: u16, mw_position: u16, lw_position: u16) {
+                self.right_wheel.set_rotor_position(rw_position);
+                self.middle_wheel.set_rotor_position(mw_position);
+                self.left_wheel.set_rotor_position(lw_position);
+                self.ground = (self.right_wheel.rotor_position(), self.middle_wheel.rotor_position(), self.left_wheel.rotor_position());
+            }
+
+        /*  function: set_ground
+            inputs: Position giving the window letters of the Grundstellung, the ground setting, leftmost rotor first, e.g.: ('W', 'Z', 'A')
+            output: none
+            limitations: The letters must be uppercase
+            algorithm: the rotors are turned to the ground setting, and reset returns them there. Under the indicator procedure the
+               ground setting is only used to encipher the message key, which is sent at the head of the message as the indicator.
+               set_message_key then turns the rotors to the message key for the body of the message, without changing the ground, so
+               the next message's indicator is enciphered from the ground setting again after a reset */
+            pub fn set_ground(&mut self, ground: Position) {
+                let (left, middle, right) = ground;
+                self.set_rotor_positions(letter_index(right) as u16, letter_index(middle) as u16, letter_index(left) as u16);
+            }
+
+        /*  function: set_message_key
+            inputs: Position giving the window letters of the message key, leftmost rotor first, e.g.: ('S', 'X', 'T')
+            output: none
+            limitations: The letters must be uppercase. The ground setting is not changed, so reset leaves the message key, see
+               set_ground for how the two are used */
+            pub fn set_message_key(&mut self, key: Position) {
+                let (left, middle, right) = key;
+                self.right_wheel.set_rotor_position(letter_index(right) as u16);
+                self.middle_wheel.set_rotor_position(letter_index(middle) as u16);
+                self.left_wheel.set_rotor_position(letter_index(left) as u16);
+            }
+
+        /*  function: set_message_key_from_passphrase
+            inputs: A &str containing a passphrase, e.g.: "ENIGMA"
+            output: none
+            limitations: This is a convenience for demonstrations and is not cryptographically strong: there are only 17576 message
+               keys, and the hash is fast and public, so the key is easily found by trying them all. The phrase is hashed exactly as
+               given, so "enigma" and "ENIGMA" give different keys. As with set_message_key, the ground setting is not changed
+            algorithm: the UTF-8 bytes of the phrase are hashed with 64 bit FNV-1a: starting from the offset basis 0xcbf29ce484222325,
+               each byte is xored in and the hash multiplied by the prime 0x100000001b3, wrapping. The hash is then read as a number in
+               base 26, its lowest digit giving the rightmost rotor's letter, the next the middle rotor's and the next the leftmost's */
+            pub fn set_message_key_from_passphrase(&mut self, phrase: &str) {
+                let hash = phrase.bytes().fold(0xcbf2_9ce4_8422_2325u64, |hash, byte| (hash ^ byte as u64).wrapping_mul(0x0000_0100_0000_01b3));
+                let digit = |place: u32| index_letter((hash / 26u64.pow(place) % 26) as usize);
+                self.set_message_key((digit(2), digit(1), digit(0)));
+            }
+
+        /*  function: reset
+            inputs: none
+            output: none
+            limitations: Only the rotors, the shift and the digit layer's wheels are reset. Ring settings, turnovers and the plugboard
+               are left as they are */
+            pub fn reset(&mut self) {
+                let (rw_position, mw_position, lw_position) = self.ground;
+                self.right_wheel.set_rotor_position(rw_position);
+                self.middle_wheel.set_rotor_position(mw_position);
+                self.left_wheel.set_rotor_position(lw_position);
+                self.shifted = false;
+                if let Some((secondary, _)) = &mut self.shift_layer {
+                    secondary.reset();
+                }
+                if let Some(digits) = &mut self.digit_layer {
+                    digits.reset();
+                }
+            }
+
+        /*  function: set_orientation
+            inputs: Orientation selecting which end of the machine holds the fast rotor
+            output: none
+            limitations: The rotors keep their names, so with LeftFast the leftmost rotor is the fast rotor and the rightmost rotor
+               is the slow rotor. The signal still passes the plugboard first and the reflector in the middle of its path */
+            pub fn set_orientation(&mut self, orientation: Orientation) {
+                self.orientation = orientation;
+            }
+
+        /*  function: set_slot_stepping
+            inputs: bools giving whether the rightmost, middle and leftmost rotor slots step, in that order
+            output: none
+            limitations: A static slot holds its rotor like the Greek wheel of the M4, which sat in the rotor row with no ratchet for
+               a pawl to push and no notch. Its rotor keeps the position it was set to, and its turnovers are ignored, so it carries
+               nothing into the slot past it. A static middle slot thus also holds the slow rotor still. The pawl in front of a
+               static slow slot still drops into the middle rotor's notch, so the middle rotor double-steps as usual. The Greek
+               wheel itself is folded into the reflector by MachineBuilder, so it never steps whatever the flags. The slots follow
+               the rotors' names, so with LeftFast the leftmost slot is the fast one
+            algorithm: the flags are kept in the machine and read by every stepping path: step_rotors, step_back,
+               presses_until_middle_step and transform_bulk */
+            pub fn set_slot_stepping(&mut self, right: bool, middle: bool, left: bool) {
+                self.stepping = (right, middle, left);
+            }
+
+        /*  function: slot_stepping
+            inputs: none
+            output: bools giving whether the rightmost, middle and leftmost rotor slots step, in that order
+            limitations: None obvious as this time */
+            pub fn slot_stepping(&self) -> (bool, bool, bool) {
+                self.stepping
+            }
+
+        /*  function: fast_middle_slow_stepping
+            inputs: none
+            output: bools giving whether the fast, middle and slow rotor slots step, in that order
+            limitations: None obvious as this time
+            algorithm: the flags are kept by slot name, so LeftFast swaps the rightmost and leftmost ones */
+            fn fast_middle_slow_stepping(&self) -> (bool, bool, bool) {
+                let (right, middle, left) = self.stepping;
+                match self.orientation {
+                    Orientation::RightFast => (right, middle, left),
+                    Orientation::LeftFast => (left, middle, right)
+                }
+            }
+
+        /*  function: remove_plugboard
+            inputs: none
+            output: none
+            limitations: For MachineBuilder, so it can build commercial machines that were never fitted with a plugboard
+            algorithm: the plugboard wheel is dropped, and the signal goes straight to the rotors instead of through an identity wiring */
+            pub(crate) fn remove_plugboard(&mut self) {
+                self.plugboard = None;
+            }
+
+        /*  function: set_keyboard_layout
+            inputs: Option<&str> containing the keys in the order of the contacts they are wired to, so the first key is wired to
+                       contact A, e.g.: "QWERTZUIOASDFGHJKPYXCVBNML" for a keyboard wired in QWERTZ order, or None for
+                       alphabetical order
+            output: Result that is Ok if the layout was set, or an EnigmaError if it is not a permutation of the alphabet
+            limitations: The shift layer keeps its own layout
+            algorithm: each key is wired to a contact in front of the plugboard, and each contact back to its lamp behind it, so the
+               signal is taken from key to contact before the plugboard and from contact to lamp after it, the inverse wiring. The
+               machine inside is unchanged and still swaps contacts in pairs, so with the same layout at both ends messages still
+               round-trip */
+            pub fn set_keyboard_layout(&mut self, layout: Option<&str>) -> Result<(), EnigmaError> {
+                self.keyboard = match layout {
+                    Some(layout) => {
+                        EnigmaWheel::check_wiring(layout).map_err(|error| EnigmaError::InvalidWheel { part: "keyboard", error })?;
+                        let mut contacts = ['A'; 26];
+                        for (contact, key) in layout.chars().enumerate() {
+                            contacts[letter_index(key)] = index_letter(contact);
+                        }
+                        Some(Plugboard::from_cipher(&contacts.iter().collect::<String>()))
+                    }
+                    None => None
+                };
+
+                Ok(())
+            }
+
+        /*  function: set_nonletter_policy
+            inputs: NonLetterPolicy selecting what is done with characters that are not letters
+            output: none
+            limitations: The policy is applied by transform, transform_message and try_transform_message. The other transforms
+               always pass such characters through. The shift toggle is treated as a letter, since the receiver needs it */
+            pub fn set_nonletter_policy(&mut self, policy: NonLetterPolicy) {
+                self.nonletter_policy = policy;
+            }
+
+        /*  function: set_strict_crib
+            inputs: Option<&str> containing the plaintext the next message is expected to start with, or None to leave strict mode
+            output: none
+            limitations: Strict mode only checks anything while a crib is set, and only in try_transform_message. It catches a
+               ciphertext that cannot match the crib, but a ciphertext that passes may still have been corrupted or sent under other
+               settings
+            algorithm: the crib is kept and compared letter by letter against each message, as try_transform_message describes */
+            pub fn set_strict_crib(&mut self, crib: Option<&str>) {
+                self.strict_crib = crib.map(str::to_owned);
+            }
+
+        /*  function: is_key
+            inputs: A char from a message
+            output: bool that is true when the char is a letter, the toggle of the shift layer, or a digit when there is a digit layer
+            limitations: None obvious as this time */
+            fn is_key(&self, chr: char) -> bool {
+                is_cipher_letter(chr)
+                    || matches!(&self.shift_layer, Some((_, toggle)) if *toggle == chr)
+                    || (self.digit_layer.is_some() && chr.is_ascii_digit())
+            }
+
+        /*  function: plugboard_mapping
+            inputs: none
+            output: [char; 26] where the entry at each index is the letter the plugboard connects that letter of the alphabet to
+            limitations: None obvious as this time */
+            pub fn plugboard_mapping(&self) -> [char; 26] {
+                match &self.plugboard {
+                    Some(plugboard) => plugboard.mapping(),
+                    None => Plugboard::new().mapping()
+                }
+            }
+
+        /*  function: set_shift_layer
+            inputs: An EnigmaMachine used in place of this one while the shift is held
+                    A char that toggles the shift each time it appears in a message
+            output: Result that is Ok if the shift layer was set, or an EnigmaError if the toggle is an uppercase letter
+            limitations: The toggle character is passed through unchanged so the receiver shifts at the same point in the message. Each
+               layer keeps its own rotor positions and only steps while it is active. The shift starts released
+            algorithm: this models the shift key of a typewriter, where holding shift selects a second set of typebars. Here it selects a
+               second set of rotors, reflector and plugboard */
+            pub fn set_shift_layer(&mut self, secondary: EnigmaMachine, toggle: char) -> Result<(), EnigmaError> {
+                if is_cipher_letter(toggle) {
+                    return Err(EnigmaError::InvalidShiftToggle(toggle));
+                }
+                self.shift_layer = Some((Box::new(secondary), toggle));
+                self.shifted = false;
+
+                Ok(())
+            }
+
+        /*  function: set_digit_layer
+            inputs: Option<NumericMachine> that enciphers the digits of a message, or None to pass digits through unchanged again
+            output: none
+            limitations: Only press_key and the transforms built on it use the digit layer. current_lamp, the substitution and
+               fixed point functions describe the letter rotors alone, and state, set_state and to_bytes save the letter rotors
+               without the digit wheels. reset returns the digit wheels to the positions they were set up with. While the shift is
+               held, digits go to the shift layer's own digit layer, if it has one
+            algorithm: the machine has two independent paths. Letters go through the plugboard, the three rotors and the reflector,
+               and only letters step those rotors, with the middle rotor's double step. Digits go through the NumericMachine's wheels
+               and reflector, and only digits step those wheels, which turn over like an odometer each time one comes back round to
+               0. So the letters of a mixed message encipher exactly as they would with the digits taken out, and the digits as
+               they would with the letters taken out, and letters always stay letters and digits stay digits */
+            pub fn set_digit_layer(&mut self, digits: Option<NumericMachine>) {
+                self.digit_layer = digits;
+            }
+
+        /*  function: rotor_positions
+            inputs: none
+            output: u16s representing the current positions of the rightmost, middle and leftmost rotors respectively
+            limitations: None obvious as this time */
+            pub fn rotor_positions(&self) -> (u16, u16, u16) {
+                (self.right_wheel.rotor_position(), self.middle_wheel.rotor_position(), self.left_wheel.rotor_position())
+            }
+
+        /*  function: state
+            inputs: none
+            output: MachineState holding the rotor positions and shift, which are all that pressing keys changes
+            limitations: None obvious as this time
+            algorithm: copies only what pressing keys changes, so saving and restoring during a search costs far less than cloning
+               the machine with its wirings */
+            pub fn state(&self) -> MachineState {
+                MachineState {
+                    rotor_positions: self.rotor_positions(),
+                    shifted: self.shifted,
+                    shift_layer: self.shift_layer.as_ref().map(|(secondary, _)| secondary.rotor_positions())
+                }
+            }
+
+        /*  function: set_state
+            inputs: MachineState saved by state
+            output: none
+            limitations: The ground setting is not changed, so reset still returns to it. A state saved from a machine with a shift
+               layer leaves the shift layer alone when restored to a machine without one */
+            pub fn set_state(&mut self, state: MachineState) {
+                let (rw_position, mw_position, lw_position) = state.rotor_positions;
+                self.right_wheel.set_rotor_position(rw_position);
+                self.middle_wheel.set_rotor_position(mw_position);
+                self.left_wheel.set_rotor_position(lw_position);
+                self.shifted = state.shifted;
+                if let (Some((secondary, _)), Some((rw_position, mw_position, lw_position))) = (&mut self.shift_layer, state.shift_layer) {
+                    secondary.right_wheel.set_rotor_position(rw_position);
+                    secondary.middle_wheel.set_rotor_position(mw_position);
+                    secondary.left_wheel.set_rotor_position(lw_position);
+                }
+            }
+
+        /*  function: transform_returning_state
+            inputs: A &str containing the message to be transformed
+            output: A tuple of the transformed message and the MachineState the machine is left in, ready to be saved and passed to
+               set_state before the next message in the same session
+            limitations: None obvious as this time
+            algorithm: transforms the message as in transform, then reads the state as in state */
+            pub fn transform_returning_state(&mut self, message: &str) -> (String, MachineState) {
+                let transformed = self.transform(message);
+                (transformed, self.state())
+            }
+
+        /*  function: to_bytes
+            inputs: none
+            output: Vec<u8> containing the machine's wiring, ring settings, positions, triggers, notch mounts and keyboard layout in
+               the layout below
+            limitations: These settings are not saved, and a restored machine is left with their defaults: the line policy, the
+               shift layer and whether the shift is held, the digit layer, the space substitution, the non-letter policy, the strict
+               crib, the output stage and the slot stepping, so every slot of the restored machine steps. The ground setting is not
+               saved either, so the restored machine's ground is the positions the rotors stood at when it was saved. A machine
+               without a plugboard is saved with an identity plugboard, which enciphers the same
+            algorithm: the layout is always 195 bytes long:
+               bytes 0 to 2      the marker "ENG"
+               byte 3            the layout version, currently 2. Version 1 stopped after the orientation, at 165 bytes
+               bytes 4 to 35     the plugboard
+               bytes 36 to 67    the rightmost rotor
+               bytes 68 to 99    the middle rotor
+               bytes 100 to 131  the leftmost rotor
+               bytes 132 to 163  the reflector
+               byte 164          the orientation, 0 for RightFast and 1 for LeftFast
+               bytes 165 to 167  the notch mounts of the rightmost, middle and leftmost rotors, 0 for Ring and 1 for Core
+               byte 168          1 if the keyboard is wired in a layout of its own, otherwise 0
+               bytes 169 to 194  the contact each key is wired to as ASCII, from key A to key Z, or all 0 for alphabetical order
+            where each wheel is stored as EnigmaWheel::to_bytes describes. The plugboard and reflector are stored as wheels that never
+               step, with the plugboard at position A and the reflector at the position it is set to */
+            pub fn to_bytes(&self) -> Vec<u8> {
+                let mut bytes: Vec<u8> = b"ENG".to_vec();
+                bytes.push(2);
+                let plugboard = EnigmaWheel::new(self.plugboard.clone().unwrap_or_default().cipher(), 0, 0);
+                let reflector = EnigmaWheel::new(self.reflector.wiring(), self.reflector.position(), 0);
+                for wheel in [&plugboard, &self.right_wheel, &self.middle_wheel, &self.left_wheel, &reflector] {
+                    bytes.extend_from_slice(&wheel.to_bytes());
+                }
+                bytes.push(match self.orientation {
+                    Orientation::RightFast => 0,
+                    Orientation::LeftFast => 1
+                });
+                for wheel in [&self.right_wheel, &self.middle_wheel, &self.left_wheel] {
+                    bytes.push(match wheel.notch_mount() {
+                        NotchMount::Ring => 0,
+                        NotchMount::Core => 1
+                    });
+                }
+                match &self.keyboard {
+                    Some(keyboard) => {
+                        bytes.push(1);
+                        bytes.extend(keyboard.cipher().bytes());
+                    }
+                    None => bytes.extend([0; 27])
+                }
+
+                bytes
+            }
+
+        /*  function: from_bytes
+            inputs: &[u8] containing a machine saved by to_bytes
+            output: Result containing the restored EnigmaMachine, or a DecodeError describing why the bytes could not be read
+            limitations: Bytes saved in version 1 of the layout are still read, and their rotors have their notches on the ring and
+               their keyboards are in alphabetical order, as every machine had before these could be chosen */
+            pub fn from_bytes(bytes: &[u8]) -> Result<EnigmaMachine, DecodeError> {
+                if bytes.len() < 4 {
+                    return Err(DecodeError::WrongLength { expected: 195, found: bytes.len() });
+                }
+                if &bytes[..3] != b"ENG" {
+                    return Err(DecodeError::BadMagic);
+                }
+                let expected = match bytes[3] {
+                    1 => 165,
+                    2 => 195,
+                    version => return Err(DecodeError::UnsupportedVersion(version))
+                };
+                if bytes.len() != expected {
+                    return Err(DecodeError::WrongLength { expected, found: bytes.len() });
+                }
+                let parts = ["plugboard", "rightmost rotor", "middle rotor", "leftmost rotor", "reflector"];
+                let mut wheels: Vec<EnigmaWheel> = vec![];
+                for (index, part) in parts.iter().enumerate() {
+                    let start = 4 + index * 32;
+                    let wheel_bytes: &[u8; 32] = bytes[start..start + 32].try_into().unwrap();
+                    wheels.push(EnigmaWheel::from_bytes(wheel_bytes).ok_or(DecodeError::InvalidPart(part))?);
+                }
+                let orientation = match bytes[164] {
+                    0 => Orientation::RightFast,
+                    1 => Orientation::LeftFast,
+                    _ => return Err(DecodeError::InvalidPart("orientation"))
+                };
+                for (wheel, byte) in wheels[1..4].iter_mut().zip(bytes.get(165..168).unwrap_or(&[0; 3])) {
+                    wheel.set_notch_mount(match byte {
+                        0 => NotchMount::Ring,
+                        1 => NotchMount::Core,
+                        _ => return Err(DecodeError::InvalidPart("notch mount"))
+                    });
+                }
+                let keyboard = match bytes.get(168..195) {
+                    Some([1, contacts @ ..]) => {
+                        let contacts = String::from_utf8(contacts.to_vec()).map_err(|_| DecodeError::InvalidPart("keyboard"))?;
+                        EnigmaWheel::check_wiring(&contacts).map_err(|_| DecodeError::InvalidPart("keyboard"))?;
+                        Some(Plugboard::from_cipher(&contacts))
+                    }
+                    Some([0, ..]) | None => None,
+                    Some(_) => return Err(DecodeError::InvalidPart("keyboard"))
+                };
+                let ground = (wheels[1].rotor_position(), wheels[2].rotor_position(), wheels[3].rotor_position());
+                // the plugboard and reflector are read back as whatever their wheels did at position A, which takes in any ring setting
+                let wiring_at_a = |wheel: &EnigmaWheel| {
+                    let mut unset = wheel.clone();
+                    unset.set_rotor_position(0);
+                    (1..=26).map(|code| index_letter(unset.right_to_left(code) as usize + 25)).collect::<String>()
+                };
+                let mut reflector = Reflector::from_wiring(&wiring_at_a(&wheels[4]));
+                reflector.set_position(wheels[4].rotor_position());
+                let plugboard = Plugboard::from_cipher(&wiring_at_a(&wheels[0]));
+                let mut wheels = wheels.into_iter().skip(1);
+
+                Ok(EnigmaMachine {
+                    keyboard,
+                    plugboard: Some(plugboard),
+                    right_wheel: wheels.next().unwrap(),
+                    middle_wheel: wheels.next().unwrap(),
+                    left_wheel: wheels.next().unwrap(),
+                    reflector,
+                    line_policy: None,
+                    orientation,
+                    stepping: (true, true, true),
+                    shift_layer: None,
+                    shifted: false,
+                    digit_layer: None,
+                    space_substitution: None,
+                    nonletter_policy: NonLetterPolicy::Pass,
+                    strict_crib: None,
+                    output_stage: None,
+                    ground
+                })
+            }
+
+        /*  function: window_position
+            inputs: none
+            output: Position containing the letters showing in the windows of the leftmost, middle and rightmost rotors
+            limitations: None obvious as this time */
+            pub fn window_position(&self) -> Position {
+                let (right, middle, left) = self.rotor_positions();
+                let letter = |position: u16| index_letter(position as usize);
+                (letter(left), letter(middle), letter(right))
+            }
+
+        /*  function: peek_positions
+            inputs: usize giving the number of key presses to look ahead
+            output: Vec<Position> containing the window letters after each of the next k key presses, in order
+            limitations: None obvious as this time
+            algorithm: steps a clone of the machine k times, so the machine itself is not changed */
+            pub fn peek_positions(&self, k: usize) -> Vec<Position> {
+                let mut ahead = self.clone();
+                (0..k).map(|_| {
+                    ahead.step(1);
+                    ahead.window_position()
+                }).collect()
+            }
+
+        /*  function: presses_until_middle_step
+            inputs: none
+            output: usize giving which of the coming key presses first moves the middle rotor, so 1 means the very next press, or
+               usize::MAX if the middle rotor would never move
+            limitations: A static middle slot never moves, and a static fast slot never carries into it
+            algorithm: as in step_rotors, the middle rotor moves on the next press if it is itself at a turnover, the double-step.
+               Otherwise it moves on the first press that finds the fast rotor at a turnover. The fast rotor moves one place a press,
+               so its turnover mask is read forward from its current position, without stepping the machine */
+            pub fn presses_until_middle_step(&self) -> usize {
+                let (fast_steps, middle_steps, _) = self.fast_middle_slow_stepping();
+                let (fast_wheel, middle_wheel) = match self.orientation {
+                    Orientation::RightFast => (&self.right_wheel, &self.middle_wheel),
+                    Orientation::LeftFast => (&self.left_wheel, &self.middle_wheel)
+                };
+                if !middle_steps {
+                    return usize::MAX;
+                }
+                if middle_wheel.at_turnover() {
+                    return 1;
+                }
+                if !fast_steps {
+                    return usize::MAX;
+                }
+                let mask = fast_wheel.turnover_mask();
+                let position = fast_wheel.rotor_position() as usize;
+                (0..26).find(|press| mask[(position + press) % 26]).map_or(usize::MAX, |press| press + 1)
+            }
+
+        /*  function: step
+            inputs: usize giving the number of key presses to step the rotors for
+            output: none
+            limitations: None obvious as this time
+            algorithm: advances the rotors exactly as that many key presses would, double-stepping included, without enciphering.
+               The count is a usize and each rotor position is reduced modulo 26 as it steps, so no counter wraps on long messages */
+            pub fn step(&mut self, times: usize) {
+                for _ in 0..times {
+                    self.step_rotors();
+                }
+            }
+
+        /*  function: step_back
+            inputs: none
+            output: none
+            limitations: Only the rotors are moved back, not the shift layer. One position can be reached two ways, by a double-step
+               of the middle rotor or by the middle rotor not moving at all, and the double-step is chosen only when the press before
+               it carried into the middle rotor, which is how a double-step always comes about once the rotors are stepping. A machine
+               set by hand to a middle rotor at its turnover may so be stepped back to a position it never held
+            algorithm: undoes step_rotors. The fast rotor always moves back, and if it moves back off a trigger it had carried into
+               the middle rotor, which moves back too. Otherwise the middle rotor moves back only if that press was a double-step. The
+               slow rotor moves back when the middle rotor is then at a turnover, since that is the press its pawl pushed them both.
+               A static slot is never moved back, and its turnovers are ignored as in step_rotors */
+            pub fn step_back(&mut self) {
+                let (fast_steps, middle_steps, slow_steps) = self.fast_middle_slow_stepping();
+                let (fast_wheel, middle_wheel, slow_wheel) = match self.orientation {
+                    Orientation::RightFast => (&mut self.right_wheel, &mut self.middle_wheel, &mut self.left_wheel),
+                    Orientation::LeftFast => (&mut self.left_wheel, &mut self.middle_wheel, &mut self.right_wheel)
+                };
+                let fast_carried = fast_steps && fast_wheel.rotate_back();
+                // a position is reached by a carry when the position before it is at a turnover
+                let reached_by_carry = |wheel: &EnigmaWheel| wheel.turnover_mask()[(wheel.rotor_position() as usize + 25) % 26];
+                let double_stepped = fast_steps && reached_by_carry(middle_wheel) && reached_by_carry(fast_wheel);
+                if middle_steps && (fast_carried || double_stepped) {
+                    middle_wheel.rotate_back();
+                }
+                if slow_steps && middle_steps && middle_wheel.at_turnover() {
+                    slow_wheel.rotate_back();
+                }
+            }
+
+        /*  function: advance_to_offset
+            inputs: usize giving the number of letters of the message already typed
+            output: none
+            limitations: Only letters step the rotors, so n counts the letters of the message and not its spaces or other characters
+            algorithm: resets the rotors to the ground setting and steps them n times, so the machine is ready to transform the tail
+               of a message from its nth letter. Setting the positions directly would miss the double-steps of the middle rotor */
+            pub fn advance_to_offset(&mut self, n: usize) {
+                self.reset();
+                self.step(n);
+            }
+
+        /*  function: step_rotors
+            inputs: none
+            output: none
+            limitations: None obvious as this time
+            algorithm: each pawl pushes on the rotor to its left when the notch of the rotor to its right is under it. The fast rotor
+               always steps. The middle rotor steps when the fast rotor is at a turnover, and also when the middle rotor is itself at
+               a turnover, since its own notch lets the next pawl push both it and the slow rotor. This second case is the double-step:
+               the middle rotor steps on two presses in a row. The turnovers are checked before any rotor moves, as the pawls all
+               engage at the same time. When the fast and middle rotors are both at a turnover on the same press, two pawls push on the
+               middle rotor at once, but it still only moves one place, and the slow rotor moves one place with it. A static slot,
+               set by set_slot_stepping, has no ratchet and no notch, so it never moves and its turnovers are ignored. The pawl in
+               front of a static slow slot still pushes the middle rotor's notch, so the double-step is kept */
+            fn step_rotors(&mut self) {
+                let (fast_steps, middle_steps, slow_steps) = self.fast_middle_slow_stepping();
+                let (fast_wheel, middle_wheel, slow_wheel) = match self.orientation {
+                    Orientation::RightFast => (&mut self.right_wheel, &mut self.middle_wheel, &mut self.left_wheel),
+                    Orientation::LeftFast => (&mut self.left_wheel, &mut self.middle_wheel, &mut self.right_wheel)
+                };
+                let fast_at_turnover = fast_steps && fast_wheel.at_turnover();
+                let middle_at_turnover = middle_steps && middle_wheel.at_turnover();
+                if fast_steps {
+                    fast_wheel.rotate();
+                }
+                // either pawl, or both together, moves the middle rotor a single place
+                if middle_steps && (fast_at_turnover || middle_at_turnover) {
+                    middle_wheel.rotate();
+                }
+                if middle_at_turnover && slow_steps {
+                    slow_wheel.rotate();
+                }
+            }
+
+        /*  function: press_key
+            inputs: A char representing the key pressed on the keyboard
+            output: A char representing the lamp lit by the key press
+            limitations: Only uppercase letters step the rotors and are transformed. Any other character is returned unchanged
+            algorithm: while the shift is held the key is passed on to the shift layer instead. Otherwise steps the rotors as step_rotors describes, then traces the signal as trace
+               describes */
+            pub fn press_key(&mut self, key: char) -> char {
+                self.try_press_key(key).unwrap_or_else(|error| panic!("{}", error))
+            }
+
+        /*  function: try_press_key
+            inputs: A char representing the key pressed on the keyboard
+            output: Result containing the char of the lamp lit by the key press, or an EnigmaError if the signal reached no lamp
+            limitations: As in press_key. The rotors have already stepped when an error is returned
+            algorithm: as press_key, with the lamp found as try_trace describes */
+            fn try_press_key(&mut self, key: char) -> Result<char, EnigmaError> {
+                if let Some((secondary, toggle)) = &mut self.shift_layer {
+                    if key == *toggle {
+                        self.shifted = !self.shifted;
+                        return Ok(key);
+                    }
+                    if self.shifted {
+                        return secondary.try_press_key(key);
+                    }
+                }
+                if is_cipher_letter(key) {
+                    self.step_rotors();
+                    self.try_trace(key)
+                } else if let Some(digits) = &mut self.digit_layer {
+                    Ok(digits.press_key(key))
+                } else {
+                    Ok(key)
+                }
+            }
+
+        /*  function: current_lamp
+            inputs: A char representing a key that is being held down
+            output: A char representing the lamp that key lights with the rotors where they stand now
+            limitations: Characters that are not uppercase letters are returned unchanged, as in press_key
+            algorithm: pressing a key first steps the rotors and only then closes the circuit, so press_key lights the lamp of the
+               position after the step. A key held down after it was pressed keeps that lamp lit without stepping again, and this is
+               that lamp: the substitution of the present position, with no step. So current_lamp(k) after press_key(k) gives the
+               lamp press_key just lit, while current_lamp(k) before press_key(k) is the lamp of the position before, which is
+               generally not what press_key will light */
+            pub fn current_lamp(&self, key: char) -> char {
+                if let Some((secondary, _)) = &self.shift_layer {
+                    if self.shifted {
+                        return secondary.current_lamp(key);
+                    }
+                }
+                if is_cipher_letter(key) {
+                    self.trace(key)
+                } else {
+                    key
+                }
+            }
+
+        /*  function: press_key_detailed
+            inputs: A char representing the key pressed on the keyboard
+            output: KeyResult holding the lamp lit, the window letters after the press, and which rotors stepped
+            limitations: As in press_key. While the shift is held the key goes to the shift layer, so no rotors are shown stepping
+            algorithm: presses the key as press_key does and compares the rotor positions before and after, since a rotor that steps
+               always moves to a different position */
+            pub fn press_key_detailed(&mut self, key: char) -> KeyResult {
+                let (right_before, middle_before, left_before) = self.rotor_positions();
+                let lamp = self.press_key(key);
+                let (right_after, middle_after, left_after) = self.rotor_positions();
+
+                KeyResult {
+                    output: if is_cipher_letter(key) { Some(lamp) } else { None },
+                    positions: self.window_position(),
+                    left_stepped: left_before != left_after,
+                    middle_stepped: middle_before != middle_after,
+                    right_stepped: right_before != right_after
+                }
+            }
+
+        /*  function: trace
+            inputs: A char holding an uppercase letter
+            output: A char representing the lamp the letter lights with the rotors where they stand, without stepping them
+            limitations: Panics if the signal reaches no lamp, see try_trace
+            algorithm: traces the signal from the key to its contact, from the plugboard through the rotors to the reflector and back
+               out through the rotors and plugboard again, and from the contact to its lamp. Both plugboard passes are skipped when
+               there is no plugboard, and both keyboard passes when the keyboard is in alphabetical order */
+            fn trace(&self, key: char) -> char {
+                self.try_trace(key).unwrap_or_else(|error| panic!("{}", error))
+            }
+
+        /*  function: try_trace
+            inputs: A char holding an uppercase letter
+            output: Result containing the char of the lamp the letter lights, or an EnigmaError::InvalidContact naming the contact
+               the signal left at if it has no lamp
+            limitations: None obvious as this time
+            algorithm: as trace, with the contact the signal leaves at turned into a lamp as contact_lamp describes */
+            fn try_trace(&self, key: char) -> Result<char, EnigmaError> {
+                let mut code: u16 = letter_index(key) as u16 + 1;
+                if let Some(keyboard) = &self.keyboard {
+                    code = keyboard.right_to_left(code);
+                }
+                let mut pos = match &self.plugboard {
+                    Some(plugboard) => plugboard.left_to_right(self.scramble(plugboard.right_to_left(code))),
+                    None => self.scramble(code)
+                };
+                if let Some(keyboard) = &self.keyboard {
+                    pos = keyboard.left_to_right(pos);
+                }
+
+                contact_lamp(pos)
+            }
+
+        /*  function: to_dot
+            inputs: none
+            output: A String holding a Graphviz DOT digraph of the signal paths with the rotors where they stand, e.g.: for rendering
+               with "dot -Tsvg"
+            limitations: Only the path toward the reflector is drawn for each part, the return path is the same wiring followed
+               backwards. The shift layer, entry wheel settings and output stage are not drawn
+            algorithm: the signal passes a column of 26 contacts between each part, starting from the keys. Each column is drawn as a
+               cluster of nodes named after the part the signal has just left, e.g.: middle_Q, and each part, plugboard first when
+               there is one, is drawn as 26 edges from one column to the next, traced as in scramble. The reflector sends the signal
+               back into the last column, so it is drawn as 26 red edges within that column, one each way for each pair it joins */
+            pub fn to_dot(&self) -> String {
+                let (fast_wheel, middle_wheel, slow_wheel) = match self.orientation {
+                    Orientation::RightFast => ((&self.right_wheel, "right"), (&self.middle_wheel, "middle"), (&self.left_wheel, "left")),
+                    Orientation::LeftFast => ((&self.left_wheel, "left"), (&self.middle_wheel, "middle"), (&self.right_wheel, "right"))
+                };
+                let traced = |path: &dyn Fn(u16) -> u16| -> [u16; 26] { std::array::from_fn(|index| path(index as u16 + 1)) };
+                let mut parts: Vec<([u16; 26], &str)> = vec![];
+                if let Some(plugboard) = &self.plugboard {
+                    parts.push((traced(&|code| plugboard.right_to_left(code)), "plugboard"));
+                }
+                for (wheel, name) in [fast_wheel, middle_wheel, slow_wheel] {
+                    parts.push((traced(&|code| wheel.right_to_left(code)), name));
+                }
+                let letter = |code: u16| index_letter(code as usize + 25);
+
+                let mut dot = String::from("digraph enigma {\n    rankdir=LR;\n");
+                for column in std::iter::once("keys").chain(parts.iter().map(|(_, name)| *name)) {
+                    dot.push_str(&format!("    subgraph cluster_{} {{\n        label=\"{}\";\n", column, column));
+                    for code in 1..=26 {
+                        dot.push_str(&format!("        {}_{} [label=\"{}\"];\n", column, letter(code), letter(code)));
+                    }
+                    dot.push_str("    }\n");
+                }
+                let mut previous = "keys";
+                for (outputs, name) in &parts {
+                    for code in 1..=26 {
+                        dot.push_str(&format!("    {}_{} -> {}_{};\n", previous, letter(code), name, letter(outputs[code as usize - 1])));
+                    }
+                    previous = name;
+                }
+                for code in 1..=26 {
+                    dot.push_str(&format!("    {}_{} -> {}_{} [color=red];\n", previous, letter(code), previous, letter(self.reflector.right_to_left(code))));
+                }
+                dot.push_str("}\n");
+
+                dot
+            }
+
+        /*  function: scramble
+            inputs: u16 giving the contact the signal enters the rotors at, counted from 1
+            output: u16 giving the contact the signal leaves the rotors at, counted from 1 and taken mod 26
+            limitations: None obvious as this time
+            algorithm: traces the signal through the rotors to the reflector and back, leaving out the plugboard */
+            fn scramble(&self, code: u16) -> u16 {
+                let (fast_wheel, middle_wheel, slow_wheel) = match self.orientation {
+                    Orientation::RightFast => (&self.right_wheel, &self.middle_wheel, &self.left_wheel),
+                    Orientation::LeftFast => (&self.left_wheel, &self.middle_wheel, &self.right_wheel)
+                };
+                let pos = &fast_wheel.right_to_left(code);
+                let pos = &middle_wheel.right_to_left(*pos);
+                let pos = &slow_wheel.right_to_left(*pos);
+                let pos = &self.reflector.right_to_left(*pos);
+                let pos = &slow_wheel.left_to_right(*pos);
+                let pos = &middle_wheel.left_to_right(*pos);
+
+                fast_wheel.left_to_right(*pos)
+            }
+
+        /*  function: substitution
+            inputs: none
+            output: [u8; 26] where the entry at each index is the letter (0 is A) that letter is swapped with by the machine, plugboard
+               included, where the rotors stand
+            limitations: The rotors are not stepped, as in fixed_points, so this is the substitution used by the last key press */
+            pub fn substitution(&self) -> [u8; 26] {
+                let mut substitution = [0u8; 26];
+                for (index, entry) in substitution.iter_mut().enumerate() {
+                    *entry = letter_index(self.trace(index_letter(index))) as u8;
+                }
+                substitution
+            }
+
+        /*  function: scrambler
+            inputs: none
+            output: [u8; 26] where the entry at each index is the letter (0 is A) the rotors and reflector, where they stand, swap that
+               letter with, as if no plugboard cables were fitted
+            limitations: The rotors are not stepped, as in fixed_points */
+            pub(crate) fn scrambler(&self) -> [u8; 26] {
+                let mut substitution = [0u8; 26];
+                for (index, entry) in substitution.iter_mut().enumerate() {
+                    *entry = ((self.scramble(index as u16 + 1) + 25) % 26) as u8;
+                }
+                substitution
+            }
+
+        /*  function: fixed_points
+            inputs: none
+            output: Vec<char> containing every letter that the rotors, where they stand, would encipher to itself
+            limitations: The rotors are not stepped, so this checks the substitution used by the last key press rather than the next
+               one. A working machine never enciphers a letter to itself, because the reflector has no letter wired to itself, so
+               this should always be empty. Useful in assertions while debugging wirings */
+            pub fn fixed_points(&self) -> Vec<char> {
+                ('A'..='Z').filter(|letter| self.trace(*letter) == *letter).collect()
+            }
+
+        /*  function: is_reciprocal
+            inputs: none
+            output: bool that is true if the reflector and plugboard each swap letters in pairs
+            limitations: The digit layer is not checked. A shift layer is checked the same way. Unlike a probe message, this holds
+               for every position at once and does not step the rotors. MachineBuilder folds an entry wheel into the plugboard, so
+               a Swiss K, whose entry wheel is wired as a cipher rather than in pairs, is reported as not reciprocal even though it
+               deciphers its own output
+            algorithm: the signal goes out through the plugboard and rotors, is sent back by the reflector, and returns through each
+               of them wired the other way, so the reflector must be an involution, with r(r(c)) = c for each contact c. A plugboard
+               is a set of cables, each joining two sockets both ways, so it is checked the same way, with p(p(l)) = l for each
+               letter l. The keyboard layout only renames the keys, being read one way on the way in and the other way on the way
+               out, so it is not checked. 26 contacts are checked for each part */
+            pub fn is_reciprocal(&self) -> bool {
+                let involution = |table: [u8; 26]| (0..26).all(|contact| table[table[contact] as usize] as usize == contact);
+                let plugboard_paired = self.plugboard.as_ref().is_none_or(|plugboard| involution(plugboard.tables().0));
+                let shift_reciprocal = self.shift_layer.as_ref().is_none_or(|(secondary, _)| secondary.is_reciprocal());
+
+                involution(self.reflector.table()) && plugboard_paired && shift_reciprocal
+            }
+
+        /*  function: transform
+            inputs: A &str containing the message to be transformed, either by encoding or decoding
+            output: A String containing the message after encoding or decoding
+            limitations: None obvious as this time
+            algorithm: THE ENIGMA IS RECIPROCAL. Enciphering and deciphering are the same operation: the reflector sends every signal
+               back along a different path, so at any one rotor position the machine swaps letters in pairs, and a letter that
+               enciphers to another deciphers back from it. Transforming the ciphertext from the same starting positions gives back
+               the plaintext, which is why the machine deliberately has no separate encipher and decipher to fall out of step with
+               each other. encrypt and decrypt are this same transform, only writing spaces as a letter and reading them back.
+               Characters that are not letters are passed through or dropped as the non-letter policy says, where Error passes them */
+            pub fn transform(&mut self, message: &str) -> String {
+                match self.nonletter_policy {
+                    NonLetterPolicy::Drop => message.chars()
+                        .filter(|chr| self.is_key(*chr))
+                        .collect::<Vec<char>>()
+                        .into_iter()
+                        .map(|chr| self.press_key(chr))
+                        .collect(),
+                    _ => message.chars().map(|chr| self.press_key(chr)).collect()
+                }
+            }
+
+        /*  function: try_transform_message
+            inputs: A &str containing the message to be transformed, either by encoding or decoding
+            output: Result containing the transformed message, or an EnigmaError naming the first character that is not a letter
+               when the non-letter policy is Error, or the first letter the message shares with the strict crib
+            limitations: None obvious as this time
+            algorithm: the whole message is checked before any key is pressed, so a refused message leaves the rotors where they
+               were. In strict mode the letters of the message are paired in turn with the letters of the crib, and a pair of the
+               same letter is refused: the reflector never joins a letter to itself, so a letter can never decrypt to itself, and
+               a ciphertext letter matching the plaintext expected there means corruption or the wrong settings. Otherwise the message
+               is transformed as in transform on a copy of the machine, which replaces the machine only if every signal reached a
+               lamp, so an InvalidContact also leaves the rotors where they were */
+            pub fn try_transform_message(&mut self, message: &str) -> Result<String, EnigmaError> {
+                if self.nonletter_policy == NonLetterPolicy::Error {
+                    if let Some(chr) = message.chars().find(|chr| !self.is_key(*chr)) {
+                        return Err(EnigmaError::NonLetter(chr));
+                    }
+                }
+                if let Some(crib) = &self.strict_crib {
+                    let letters = |text: &str| text.chars().filter(|chr| is_cipher_letter(*chr)).collect::<Vec<char>>();
+                    let clash = letters(message).into_iter().zip(letters(crib)).position(|(cipher, plain)| cipher == plain);
+                    if let Some(position) = clash {
+                        return Err(EnigmaError::SelfEncipherment { position, letter: letters(message)[position] });
+                    }
+                }
+
+                let keys: Vec<char> = message.chars()
+                    .filter(|chr| self.nonletter_policy != NonLetterPolicy::Drop || self.is_key(*chr))
+                    .collect();
+                let mut machine = self.clone();
+                let transformed = keys.into_iter().map(|key| machine.try_press_key(key)).collect::<Result<String, EnigmaError>>()?;
+                *self = machine;
+
+                Ok(transformed)
+            }
+
+        /*  function: transform_fields
+            inputs: A &[&str] containing the fields of a record, e.g.: the sender, subject and body of a message
+            output: A Vec<String> containing each field transformed, in the same order
+            limitations: The fields are not keyed independently. Each one continues from the rotor positions the last one left, so
+               they must be transformed back in the same order, from the same starting positions
+            algorithm: each field is transformed as in transform. Since characters that are not letters never step the rotors, this
+               is the same as transforming the fields joined by a separator that is not a letter, e.g.: "|", and splitting the
+               result at the separators */
+            pub fn transform_fields(&mut self, fields: &[&str]) -> Vec<String> {
+                fields.iter().map(|field| self.transform(field)).collect()
+            }
+
+        /*  function: transform_batch
+            inputs: A &[&str] containing messages sent with the same key, e.g.: the traffic of one day
+            output: A Vec<String> containing each message transformed, in the same order
+            limitations: Every message starts from the ground setting, so with the indicator procedure abandoned they all share one
+               keystream, which is insecure for real traffic. The machine is left where the last message ends
+            algorithm: the machine is reset before each message and the message is transformed as in transform. Unlike transform_fields,
+               which continues each field from where the last one stopped, each message can be read back on its own from the ground
+               setting, in any order */
+            pub fn transform_batch(&mut self, messages: &[&str]) -> Vec<String> {
+                messages.iter().map(|message| {
+                    self.reset();
+                    self.transform(message)
+                }).collect()
+            }
+
+        /*  function: transform_with_errors
+            inputs: A &str containing the message to be transformed
+                    An f64 giving the chance, from 0.0 to 1.0, that each letter is keyed wrongly
+                    A &mut Rng supplying the random choices, so a seeded generator gives the same errors each time
+            output: A String containing the message transformed after the errors were made
+            limitations: This is for generating training data for codebreakers, not for sending traffic, since the output no longer
+               decrypts to the message. Only letters are perturbed, other characters are left as transform would treat them
+            algorithm: each letter is kept with probability 1 - error_rate. Otherwise, as an operator might, the wrong key is pressed
+               in its place or it is skipped, each equally likely. The perturbed message is then transformed as usual */
+            pub fn transform_with_errors(&mut self, message: &str, error_rate: f64, rng: &mut impl Rng) -> String {
+                let mut keyed = String::with_capacity(message.len());
+                for chr in message.chars() {
+                    if !is_cipher_letter(chr) || rng.next_f64() >= error_rate {
+                        keyed.push(chr);
+                    } else if rng.below(2) == 0 {
+                        keyed.push(index_letter(rng.below(26) as usize));
+                    }
+                }
+                self.transform(&keyed)
+            }
+
+        /*  function: transform_message
+            inputs: A String containing the message to be transformed, either by encoding or decoding 
+            output: A string containing the message after encoding or decoding
+            limitations: None obvious as this time
+            algorithm: the same as transform, see there for why one method serves for both directions */
+            pub fn transform_message(&mut self, message: String) -> String{
+                self.transform(&message)
+            }
+
+        /*  function: transform_latin1
+            inputs: A &[u8] containing the message to be transformed in a single byte encoding such as Latin-1 or Windows-1252
+            output: A Vec<u8> containing the transformed message, the same length as the input
+            limitations: Only bytes 65 to 90, the uppercase letters A to Z, are transformed. Every other byte, including the accented
+               letters above 127, is copied unchanged, so lowercase and accented text should be converted to A to Z first
+            algorithm: all of these encodings agree with ASCII below 128, so the bytes are transformed as in transform_bulk without
+               ever being read as UTF-8 */
+            pub fn transform_latin1(&mut self, input: &[u8]) -> Vec<u8> {
+                let mut output = vec![0u8; input.len()];
+                self.transform_bulk(input, &mut output);
+                output
+            }
+
+        /*  function: transform_bulk
+            inputs: A &[u8] containing the message to be transformed as ASCII bytes
+                    A &mut [u8] the transformed message is written to, which must be at least as long as the input
+            output: none
+            limitations: Panics if output is shorter than input. As in press_key, only uppercase letters step the rotors and are
+               transformed, and every other byte is copied unchanged. A machine with a shift layer or a digit layer is handled a key
+               at a time through press_key, so it gets none of the speedup
+            algorithm: the same substitution as press_key, but without allocating and with the wheel state held in locals. Each wheel's
+               lookup tables are laid out twice over so the rotor position can be added to a contact without reducing it mod 26, and
+               a second table does the reduction after the position is taken away again. The keyboard, plugboard and reflector never
+               move, so the keyboard is folded into the plugboard and each is collapsed into a single table up front. The rotors
+               step using their turnover masks, adding the turnover flags to the positions instead of branching on them. On a 10 MB
+               message in a release build this ran about seven times faster than transform_message, which pushes each character
+               onto a String and divides to wrap every contact */
+            pub fn transform_bulk(&mut self, input: &[u8], output: &mut [u8]) {
+                assert!(output.len() >= input.len(), "output holds {} bytes but the input has {}", output.len(), input.len());
+                if self.shift_layer.is_some() || self.digit_layer.is_some() {
+                    for (byte, out) in input.iter().zip(output.iter_mut()) {
+                        *out = self.press_key(*byte as char) as u8;
+                    }
+                    return;
+                }
+
+                let mut wrap = [0u8; 52];
+                for (index, entry) in wrap.iter_mut().enumerate() {
+                    *entry = (index % 26) as u8;
+                }
+                let doubled = |table: &[u8; 26]| {
+                    let mut twice = [0u8; 52];
+                    twice[..26].copy_from_slice(table);
+                    twice[26..].copy_from_slice(table);
+                    twice
+                };
+                let (mut plug_in, mut plug_out) = self.plugboard.clone().unwrap_or_default().tables();
+                if let Some(keyboard) = &self.keyboard {
+                    let (key_in, key_out) = keyboard.tables();
+                    plug_in = key_in.map(|contact| plug_in[contact as usize]);
+                    plug_out = plug_out.map(|contact| key_out[contact as usize]);
+                }
+                let reflect = self.reflector.table();
+
+                // a static slot adds no step, and its turnovers are cleared so it carries nothing
+                let (fast_steps, middle_steps, slow_steps) = self.fast_middle_slow_stepping();
+                let (fast_step, middle_step, slow_step) = (fast_steps as usize, middle_steps as usize, slow_steps as usize);
+                let (fast_wheel, middle_wheel, slow_wheel) = match self.orientation {
+                    Orientation::RightFast => (&mut self.right_wheel, &mut self.middle_wheel, &mut self.left_wheel),
+                    Orientation::LeftFast => (&mut self.left_wheel, &mut self.middle_wheel, &mut self.right_wheel)
+                };
+                let (fast_forward, fast_inverse) = (doubled(fast_wheel.tables().0), doubled(fast_wheel.tables().1));
+                let (middle_forward, middle_inverse) = (doubled(middle_wheel.tables().0), doubled(middle_wheel.tables().1));
+                let (slow_forward, slow_inverse) = (doubled(slow_wheel.tables().0), doubled(slow_wheel.tables().1));
+                let fast_mask = fast_wheel.turnover_mask().map(|turnover| turnover && fast_steps);
+                let middle_mask = middle_wheel.turnover_mask().map(|turnover| turnover && middle_steps);
+                let mut fast = fast_wheel.rotor_position() as usize;
+                let mut middle = middle_wheel.rotor_position() as usize;
+                let mut slow = slow_wheel.rotor_position() as usize;
+
+                for (byte, out) in input.iter().zip(output.iter_mut()) {
+                    if !is_cipher_letter(*byte as char) {
+                        *out = *byte;
+                        continue;
+                    }
+                    let fast_turn = fast_mask[fast] as usize;
+                    let middle_turn = middle_mask[middle] as usize;
+                    fast = wrap[fast + fast_step] as usize;
+                    middle = wrap[middle + ((fast_turn | middle_turn) & middle_step)] as usize;
+                    slow = wrap[slow + (middle_turn & slow_step)] as usize;
+
+                    let contact = plug_in[(byte - ALPHABET_BASE) as usize] as usize;
+                    let contact = wrap[fast_forward[contact + fast] as usize + 26 - fast] as usize;
+                    let contact = wrap[middle_forward[contact + middle] as usize + 26 - middle] as usize;
+                    let contact = wrap[slow_forward[contact + slow] as usize + 26 - slow] as usize;
+                    let contact = reflect[contact] as usize;
+                    let contact = wrap[slow_inverse[contact + slow] as usize + 26 - slow] as usize;
+                    let contact = wrap[middle_inverse[contact + middle] as usize + 26 - middle] as usize;
+                    let contact = wrap[fast_inverse[contact + fast] as usize + 26 - fast] as usize;
+                    *out = plug_out[contact] + ALPHABET_BASE;
+                }
+
+                fast_wheel.set_rotor_position(fast as u16);
+                middle_wheel.set_rotor_position(middle as u16);
+                slow_wheel.set_rotor_position(slow as u16);
+            }
+
+        /*  function: transform_cow
+            inputs: A &str containing the message to be transformed, either by encoding or decoding
+            output: A Cow<str> containing the message after encoding or decoding
+            limitations: None obvious as this time
+            algorithm: a message with no keys on the machine, neither uppercase letters nor the shift toggle nor digits when there is
+               a digit layer, steps nothing, so it is returned as Borrowed without allocating: as it is, or empty under the Drop
+               non-letter policy. Any other message is transformed as in transform, following the non-letter policy, and returned as
+               Owned */
+            pub fn transform_cow<'a>(&mut self, message: &'a str) -> Cow<'a, str> {
+                if message.chars().any(|chr| self.is_key(chr)) {
+                    Cow::Owned(self.transform(message))
+                } else if self.nonletter_policy == NonLetterPolicy::Drop {
+                    Cow::Borrowed(&message[..0])
+                } else {
+                    Cow::Borrowed(message)
+                }
+            }
+
+        /*  function: transform_ungrouped
+            inputs: A &str containing a message that was received in groups, e.g.: "QMJID OMZWZ JFJR"
+            output: A String containing the transformed message with the grouping removed
+            limitations: All whitespace is treated as an artifact of the grouping, so word breaks in the original message are lost
+            algorithm: whitespace is stripped from the message, then the remaining characters are transformed as in transform_message */
+            pub fn transform_ungrouped(&mut self, message: &str) -> String {
+                message.chars()
+                    .filter(|chr| !chr.is_whitespace())
+                    .map(|chr| self.press_key(chr))
+                    .collect()
+            }
+
+        /*  function: set_line_policy
+            inputs: Option<usize> giving the number of groups written on each line of grouped output, or None to write every group on
+               a single line
+            output: none
+            limitations: A policy of Some(0) is treated the same as None */
+            pub fn set_line_policy(&mut self, groups_per_line: Option<usize>) {
+                self.line_policy = groups_per_line;
+            }
+
+        /*  function: set_space_substitution
+            inputs: Option<char> giving the letter operators wrote in place of a space, e.g.: Some('X'), or None to leave spaces alone
+            output: none
+            limitations: The substitution is only applied by encrypt and decrypt */
+            pub fn set_space_substitution(&mut self, letter: Option<char>) {
+                self.space_substitution = letter;
+            }
+
+        /*  function: with_output_stage
+            inputs: Option<EnigmaWheel> used as a fixed substitution after the machine, or None for no output stage
+            output: the EnigmaMachine, so the call can be chained after a constructor
+            limitations: The stage is only applied by encrypt and decrypt, since transform is its own inverse and a stage after it
+               is not. The stage never rotates, so it enciphers at the position and ring setting it was given
+            algorithm: encrypt passes its ciphertext through the stage's encipher, and decrypt takes it back out with the stage's
+               decipher before transforming, so layered experiments still read back. Passing None removes any stage */
+            pub fn with_output_stage(mut self, stage: Option<EnigmaWheel>) -> EnigmaMachine {
+                self.output_stage = stage;
+                self
+            }
+
+        /*  function: encrypt
+            inputs: A &str containing the plaintext
+            output: A String containing the ciphertext
+            limitations: None obvious as this time
+            algorithm: each space is replaced with the space substitution letter, if one is set, then the message is transformed as in
+               transform_message, and passed through the output stage, if one is set */
+            pub fn encrypt(&mut self, plaintext: &str) -> String {
+                let ciphertext: String = match self.space_substitution {
+                    Some(letter) => plaintext.chars().map(|chr| self.press_key(if chr == ' ' { letter } else { chr })).collect(),
+                    None => self.transform(plaintext)
+                };
+                match &self.output_stage {
+                    Some(stage) => stage.encipher(&ciphertext),
+                    None => ciphertext
+                }
+            }
+
+        /*  function: decrypt
+            inputs: A &str containing the ciphertext
+            output: A String containing the plaintext
+            limitations: This is only a best effort. The plaintext cannot show which letters were spaces and which were the letter
+               itself, so every occurrence of the space substitution letter becomes a space, e.g.: with X for space "EXTRA X" is
+               decrypted as "E TRA  "
+            algorithm: the output stage is taken off the message, if one is set, then the message is transformed as in
+               transform_message, and each space substitution letter is replaced with a space, if one is set */
+            pub fn decrypt(&mut self, ciphertext: &str) -> String {
+                let plaintext = match &self.output_stage {
+                    Some(stage) => {
+                        let ciphertext = stage.decipher(ciphertext);
+                        self.transform(&ciphertext)
+                    }
+                    None => self.transform(ciphertext)
+                };
+                match self.space_substitution {
+                    Some(letter) => plaintext.replace(letter, " "),
+                    None => plaintext
+                }
+            }
+
+        /*  function: encrypt_and_verify
+            inputs: A &str containing the plaintext
+            output: A tuple of the ciphertext and a bool that is true if the ciphertext decrypts back to the plaintext
+            limitations: The machine is reset to its ground setting first, so the message always starts from there. Settings that
+               lose part of the message also give false, since it cannot come back: characters dropped by the non-letter policy,
+               and the space substitution letter used in the plaintext itself
+            algorithm: a copy of the machine is kept at the ground setting while the message is encrypted as in encrypt. The copy
+               then decrypts the ciphertext as in decrypt, which gives the plaintext back whenever the settings are self-consistent,
+               since the machine is reciprocal. A reflector that is not reciprocal, for one, breaks this */
+            pub fn encrypt_and_verify(&mut self, message: &str) -> (String, bool) {
+                self.reset();
+                let mut checker = self.clone();
+                let ciphertext = self.encrypt(message);
+                let verified = checker.decrypt(&ciphertext) == message;
+
+                (ciphertext, verified)
+            }
+
+        /*  function: output_histogram
+            inputs: usize giving the number of times to press the key
+            output: [u32; 26] where the entry at each index counts the presses that lit that letter (0 is A)
+            limitations: The key pressed is always A, so its own entry is always 0, since the machine never enciphers a letter to
+               itself. The machine is left in the state it was in before, as if no key had been pressed
+            algorithm: A is pressed sample_len times from the current state and each lamp is tallied. Over a full cycle of the rotors,
+               16900 presses on a machine with one notch on each rotor, a working machine lights each of the other 25 letters a
+               similar number of times. A wiring mistake that leaves the substitution stuck in short cycles shows up as letters lit
+               far more or less often than the rest */
+            pub fn output_histogram(&mut self, sample_len: usize) -> [u32; 26] {
+                let saved = self.state();
+                let mut histogram = [0u32; 26];
+                for _ in 0..sample_len {
+                    let lamp = self.press_key('A');
+                    if is_cipher_letter(lamp) {
+                        histogram[letter_index(lamp)] += 1;
+                    }
+                }
+                self.set_state(saved);
+
+                histogram
+            }
+
+        /*  function: decrypt_verified
+            inputs: A &str containing the ciphertext
+                    f64 giving the lowest score accepted as language. English scores about -3.9 and random letters about -4.3, so
+                       -4.15 separates them for messages of a few dozen letters or more
+            output: Result containing the plaintext, or a VerifyError if it scores below min_score
+            limitations: The score is measured against English, so German plaintext needs a lower threshold. Plaintext with fewer
+               than three letters has no trigrams and is always refused. The rotors are stepped either way
+            algorithm: the message is decrypted as in decrypt, then scored with analysis::ngram_score using trigrams. The sum is
+               divided by the number of trigrams, so one threshold serves messages of any length. A wrong key gives random looking
+               letters, which score well below any real language */
+            pub fn decrypt_verified(&mut self, ciphertext: &str, min_score: f64) -> Result<String, VerifyError> {
+                let plaintext = self.decrypt(ciphertext);
+                let trigrams = plaintext.chars().filter(|chr| is_cipher_letter(*chr)).count().saturating_sub(2);
+                let score = if trigrams == 0 { f64::NEG_INFINITY } else { ngram_score(&plaintext, 3) / trigrams as f64 };
+                if score < min_score {
+                    return Err(VerifyError::LowScore { score, min_score });
+                }
+
+                Ok(plaintext)
+            }
+
+        /*  function: decrypt_dir
+            inputs: A &Path of a directory holding one message in each file
+                    A &Path of a directory to write the plaintexts to, which must already exist
+            output: io::Result holding the number of files decrypted, or the first error met reading or writing a file
+            limitations: Subdirectories are skipped rather than searched. Each plaintext is written under the same file name as its
+               message, replacing any file already there, so out_dir should not be dir. Files that are not UTF-8 stop the batch with
+               an error, and files decrypted before it are left written
+            algorithm: every message of a day was sent from its own message key, so the messages are independent. The machine is
+               reset to the ground setting before each file, in name order, and the file is decrypted as in decrypt */
+            pub fn decrypt_dir(&mut self, dir: &Path, out_dir: &Path) -> io::Result<usize> {
+                let mut paths: Vec<_> = fs::read_dir(dir)?
+                    .map(|entry| entry.map(|entry| entry.path()))
+                    .collect::<io::Result<_>>()?;
+                paths.retain(|path| path.is_file());
+                paths.sort();
+                for path in &paths {
+                    let ciphertext = fs::read_to_string(path)?;
+                    self.reset();
+                    let plaintext = self.decrypt(&ciphertext);
+                    fs::write(out_dir.join(path.file_name().unwrap()), plaintext)?;
+                }
+
+                Ok(paths.len())
+            }
+
+        /*  function: transform_keep_layout
+            inputs: A &str containing the message to be transformed, either by encoding or decoding
+            output: A String containing the message after encoding or decoding, with every character that is not a letter, including
+               spaces and newlines, at the same place it had in the input
+            limitations: None obvious as this time
+            algorithm: each letter is transformed as in transform and every other character is copied through, whatever the
+               non-letter policy says. This is transform's behaviour under the default Pass policy, kept as its own method for callers
+               who rely on the layout. transform_grouped is the opposite, dropping the layout and writing five letter groups, as does
+               transform under the Drop policy, which strips it */
+            pub fn transform_keep_layout(&mut self, message: &str) -> String {
+                message.chars().map(|chr| self.press_key(chr)).collect()
+            }
+
+        /*  function: transform_grouped
+            inputs: A &str containing the message to be transformed, either by encoding or decoding
+            output: A String containing the transformed letters written in groups of five, as they would be sent by the operator
+            limitations: Characters that are not uppercase letters are dropped from the output, including spaces and newlines
+            algorithm: each letter is transformed as in transform_message. Groups are separated by a space, except that when a line policy
+               is set a newline is written after every groups_per_line groups instead */
+            pub fn transform_grouped(&mut self, message: &str) -> String {
+                let mut grouped: String = String::new();
+                let mut letters: usize = 0;
+
+                for chr in message.chars() {
+                    if is_cipher_letter(chr) {
+                        if letters > 0 && letters.is_multiple_of(5) {
+                            match self.line_policy {
+                                Some(groups_per_line) if groups_per_line > 0 && (letters / 5).is_multiple_of(groups_per_line) => grouped.push('\n'),
+                                _ => grouped.push(' ')
+                            }
+                        }
+                        grouped.push(self.press_key(chr));
+                        letters += 1;
+                    }
+                }
+
+                grouped
+            }
+
+        /*  function: keystream
+            inputs: A char representing a key that is held down and pressed repeatedly
+            output: An endless iterator yielding the lamp lit by each successive press of the key
+            limitations: The iterator never ends, so callers must limit it (e.g.: with take). A held key that is not an uppercase
+               letter does not step the rotors, so the iterator simply repeats it
+            algorithm: presses the same key over and over, stepping the rotors each time. This exposes the cycle of permutations
+               the machine passes through as it steps */
+            pub fn keystream(&mut self, held_key: char) -> impl Iterator<Item = char> + '_ {
+                std::iter::repeat(held_key).map(move |key| self.press_key(key))
+            }
+
+        /*  function: transform_logged
+            inputs: A &str containing the message to be transformed, either by encoding or decoding
+                    A Write the log is streamed to
+            output: io::Result containing the transformed message, or the first error the log returned
+            limitations: Only uppercase letters are logged, since nothing else steps the rotors. The message is transformed up to the
+               letter whose line could not be written when an error is returned
+            algorithm: transforms the message as in transform_message, writing one line for each letter giving the letter pressed,
+               the window letters before and after the rotors step, and the lamp lit, e.g.: "Q MCK MCL E" */
+            pub fn transform_logged<W: Write>(&mut self, message: &str, log: &mut W) -> io::Result<String> {
+                let letters = |(left, middle, right): Position| [left, middle, right].iter().collect::<String>();
+                let mut transformed = String::with_capacity(message.len());
+                for key in message.chars() {
+                    if is_cipher_letter(key) {
+                        let before = self.window_position();
+                        let lamp = self.press_key(key);
+                        writeln!(log, "{} {} {} {}", key, letters(before), letters(self.window_position()), lamp)?;
+                        transformed.push(lamp);
+                    } else {
+                        transformed.push(self.press_key(key));
+                    }
+                }
+
+                Ok(transformed)
+            }
+    }
+
+    /* A SyncEnigmaMachine is an EnigmaMachine that can be shared between threads, e.g.: in an Arc. It contains the following:
+         A Mutex<EnigmaMachine> holding the machine, locked by each call
+       A SyncEnigmaMachine has the following functions available to it:
+         new is a constructor that takes ownership of an EnigmaMachine
+         transform is a function like EnigmaMachine::transform that takes &self
+         reset is a function like EnigmaMachine::reset that takes &self
+         lock is a function that gives the machine to one thread for a series of calls
+       Concurrent calls do not run side by side, they wait for each other and run one at a time. Each call keeps the rotors where it
+       leaves them, so two threads each calling reset and then transform may have their calls interleaved, leaving one message
+       transformed from the other's rotor positions. Use lock to reset and transform without another thread stepping in between */
+    pub struct SyncEnigmaMachine {
+        machine: Mutex<EnigmaMachine>
+    }
+
+    impl SyncEnigmaMachine {
+        pub fn new(machine: EnigmaMachine) -> SyncEnigmaMachine {
+            SyncEnigmaMachine { machine: Mutex::new(machine) }
+        }
+
+        /*  function: transform
+            inputs: A &str containing the message to be transformed, either by encoding or decoding
+            output: A String containing the message after encoding or decoding
+            limitations: Waits for any other thread using the machine to finish first
+            algorithm: locks the machine, then transforms as in EnigmaMachine::transform */
+            pub fn transform(&self, message: &str) -> String {
+                self.lock().transform(message)
+            }
+
+        /*  function: reset
+            inputs: none
+            output: none
+            limitations: Waits for any other thread using the machine to finish first */
+            pub fn reset(&self) {
+                self.lock().reset();
+            }
+
+        /*  function: lock
+            inputs: none
+            output: MutexGuard giving this thread the machine until the guard is dropped
+            limitations: Every other thread waits on the machine while the guard is held
+            algorithm: a thread that panicked while holding the machine cannot have left it half changed, since every key press
+               moves the rotors to a valid position, so the machine is still handed out after such a panic */
+            pub fn lock(&self) -> MutexGuard<'_, EnigmaMachine> {
+                self.machine.lock().unwrap_or_else(PoisonError::into_inner)
+            }
+    }
+
+    /* With the serde feature enabled, an EnigmaMachine can describe each key press of a message as JSON */
+    #[cfg(feature = "serde")]
+    impl EnigmaMachine {
+        /*  function: transform_message_json
+            inputs: A &str containing the message to be transformed, either by encoding or decoding
+            output: A String holding a JSON array with an object for each letter of the message, e.g.:
+               [{"input":"H","output":"Q","positions":"MDL"}], where positions are the window letters after the press, leftmost first
+            limitations: Characters that are not letters are skipped, as they light no lamp, but are still pressed so a shift toggle
+               takes effect. The JSON is written without whitespace
+            algorithm: each character is pressed as in press_key_detailed, and each lit lamp is written out with its key and the
+               rotor positions. The values are all uppercase letters, so none need escaping */
+            pub fn transform_message_json(&mut self, message: &str) -> String {
+                let presses: Vec<String> = message.chars().filter_map(|key| {
+                    let result = self.press_key_detailed(key);
+                    let (left, middle, right) = result.positions;
+                    result.output.map(|lamp| format!("{{\"input\":\"{}\",\"output\":\"{}\",\"positions\":\"{}{}{}\"}}",
+                        key, lamp, left, middle, right))
+                }).collect();
+
+                format!("[{}]", presses.join(","))
+            }
+    }
+
+    /* With the base64 feature enabled, an EnigmaMachine can write its output as base64, and read base64 back */
+    #[cfg(feature = "base64")]
+    impl EnigmaMachine {
+        /*  function: transform_to_base64
+            inputs: A &str containing the message to be transformed
+            output: A String containing the UTF-8 bytes of the transformed message encoded as base64
+            limitations: None obvious as this time
+            algorithm: the message is transformed as in transform, then encoded as base64::encode describes */
+            pub fn transform_to_base64(&mut self, message: &str) -> String {
+                base64::encode(self.transform(message).as_bytes())
+            }
+
+        /*  function: transform_from_base64
+            inputs: A &str containing base64 written by transform_to_base64
+            output: Result containing the transformed message, or a Base64Error if the text is not base64 of UTF-8 text
+            limitations: The rotors are only stepped once the text has decoded, so a rejected text leaves them where they were
+            algorithm: the text is decoded as base64::decode describes, then transformed as in transform. As the machine is
+               reciprocal, this undoes transform_to_base64 from the same starting positions */
+            pub fn transform_from_base64(&mut self, encoded: &str) -> Result<String, Base64Error> {
+                let bytes = base64::decode(encoded)?;
+                let message = String::from_utf8(bytes).map_err(|_| Base64Error::InvalidUtf8)?;
+
+                Ok(self.transform(&message))
+            }
+    }
+
+    /* With the async feature enabled, an EnigmaMachine can transform a stream as it arrives, without blocking on it */
+    #[cfg(feature = "async")]
+    impl EnigmaMachine {
+        /*  function: transform_async
+            inputs: A PollRead the message is read from, as ASCII bytes
+                    A PollWrite the transformed message is written to
+            output: io::Result holding the number of bytes transformed, or the first error met reading or writing the streams
+            limitations: Bytes are transformed as in transform_bulk, so only uppercase letters step the rotors and every other byte,
+               including each byte of a character that is not ASCII, is written unchanged. A machine with a shift layer steps it a key
+               at a time, as transform_bulk does. The streams are bound on this crate's PollRead and PollWrite, not on tokio's traits,
+               so a tokio stream must be wrapped in a type that implements them
+            algorithm: the reader is read a chunk at a time, and each chunk is transformed and written in full before the next read is
+               awaited. The rotors are left where the last chunk moved them, so the stream gives the same result however its bytes
+               are split into chunks, and the same as transform_bulk over the whole message. The writer is flushed at the end */
+            pub async fn transform_async<R: PollRead + Unpin, W: PollWrite + Unpin>(&mut self, mut reader: R, mut writer: W) -> io::Result<u64> {
+                let mut input = [0u8; 8192];
+                let mut output = [0u8; 8192];
+                let mut total: u64 = 0;
+                loop {
+                    let count = std::future::poll_fn(|cx| Pin::new(&mut reader).poll_read(cx, &mut input)).await?;
+                    if count == 0 {
+                        break;
+                    }
+                    self.transform_bulk(&input[..count], &mut output[..count]);
+                    let mut written = 0;
+                    while written < count {
+                        let wrote = std::future::poll_fn(|cx| Pin::new(&mut writer).poll_write(cx, &output[written..count])).await?;
+                        if wrote == 0 {
+                            return Err(io::Error::from(io::ErrorKind::WriteZero));
+                        }
+                        written += wrote;
+                    }
+                    total += count as u64;
+                }
+                std::future::poll_fn(|cx| Pin::new(&mut writer).poll_flush(cx)).await?;
+
+                Ok(total)
+            }
+    }
+
+    #[test]
+    // This deciphers a known message with known machine settings to ensure the EnigmaMachine is working properlsy
     fn test_full_machine() {
         let mut my_enigma = EnigmaMachine::new("ABCDEFGHIJKLMNOPQRSTUVWXYZ".to_owned(),
         "BDFHJLCPRTXVZNYEIWGAKMUSQO".to_owned(), 10, 0,
@@ -448,7 +3224,1219 @@ pub mod enigma_machine {
         "YRUHQSLDPXNGOKMIEBFZCWVJAT".to_owned()
     );
     my_enigma.set_triggers(vec![22], vec![5], vec![17]);
-    let transformed:String = my_enigma.transform_message("QMJIDO MZWZJFJR".to_owned());
-    assert_eq!("ENIGMA REVEALED", transformed);
+    let transformed:String = my_enigma.transform_message("QMJIDO MZWZJFJR".to_owned());
+    assert_eq!("ENIGMA REVEALED", transformed);
+    }
+
+    #[test]
+    // Tests to see if the keystream for a held key matches pressing that key by hand
+    fn test_keystream() {
+        let mut streamed = EnigmaMachine::new("ABCDEFGHIJKLMNOPQRSTUVWXYZ".to_owned(),
+        "BDFHJLCPRTXVZNYEIWGAKMUSQO".to_owned(), 10, 0,
+        "AJDKSIRUXBLHWTMCQGZNPYFVOE".to_owned(), 2, 0,
+        "EKMFLGDQVZNTOWYHXUSPAIBRCJ".to_owned(), 12, 0,
+        "YRUHQSLDPXNGOKMIEBFZCWVJAT".to_owned()
+    );
+    streamed.set_triggers(vec![22], vec![5], vec![17]);
+    let mut pressed = EnigmaMachine::new("ABCDEFGHIJKLMNOPQRSTUVWXYZ".to_owned(),
+        "BDFHJLCPRTXVZNYEIWGAKMUSQO".to_owned(), 10, 0,
+        "AJDKSIRUXBLHWTMCQGZNPYFVOE".to_owned(), 2, 0,
+        "EKMFLGDQVZNTOWYHXUSPAIBRCJ".to_owned(), 12, 0,
+        "YRUHQSLDPXNGOKMIEBFZCWVJAT".to_owned()
+    );
+    pressed.set_triggers(vec![22], vec![5], vec![17]);
+    let stream: String = streamed.keystream('A').take(26).collect();
+    let mut by_hand: String = String::new();
+    for _ in 0..26 {
+        by_hand.push(pressed.press_key('A'));
+    }
+    assert_eq!(by_hand, stream);
+    assert!(!stream.contains('A'));
+    }
+
+    #[test]
+    // Tests to see if grouped output is wrapped onto a new line after every ten groups
+    fn test_line_policy() {
+        let mut my_enigma = EnigmaMachine::new("ABCDEFGHIJKLMNOPQRSTUVWXYZ".to_owned(),
+        "BDFHJLCPRTXVZNYEIWGAKMUSQO".to_owned(), 10, 0,
+        "AJDKSIRUXBLHWTMCQGZNPYFVOE".to_owned(), 2, 0,
+        "EKMFLGDQVZNTOWYHXUSPAIBRCJ".to_owned(), 12, 0,
+        "YRUHQSLDPXNGOKMIEBFZCWVJAT".to_owned()
+    );
+    my_enigma.set_triggers(vec![22], vec![5], vec![17]);
+    my_enigma.set_line_policy(Some(10));
+    let grouped = my_enigma.transform_grouped(&"ENIGMA REVEALED ".repeat(10));
+    let lines: Vec<&str> = grouped.split('\n').collect();
+    assert_eq!(3, lines.len());
+    assert_eq!(10, lines[0].split(' ').count());
+    assert_eq!(10, lines[1].split(' ').count());
+    assert_eq!("QMJID", &lines[0][..5]);
+    assert_eq!(59, lines[0].len());
+    assert_eq!(8, lines[2].split(' ').count());
+    }
+
+    #[cfg(test)]
+    // Builds the machine used by the sample in main.rs, with all ring settings at zero
+    fn test_machine() -> EnigmaMachine {
+        let mut my_enigma = EnigmaMachine::new("ABCDEFGHIJKLMNOPQRSTUVWXYZ".to_owned(),
+            "BDFHJLCPRTXVZNYEIWGAKMUSQO".to_owned(), 10, 0,
+            "AJDKSIRUXBLHWTMCQGZNPYFVOE".to_owned(), 2, 0,
+            "EKMFLGDQVZNTOWYHXUSPAIBRCJ".to_owned(), 12, 0,
+            "YRUHQSLDPXNGOKMIEBFZCWVJAT".to_owned()
+        );
+        my_enigma.set_triggers(vec![22], vec![5], vec![17]);
+        my_enigma
+    }
+
+    #[cfg(test)]
+    // Builds test_machine with rotor I wired in as the reflector, which does not swap letters in pairs, so the machine is broken
+    fn broken_reflector_machine() -> EnigmaMachine {
+        let mut broken = test_machine();
+        broken.reflector = Reflector::from_wiring("EKMFLGDQVZNTOWYHXUSPAIBRCJ");
+        broken
+    }
+
+    #[test]
+    // Tests to see if grouped ciphertext deciphers to contiguous plaintext
+    fn test_transform_ungrouped() {
+        let mut my_enigma = test_machine();
+        let transformed = my_enigma.transform_ungrouped("QMJID OMZWZ\nJFJR");
+        assert_eq!("ENIGMAREVEALED", transformed);
+    }
+
+    #[cfg(feature = "zeroize")]
+    #[test]
+    // Tests to see if wiping a machine clears the ground setting and strict crib it holds itself
+    fn test_wipe_machine() {
+        let mut my_enigma = test_machine();
+        my_enigma.set_strict_crib(Some("WETTER"));
+        my_enigma.wipe();
+        assert_eq!((0, 0, 0), my_enigma.ground);
+        assert_eq!(Some("\0\0\0\0\0\0"), my_enigma.strict_crib.as_deref());
+    }
+
+    #[test]
+    // Tests to see if a mirror-image machine produces the same ciphertext as the standard machine it mirrors
+    fn test_orientation() {
+        let mut standard = test_machine();
+        let mut mirrored = EnigmaMachine::new("ABCDEFGHIJKLMNOPQRSTUVWXYZ".to_owned(),
+            "EKMFLGDQVZNTOWYHXUSPAIBRCJ".to_owned(), 12, 0,
+            "AJDKSIRUXBLHWTMCQGZNPYFVOE".to_owned(), 2, 0,
+            "BDFHJLCPRTXVZNYEIWGAKMUSQO".to_owned(), 10, 0,
+            "YRUHQSLDPXNGOKMIEBFZCWVJAT".to_owned()
+        );
+        mirrored.set_triggers(vec![17], vec![5], vec![22]);
+        mirrored.set_orientation(Orientation::LeftFast);
+        let message = "THE TIME HAS COME THE WALRUS SAID TO TALK OF MANY THINGS".to_owned();
+        let transformed = standard.transform_message(message.clone());
+        assert_eq!(transformed, mirrored.transform_message(message.clone()));
+        assert_eq!("ENIGMA REVEALED", test_machine().transform_message("QMJIDO MZWZJFJR".to_owned()));
+        let mut flipped = test_machine();
+        flipped.set_orientation(Orientation::LeftFast);
+        assert_ne!(transformed, flipped.transform_message(message));
+
+        // with LeftFast the left rotor steps on each press, and the right rotor is the slow one the double-step carries into
+        let mut standard = test_machine();
+        let mut flipped = test_machine();
+        flipped.set_orientation(Orientation::LeftFast);
+        let (right, middle, left) = flipped.rotor_positions();
+        assert_eq!((right, middle, left), standard.rotor_positions());
+        standard.step(1);
+        flipped.step(1);
+        assert_eq!((right + 1, middle, left), standard.rotor_positions());
+        assert_eq!((right, middle, left + 1), flipped.rotor_positions());
+        flipped.step(29);
+        assert_eq!((10, 3, 16), flipped.rotor_positions());
+        flipped.step(1);
+        assert_eq!((10, 4, 17), flipped.rotor_positions());
+        flipped.step(1);
+        assert_eq!((11, 5, 18), flipped.rotor_positions());
+        flipped.step(1);
+        assert_eq!((11, 5, 19), flipped.rotor_positions());
+        assert_ne!(test_machine().transform_message("AAAAA".to_owned()), {
+            let mut flipped = test_machine();
+            flipped.set_orientation(Orientation::LeftFast);
+            flipped.transform_message("AAAAA".to_owned())
+        });
+    }
+
+    #[test]
+    // Tests to see if a message without letters is returned without allocating, and one with letters is transformed
+    fn test_transform_cow() {
+        let mut my_enigma = test_machine();
+        assert!(matches!(my_enigma.transform_cow("0123 456789"), Cow::Borrowed("0123 456789")));
+        let transformed = my_enigma.transform_cow("QMJIDO MZWZJFJR");
+        assert!(matches!(transformed, Cow::Owned(_)));
+        assert_eq!("ENIGMA REVEALED", transformed);
+
+        // with a digit layer the digits are keys, so they are enciphered and step the digit wheels
+        let digits = || NumericMachine::new(vec![NumericWheel::new("3917508264", 4), NumericWheel::new("8302716945", 7)], "5678901234");
+        let mut my_enigma = test_machine();
+        my_enigma.set_digit_layer(Some(digits()));
+        let transformed = my_enigma.transform_cow("0123");
+        assert!(matches!(transformed, Cow::Owned(_)));
+        let mut expected = test_machine();
+        expected.set_digit_layer(Some(digits()));
+        assert_eq!(expected.transform("0123"), transformed);
+        assert_ne!("0123", transformed);
+
+        // a lone shift toggle is a key too, and holds the shift
+        let mut my_enigma = test_machine();
+        my_enigma.set_shift_layer(test_machine(), '^').unwrap();
+        assert!(matches!(my_enigma.transform_cow("^"), Cow::Owned(_)));
+        assert!(my_enigma.state().shifted);
+
+        // the Drop policy leaves out what is not a key, as transform does
+        let mut my_enigma = test_machine();
+        my_enigma.set_nonletter_policy(NonLetterPolicy::Drop);
+        assert_eq!("QMJIDOMZWZJFJR", my_enigma.transform_cow("ENIGMA REVEALED!"));
+        assert!(matches!(my_enigma.transform_cow("0123 456789"), Cow::Borrowed("")));
+    }
+
+    #[test]
+    // Tests to see if stepping by hand matches the stepping done while enciphering, including the double-step
+    fn test_step() {
+        let mut stepped = test_machine();
+        let mut typed = test_machine();
+        stepped.step(26);
+        typed.transform_message("A".repeat(26));
+        assert_eq!(typed.rotor_positions(), stepped.rotor_positions());
+        assert_eq!((10, 3, 12), stepped.rotor_positions());
+        // rotors I, II and III at A, D and U step to A, D, V then A, E, W then B, F, X
+        stepped.set_rotor_positions(20, 3, 0);
+        stepped.step(1);
+        assert_eq!((21, 3, 0), stepped.rotor_positions());
+        stepped.step(1);
+        assert_eq!((22, 4, 0), stepped.rotor_positions());
+        stepped.step(1);
+        assert_eq!((23, 5, 1), stepped.rotor_positions());
+    }
+
+    #[test]
+    // Tests to see if the stepped flags fire at the presses where the rotors turn over, and non-letters light no lamp
+    fn test_press_key_detailed() {
+        let mut my_enigma = test_machine();
+        my_enigma.set_rotor_positions(20, 3, 0);
+        let steps: Vec<(Position, bool, bool, bool)> = "AAAA".chars().map(|key| {
+            let result = my_enigma.press_key_detailed(key);
+            assert!(result.output.is_some());
+            (result.positions, result.left_stepped, result.middle_stepped, result.right_stepped)
+        }).collect();
+        assert_eq!(vec![
+            (('A', 'D', 'V'), false, false, true),
+            (('A', 'E', 'W'), false, true, true),
+            (('B', 'F', 'X'), true, true, true),
+            (('B', 'F', 'Y'), false, false, true)
+        ], steps);
+        let result = my_enigma.press_key_detailed(' ');
+        assert_eq!(None, result.output);
+        assert!(!result.right_stepped);
+    }
+
+    #[test]
+    // Tests to see if the middle and left rotors each advance exactly once when the right and middle notches coincide
+    fn test_coinciding_notches() {
+        let mut my_enigma = test_machine();
+        // rotor III showing V and rotor II showing E are both at their turnovers
+        my_enigma.set_rotor_positions(21, 4, 0);
+        my_enigma.step(1);
+        assert_eq!(('B', 'F', 'W'), my_enigma.window_position());
+        my_enigma.step(1);
+        assert_eq!(('B', 'F', 'X'), my_enigma.window_position());
+        let mut bulk = test_machine();
+        bulk.set_rotor_positions(21, 4, 0);
+        let mut output = [0u8; 2];
+        bulk.transform_bulk(b"AA", &mut output);
+        assert_eq!(my_enigma.rotor_positions(), bulk.rotor_positions());
+    }
+
+    #[test]
+    // Tests to see if switching to the shift layer mid-message enciphers with the second machine and still round-trips
+    fn test_shift_layer() {
+        let layer = || EnigmaMachine::new("ABCDEFGHIJKLMNOPQRSTUVWXYZ".to_owned(),
+            "EKMFLGDQVZNTOWYHXUSPAIBRCJ".to_owned(), 4, 0,
+            "BDFHJLCPRTXVZNYEIWGAKMUSQO".to_owned(), 7, 0,
+            "AJDKSIRUXBLHWTMCQGZNPYFVOE".to_owned(), 19, 0,
+            "FVPJIAOYEDRZXWGCTKUQSBNMHL".to_owned()
+        );
+        let mut sender = test_machine();
+        assert_eq!(Err(EnigmaError::InvalidShiftToggle('S')), sender.set_shift_layer(layer(), 'S'));
+        sender.set_shift_layer(layer(), '^').unwrap();
+        let message = "ENIGMA ^REVEALED^ ENIGMA";
+        let transformed = sender.transform_message(message.to_owned());
+        assert_eq!("QMJIDO ", &transformed[..7]);
+        assert_eq!(layer().transform_message("REVEALED".to_owned()), transformed[8..16]);
+        let mut receiver = test_machine();
+        receiver.set_shift_layer(layer(), '^').unwrap();
+        assert_eq!(message, receiver.transform_message(transformed));
+    }
+
+    #[test]
+    // Tests to see if a digit layer enciphers the digits of a mixed message apart from the letters, and the message round-trips
+    fn test_digit_layer() {
+        let digits = || NumericMachine::new(vec![NumericWheel::new("3917508264", 4), NumericWheel::new("8302716945", 7)], "5678901234");
+        let mut sender = test_machine();
+        sender.set_digit_layer(Some(digits()));
+        let transformed = sender.transform("ROOM 101");
+        let letters: String = test_machine().transform("ROOM");
+        assert_eq!(letters, transformed[..4]);
+        assert_eq!(digits().transform("101"), transformed[5..]);
+        assert!(transformed[5..].chars().all(|chr| chr.is_ascii_digit()));
+        assert_ne!("101", &transformed[5..]);
+
+        let mut receiver = test_machine();
+        receiver.set_digit_layer(Some(digits()));
+        assert_eq!("ROOM 101", receiver.transform(&transformed));
+        receiver.reset();
+        let mut bulk = [0u8; 8];
+        receiver.transform_bulk(transformed.as_bytes(), &mut bulk);
+        assert_eq!(b"ROOM 101", &bulk);
+
+        receiver.set_digit_layer(None);
+        receiver.reset();
+        let without_digits = receiver.transform(&transformed);
+        assert_eq!("ROOM", &without_digits[..4]);
+        assert_eq!(transformed[5..], without_digits[5..]);
+    }
+
+    #[test]
+    // Tests to see if a QWERTZ keyboard changes the ciphertext but still round-trips, and alphabetical order changes nothing
+    fn test_keyboard_layout() {
+        let qwertz = || {
+            let mut machine = test_machine();
+            machine.set_keyboard_layout(Some("QWERTZUIOASDFGHJKPYXCVBNML")).unwrap();
+            machine
+        };
+        assert_eq!(Err(EnigmaError::InvalidWheel { part: "keyboard", error: WheelError::RepeatedLetter('Q') }),
+            test_machine().set_keyboard_layout(Some("QQERTZUIOASDFGHJKPYXCVBNML")));
+        let message = "THE TIME HAS COME THE WALRUS SAID";
+        let transformed = qwertz().transform(message);
+        assert_ne!(test_machine().transform(message), transformed);
+        assert_eq!(message, qwertz().transform(&transformed));
+        let bytes = qwertz().to_bytes();
+        assert_eq!(1, bytes[168]);
+        assert_eq!(message, EnigmaMachine::from_bytes(&bytes).unwrap().transform(&transformed));
+        let mut corrupted = bytes.clone();
+        corrupted[170] = corrupted[169];
+        assert_eq!(Err(DecodeError::InvalidPart("keyboard")), EnigmaMachine::from_bytes(&corrupted).map(|_| ()));
+        // Q is wired to contact A, so pressing Q sends the signal the plain machine sends from A, and its lamp is the key wired
+        // to the contact the plain machine lights
+        let contact = letter_index(test_machine().transform("A").chars().next().unwrap());
+        assert_eq!("QWERTZUIOASDFGHJKPYXCVBNML"[contact..contact + 1], qwertz().transform("Q"));
+
+        let mut output = vec![0u8; message.len()];
+        qwertz().transform_bulk(message.as_bytes(), &mut output);
+        assert_eq!(transformed.as_bytes(), &output[..]);
+
+        let mut alphabetical = test_machine();
+        alphabetical.set_keyboard_layout(Some("ABCDEFGHIJKLMNOPQRSTUVWXYZ")).unwrap();
+        assert_eq!(test_machine().transform(message), alphabetical.transform(message));
+    }
+
+    #[test]
+    // Tests to see if a working machine is reciprocal, and one whose reflector, plugboard or keyboard does not swap in pairs is not
+    fn test_is_reciprocal() {
+        assert!(test_machine().is_reciprocal());
+        let unpaired = broken_reflector_machine();
+        assert!(!unpaired.is_reciprocal());
+        let mut shifted = test_machine();
+        shifted.set_shift_layer(unpaired, '^').unwrap();
+        assert!(!shifted.is_reciprocal());
+
+        let mut asymmetric = EnigmaMachine::new("BCDEFGHIJKLMNOPQRSTUVWXYZA".to_owned(),
+            "BDFHJLCPRTXVZNYEIWGAKMUSQO".to_owned(), 10, 0,
+            "AJDKSIRUXBLHWTMCQGZNPYFVOE".to_owned(), 2, 0,
+            "EKMFLGDQVZNTOWYHXUSPAIBRCJ".to_owned(), 12, 0,
+            "YRUHQSLDPXNGOKMIEBFZCWVJAT".to_owned()
+        );
+        assert!(!asymmetric.is_reciprocal());
+        asymmetric.set_shift_layer(test_machine(), '^').unwrap();
+        assert!(!asymmetric.is_reciprocal());
+
+        let mut plugged = MachineBuilder::new().plugs(&[('A', 'R'), ('G', 'K'), ('O', 'X')]).build().unwrap();
+        assert!(plugged.is_reciprocal());
+        plugged.set_keyboard_layout(Some("QWERTZUIOASDFGHJKPYXCVBNML")).unwrap();
+        assert!(plugged.is_reciprocal());
+    }
+
+    #[test]
+    // Tests to see if peeking at the next positions matches actually stepping, and leaves the machine where it was
+    fn test_peek_positions() {
+        let mut my_enigma = test_machine();
+        assert_eq!(('M', 'C', 'K'), my_enigma.window_position());
+        let peeked = my_enigma.peek_positions(3);
+        assert_eq!(('M', 'C', 'K'), my_enigma.window_position());
+        let mut stepped: Vec<Position> = vec![];
+        for _ in 0..3 {
+            my_enigma.step(1);
+            stepped.push(my_enigma.window_position());
+        }
+        assert_eq!(stepped, peeked);
+        assert_eq!(vec![('M', 'C', 'L'), ('M', 'C', 'M'), ('M', 'C', 'N')], peeked);
+    }
+
+    #[test]
+    // Tests to see if a machine saved to bytes and restored gives the same bytes and enciphers the same way
+    fn test_to_bytes() {
+        let mut my_enigma = test_machine();
+        my_enigma.step(40);
+        let bytes = my_enigma.to_bytes();
+        assert_eq!(195, bytes.len());
+        let mut restored = EnigmaMachine::from_bytes(&bytes).unwrap();
+        assert_eq!(bytes, restored.to_bytes());
+        let message = "THE TIME HAS COME THE WALRUS SAID".to_owned();
+        assert_eq!(my_enigma.transform_message(message.clone()), restored.transform_message(message));
+        assert_eq!(Err(DecodeError::WrongLength { expected: 195, found: 3 }), EnigmaMachine::from_bytes(b"ENG").map(|_| ()));
+        assert_eq!(Err(DecodeError::WrongLength { expected: 165, found: 195 }), EnigmaMachine::from_bytes(&[b"ENG\x01", &bytes[4..]].concat()).map(|_| ()));
+        assert_eq!(Err(DecodeError::UnsupportedVersion(3)), EnigmaMachine::from_bytes(&[b"ENG\x03", &bytes[4..]].concat()).map(|_| ()));
+        let mut corrupted = bytes.clone();
+        corrupted[80] = b'a';
+        assert_eq!(Err(DecodeError::InvalidPart("middle rotor")), EnigmaMachine::from_bytes(&corrupted).map(|_| ()));
+    }
+
+    #[test]
+    // Tests to see if core mounted notches survive saving, so the restored machine turns over at the same letters, and if bytes
+    // saved before the notch mount was stored are still read with ring mounted notches
+    fn test_to_bytes_notch_mount() {
+        let message = "THE TIME HAS COME THE WALRUS SAID TO TALK OF MANY THINGS".to_owned();
+        let mounted = |notch_mount: NotchMount| {
+            let mut machine = test_machine();
+            machine.set_ring_settings(2, 9, 7);
+            machine.set_notch_mount(notch_mount);
+            machine
+        };
+        let bytes = mounted(NotchMount::Core).to_bytes();
+        assert_eq!([1, 1, 1], bytes[165..168]);
+        // the rightmost rotor turns the middle one over within the message, where the two mounts step differently
+        let core_ciphertext = mounted(NotchMount::Core).transform_message(message.clone());
+        assert_ne!(mounted(NotchMount::Ring).transform_message(message.clone()), core_ciphertext);
+        assert_eq!(core_ciphertext, EnigmaMachine::from_bytes(&bytes).unwrap().transform_message(message.clone()));
+
+        let mut version_1 = mounted(NotchMount::Ring).to_bytes();
+        version_1.truncate(165);
+        version_1[3] = 1;
+        assert_eq!(mounted(NotchMount::Ring).transform_message(message.clone()),
+            EnigmaMachine::from_bytes(&version_1).unwrap().transform_message(message));
+
+        let mut corrupted = bytes.clone();
+        corrupted[166] = 2;
+        assert_eq!(Err(DecodeError::InvalidPart("notch mount")), EnigmaMachine::from_bytes(&corrupted).map(|_| ()));
+    }
+
+    #[test]
+    // Tests to see if triggers that can never be reached, including ones too large for the mask, are left out when saving
+    fn test_to_bytes_out_of_range_trigger() {
+        let mut wheel = EnigmaWheel::new("EKMFLGDQVZNTOWYHXUSPAIBRCJ".to_owned(), 0, 0);
+        wheel.set_triggers(vec![40, 4, 26, u16::MAX]);
+        let bytes = wheel.to_bytes();
+        assert_eq!(1u32 << 4, u32::from_le_bytes([bytes[28], bytes[29], bytes[30], bytes[31]]));
+        let restored = EnigmaWheel::from_bytes(&bytes).unwrap();
+        assert_eq!(vec!['D'], restored.turnover_letters());
+
+        let mut my_enigma = test_machine();
+        my_enigma.set_triggers(vec![22, 33], vec![5], vec![17, 300]);
+        let mut restored = EnigmaMachine::from_bytes(&my_enigma.to_bytes()).unwrap();
+        let message = "THE TIME HAS COME THE WALRUS SAID TO TALK OF MANY THINGS".to_owned();
+        assert_eq!(my_enigma.transform_message(message.clone()), restored.transform_message(message));
+    }
+
+    #[test]
+    // Tests to see if changing the ring settings of a built machine matches building it with those ring settings
+    fn test_set_ring_settings() {
+        let mut changed = test_machine();
+        changed.set_ring_settings(2, 9, 7);
+        let mut fresh = EnigmaMachine::new("ABCDEFGHIJKLMNOPQRSTUVWXYZ".to_owned(),
+            "BDFHJLCPRTXVZNYEIWGAKMUSQO".to_owned(), 10, 2,
+            "AJDKSIRUXBLHWTMCQGZNPYFVOE".to_owned(), 2, 9,
+            "EKMFLGDQVZNTOWYHXUSPAIBRCJ".to_owned(), 12, 7,
+            "YRUHQSLDPXNGOKMIEBFZCWVJAT".to_owned()
+        );
+        fresh.set_triggers(vec![22], vec![5], vec![17]);
+        let message = "THE TIME HAS COME THE WALRUS SAID".to_owned();
+        assert_eq!(fresh.transform_message(message.clone()), changed.transform_message(message));
+    }
+
+    #[test]
+    // Tests to see if a working machine has no fixed points, while one with a reflector that wires letters to themselves does
+    fn test_fixed_points() {
+        let mut my_enigma = test_machine();
+        for _ in 0..100 {
+            assert!(my_enigma.fixed_points().is_empty());
+            my_enigma.step(1);
+        }
+        let broken = EnigmaMachine::new("ABCDEFGHIJKLMNOPQRSTUVWXYZ".to_owned(),
+            "BDFHJLCPRTXVZNYEIWGAKMUSQO".to_owned(), 10, 0,
+            "AJDKSIRUXBLHWTMCQGZNPYFVOE".to_owned(), 2, 0,
+            "EKMFLGDQVZNTOWYHXUSPAIBRCJ".to_owned(), 12, 0,
+            "ABCDEFGHIJKLMNOPQRSTUVWXYZ".to_owned()
+        );
+        assert_eq!(26, broken.fixed_points().len());
+    }
+
+    #[test]
+    // Tests to see if the bulk transform matches transform_message on random ASCII and leaves the rotors in the same place
+    fn test_transform_bulk() {
+        let mut rng = XorShift::new(0x2545_f491);
+        let input: Vec<u8> = (0..5000).map(|_| rng.below(96) as u8 + 32).collect();
+        for orientation in [Orientation::RightFast, Orientation::LeftFast] {
+            let mut slow_path = test_machine();
+            slow_path.set_ring_settings(3, 17, 25);
+            slow_path.set_orientation(orientation);
+            let mut fast_path = slow_path.clone();
+            let expected = slow_path.transform_message(String::from_utf8(input.clone()).unwrap());
+            let mut output = vec![0u8; input.len()];
+            fast_path.transform_bulk(&input, &mut output);
+            assert_eq!(expected.as_bytes(), &output[..]);
+            assert_eq!(slow_path.rotor_positions(), fast_path.rotor_positions());
+        }
+    }
+
+    #[test]
+    // Tests to see if spaces written as X are enciphered as X and turned back into spaces on decryption
+    fn test_space_substitution() {
+        let mut sender = test_machine();
+        let mut receiver = test_machine();
+        sender.set_space_substitution(Some('X'));
+        receiver.set_space_substitution(Some('X'));
+        let ciphertext = sender.encrypt("HELLO WORLD");
+        assert_eq!(test_machine().transform_message("HELLOXWORLD".to_owned()), ciphertext);
+        assert!(!ciphertext.contains(' '));
+        assert_eq!("HELLO WORLD", receiver.decrypt(&ciphertext));
+    }
+
+    #[test]
+    // Tests to see if the log holds one line per letter showing the rotors stepping, and the message is transformed as usual
+    fn test_transform_logged() {
+        let mut my_enigma = test_machine();
+        let mut log: Vec<u8> = vec![];
+        let transformed = my_enigma.transform_logged("QMJIDO MZWZJFJR", &mut log).unwrap();
+        assert_eq!("ENIGMA REVEALED", transformed);
+        let log = String::from_utf8(log).unwrap();
+        assert_eq!(14, log.lines().count());
+        assert_eq!(Some("Q MCK MCL E"), log.lines().next());
+    }
+
+    #[test]
+    // Tests to see if deciphering from an offset gives the tail of the fully deciphered message, across a double-step
+    fn test_advance_to_offset() {
+        let ciphertext = "QMJIDOMZWZJFJR";
+        let mut my_enigma = test_machine();
+        my_enigma.set_rotor_positions(20, 3, 0);
+        let plaintext = my_enigma.transform_message(ciphertext.to_owned());
+        for n in [0, 1, 2, 9] {
+            my_enigma.advance_to_offset(n);
+            assert_eq!(plaintext[n..], my_enigma.transform_message(ciphertext[n..].to_owned()));
+        }
+    }
+
+    #[test]
+    // Tests to see if restoring a saved state makes the machine repeat the same transform, across a double-step
+    fn test_state() {
+        let mut my_enigma = test_machine();
+        my_enigma.set_rotor_positions(20, 3, 0);
+        my_enigma.transform_message("ABC".to_owned());
+        let saved = my_enigma.state();
+        let first = my_enigma.transform_message("THE TIME HAS COME".to_owned());
+        assert_ne!(saved, my_enigma.state());
+        my_enigma.set_state(saved);
+        assert_eq!(saved, my_enigma.state());
+        assert_eq!(first, my_enigma.transform_message("THE TIME HAS COME".to_owned()));
+    }
+
+    #[test]
+    // Tests to see if carrying the returned state into a second machine continues the message as one transform would
+    fn test_transform_returning_state() {
+        let mut first = test_machine();
+        first.set_rotor_positions(20, 3, 0);
+        let (start, state) = first.transform_returning_state("THE TIME HAS COME");
+        assert_eq!(first.state(), state);
+
+        let mut second = test_machine();
+        second.set_state(state);
+        let end = second.transform("THE WALRUS SAID");
+
+        let mut whole = test_machine();
+        whole.set_rotor_positions(20, 3, 0);
+        assert_eq!(whole.transform("THE TIME HAS COMETHE WALRUS SAID"), start + &end);
+    }
+
+    #[test]
+    // Tests to see if strict mode refuses a ciphertext that would decrypt a letter of the crib to itself, and accepts the real one
+    fn test_strict_crib() {
+        let mut my_enigma = test_machine();
+        let ciphertext = my_enigma.transform("WETTERBERICHT");
+        my_enigma.reset();
+        my_enigma.set_strict_crib(Some("WETTER"));
+        assert_eq!(Ok("WETTERBERICHT".to_owned()), my_enigma.try_transform_message(&ciphertext));
+
+        // the third letter of the crib is T, so a ciphertext with T there cannot be right
+        my_enigma.reset();
+        let corrupted: String = ciphertext.chars().enumerate().map(|(index, chr)| if index == 2 { 'T' } else { chr }).collect();
+        assert_eq!(Err(EnigmaError::SelfEncipherment { position: 2, letter: 'T' }), my_enigma.try_transform_message(&corrupted));
+        assert_eq!(test_machine().rotor_positions(), my_enigma.rotor_positions());
+
+        my_enigma.set_strict_crib(None);
+        assert!(my_enigma.try_transform_message(&corrupted).is_ok());
+    }
+
+    #[test]
+    // Tests to see if spaces and newlines come back at the same offsets, even when the non-letter policy would drop them
+    fn test_transform_keep_layout() {
+        let message = "QBLTW LDAHH\nYEOEF PTWYB\n\n  LENDP MKOXL";
+        let mut my_enigma = test_machine();
+        my_enigma.set_nonletter_policy(NonLetterPolicy::Drop);
+        let kept = my_enigma.transform_keep_layout(message);
+        assert_eq!(message.len(), kept.len());
+        for (original, transformed) in message.chars().zip(kept.chars()) {
+            assert_eq!(is_cipher_letter(original), is_cipher_letter(transformed));
+            if !is_cipher_letter(original) {
+                assert_eq!(original, transformed);
+            }
+        }
+
+        my_enigma.reset();
+        let stripped = my_enigma.transform(message);
+        assert_eq!(kept.chars().filter(|chr| is_cipher_letter(*chr)).collect::<String>(), stripped);
+        my_enigma.reset();
+        my_enigma.set_nonletter_policy(NonLetterPolicy::Pass);
+        assert_eq!(message, my_enigma.transform_keep_layout(&kept));
+    }
+
+    #[test]
+    // Tests to see if the DOT graph has a node for every contact, balanced braces, and an edge each way for each reflector pair
+    fn test_to_dot() {
+        let my_enigma = test_machine();
+        let dot = my_enigma.to_dot();
+        assert!(dot.starts_with("digraph enigma {"));
+        assert_eq!(dot.matches('{').count(), dot.matches('}').count());
+        assert_eq!(5 * 26, dot.lines().filter(|line| line.contains("[label=")).count());
+        assert_eq!(5 * 26, dot.matches(" -> ").count());
+        let reflector_edges: Vec<&str> = dot.lines().filter(|line| line.contains("[color=red]")).collect();
+        assert_eq!(26, reflector_edges.len());
+        // reflector B joins A and Y, and the leftmost rotor's column is the last one the signal reaches
+        assert!(reflector_edges.contains(&"    left_A -> left_Y [color=red];"));
+        assert!(reflector_edges.contains(&"    left_Y -> left_A [color=red];"));
+
+        let mut unplugged = MachineBuilder::new().no_plugboard().build().unwrap();
+        unplugged.step(1);
+        assert_eq!(4 * 26, unplugged.to_dot().lines().filter(|line| line.contains("[label=")).count());
+        assert_ne!(dot, unplugged.to_dot());
+    }
+
+    #[test]
+    // Tests to see if triggers refused for one rotor leave every rotor's triggers as they were
+    fn test_try_set_triggers() {
+        let mut my_enigma = test_machine();
+        let before = my_enigma.peek_positions(700);
+        let error = my_enigma.try_set_triggers(vec![22], vec![5, 5], vec![17]).unwrap_err();
+        assert_eq!(EnigmaError::InvalidWheel { part: "middle rotor", error: WheelError::RepeatedTrigger(5) }, error);
+        assert_eq!(before, my_enigma.peek_positions(700));
+        assert_eq!(Ok(()), my_enigma.try_set_triggers(vec![22, 9], vec![5], vec![17]));
+        assert_ne!(before, my_enigma.peek_positions(700));
+    }
+
+    #[test]
+    // Tests to see if a working machine verifies its own ciphertext, while one with a rotor wired in as the reflector does not
+    fn test_encrypt_and_verify() {
+        let mut my_enigma = test_machine();
+        my_enigma.step(5);
+        let (ciphertext, verified) = my_enigma.encrypt_and_verify("THE TIME HAS COME");
+        assert!(verified);
+        my_enigma.reset();
+        assert_eq!(my_enigma.encrypt("THE TIME HAS COME"), ciphertext);
+
+        assert!(!broken_reflector_machine().encrypt_and_verify("THE TIME HAS COME").1);
+    }
+
+    #[test]
+    // Tests to see if a machine built around the Plugboard and Reflector types still round trips, on every path through it
+    fn test_plugboard_and_reflector_types() {
+        let message = "THE QUICK BROWN FOX JUMPS OVER THE LAZY DOG";
+        let mut my_enigma = MachineBuilder::new().rings([3, 17, 9]).positions([20, 4, 1])
+            .plugs(&[('A', 'V'), ('B', 'S'), ('C', 'G')]).build().unwrap();
+        let ciphertext = my_enigma.transform(message);
+        my_enigma.reset();
+        assert_eq!(message, my_enigma.transform(&ciphertext));
+
+        my_enigma.reset();
+        let mut bulk = vec![0u8; message.len()];
+        my_enigma.transform_bulk(message.as_bytes(), &mut bulk);
+        assert_eq!(ciphertext.as_bytes(), &bulk[..]);
+
+        let mut set = test_machine();
+        set.set_reflector(&Reflector::from_pairs("AC BO DI EG FR HU JY KT LZ MX NW PQ SV").unwrap(), 7);
+        let mut restored = EnigmaMachine::from_bytes(&set.to_bytes()).unwrap();
+        assert_eq!(set.transform(message), restored.transform(message));
+    }
+
+    #[test]
+    // Tests to see if transforming a message twice from the same starting positions always gives the message back
+    fn test_reciprocal() {
+        let symbols: Vec<char> = "ABCDEFGHIJKLMNOPQRSTUVWXYZ ,.?".chars().collect();
+        let mut rng = XorShift::new(0x9e37_79b9);
+        let mut my_enigma = test_machine();
+        my_enigma.set_rotor_positions(20, 3, 0);
+        for length in [0, 1, 25, 26, 27, 700] {
+            let message: String = (0..length).map(|_| symbols[rng.below(symbols.len() as u64) as usize]).collect();
+            my_enigma.reset();
+            let transformed = my_enigma.transform(&message);
+            my_enigma.reset();
+            assert_eq!(message, my_enigma.transform(&transformed));
+        }
+    }
+
+    #[test]
+    // Tests to see if a rotor wiring passed as the reflector is rejected, while the real reflector is accepted
+    fn test_try_new() {
+        let build = |reflector: &str| EnigmaMachine::try_new("ABCDEFGHIJKLMNOPQRSTUVWXYZ".to_owned(),
+            "BDFHJLCPRTXVZNYEIWGAKMUSQO".to_owned(), 10, 0,
+            "AJDKSIRUXBLHWTMCQGZNPYFVOE".to_owned(), 2, 0,
+            "EKMFLGDQVZNTOWYHXUSPAIBRCJ".to_owned(), 12, 0,
+            reflector.to_owned()
+        );
+        assert!(build("YRUHQSLDPXNGOKMIEBFZCWVJAT").is_ok());
+        // new takes the broken machine's reflector unchecked, but try_new refuses it
+        let error = build(&broken_reflector_machine().reflector.wiring()).err().unwrap();
+        assert_eq!(EnigmaError::InvalidReflector(ReflectorError::NotReciprocal('A')), error);
+        assert_eq!("invalid reflector: letter A is not wired back to the letter it is wired to", error.to_string());
+        let error = EnigmaMachine::try_new("ABC".to_owned(),
+            "BDFHJLCPRTXVZNYEIWGAKMUSQO".to_owned(), 10, 0,
+            "AJDKSIRUXBLHWTMCQGZNPYFVOE".to_owned(), 2, 0,
+            "EKMFLGDQVZNTOWYHXUSPAIBRCJ".to_owned(), 12, 0,
+            "YRUHQSLDPXNGOKMIEBFZCWVJAT".to_owned()
+        ).err();
+        assert_eq!(Some(EnigmaError::InvalidWheel { part: "plugboard", error: WheelError::InvalidLength(3) }), error);
+    }
+
+    #[test]
+    // Tests to see if a message far longer than a u16 can count round-trips, and if the rotors keep cycling with their period
+    fn test_long_message() {
+        let message: String = (0..100_000).map(|i| (b'A' + (i * 7 % 26) as u8) as char).collect();
+        let mut my_enigma = test_machine();
+        my_enigma.set_rotor_positions(20, 3, 0);
+        let ciphertext = my_enigma.transform(&message);
+        let end = my_enigma.rotor_positions();
+        my_enigma.reset();
+        assert_eq!(message, my_enigma.transform(&ciphertext));
+        my_enigma.advance_to_offset(100_000 - 26 * 25 * 26);
+        assert_eq!(end, my_enigma.rotor_positions());
+        my_enigma.advance_to_offset(70_000);
+        assert_eq!(message[70_000..], my_enigma.transform(&ciphertext[70_000..]));
+    }
+
+    #[test]
+    // Tests to see if a digit in a message is passed through, dropped or refused according to the non-letter policy
+    fn test_nonletter_policy() {
+        let mut my_enigma = test_machine();
+        assert_eq!(Ok("DLT7BBQVPQVOKDC".to_owned()), my_enigma.try_transform_message("HEL7LOWORLDHELL"));
+
+        my_enigma.reset();
+        my_enigma.set_nonletter_policy(NonLetterPolicy::Drop);
+        assert_eq!(Ok("DLTBBQVPQVOKDC".to_owned()), my_enigma.try_transform_message("HEL7LOWORLDHELL"));
+
+        my_enigma.reset();
+        my_enigma.set_nonletter_policy(NonLetterPolicy::Error);
+        let start = my_enigma.rotor_positions();
+        let error = my_enigma.try_transform_message("HEL7LOWORLDHELL").err().unwrap();
+        assert_eq!(EnigmaError::NonLetter('7'), error);
+        assert_eq!("'7' is not a letter the machine can encipher", error.to_string());
+        assert_eq!(start, my_enigma.rotor_positions());
+        assert_eq!(Ok("DLTBBQVPQVOKDC".to_owned()), my_enigma.try_transform_message("HELLOWORLDHELL"));
+    }
+
+    #[test]
+    // Tests to see if each file of a directory is decrypted from the ground setting, independently of the files before it
+    fn test_decrypt_dir() {
+        let root = std::env::temp_dir().join(format!("enigma_decrypt_dir_{}", std::process::id()));
+        let (dir, out_dir) = (root.join("in"), root.join("out"));
+        fs::create_dir_all(&dir).unwrap();
+        fs::create_dir_all(&out_dir).unwrap();
+        let mut my_enigma = test_machine();
+        fs::write(dir.join("first.txt"), my_enigma.transform("HELLO WORLD")).unwrap();
+        my_enigma.reset();
+        fs::write(dir.join("second.txt"), my_enigma.transform("THE TIME HAS COME")).unwrap();
+        fs::create_dir_all(dir.join("nested")).unwrap();
+
+        assert_eq!(2, my_enigma.decrypt_dir(&dir, &out_dir).unwrap());
+        assert_eq!("HELLO WORLD", fs::read_to_string(out_dir.join("first.txt")).unwrap());
+        assert_eq!("THE TIME HAS COME", fs::read_to_string(out_dir.join("second.txt")).unwrap());
+        assert!(my_enigma.decrypt_dir(&root.join("missing"), &out_dir).is_err());
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    // Tests to see if the indicator is enciphered at the ground setting and the body at the message key, with reset going to the ground
+    fn test_ground_and_message_key() {
+        let mut sender = test_machine();
+        sender.set_ground(('W', 'Z', 'A'));
+        let indicator = sender.transform("SXT");
+        sender.set_message_key(('S', 'X', 'T'));
+        let body = sender.transform("THE TIME HAS COME");
+        sender.reset();
+        assert_eq!(('W', 'Z', 'A'), sender.window_position());
+
+        let mut receiver = test_machine();
+        receiver.set_ground(('W', 'Z', 'A'));
+        let key: Vec<char> = receiver.transform(&indicator).chars().collect();
+        assert_eq!(vec!['S', 'X', 'T'], key);
+        receiver.set_message_key((key[0], key[1], key[2]));
+        assert_eq!(('S', 'X', 'T'), receiver.window_position());
+        assert_eq!("THE TIME HAS COME", receiver.transform(&body));
+        receiver.reset();
+        assert_eq!(('W', 'Z', 'A'), receiver.window_position());
+    }
+
+    #[test]
+    // Tests to see if a passphrase always gives the same message key, worked out by hand from its FNV-1a hash, and others differ
+    fn test_set_message_key_from_passphrase() {
+        let mut my_enigma = test_machine();
+        my_enigma.set_message_key_from_passphrase("ENIGMA");
+        assert_eq!(('V', 'H', 'S'), my_enigma.window_position());
+        my_enigma.set_message_key_from_passphrase("ENIGMA");
+        assert_eq!(('V', 'H', 'S'), my_enigma.window_position());
+        // the empty phrase leaves the offset basis 0xcbf29ce484222325
+        my_enigma.set_message_key_from_passphrase("");
+        assert_eq!(('Z', 'Y', 'B'), my_enigma.window_position());
+        my_enigma.set_message_key_from_passphrase("enigma");
+        assert_eq!(('A', 'O', 'A'), my_enigma.window_position());
+
+        let keys: std::collections::HashSet<Position> = ["ALPHA", "BRAVO", "CHARLIE", "DELTA", "ECHO", "FOXTROT", "GOLF", "HOTEL"]
+            .iter()
+            .map(|phrase| {
+                my_enigma.set_message_key_from_passphrase(phrase);
+                my_enigma.window_position()
+            })
+            .collect();
+        assert!(keys.len() >= 7);
+        my_enigma.reset();
+        assert_eq!(test_machine().window_position(), my_enigma.window_position());
+    }
+
+    #[cfg(feature = "base64")]
+    #[test]
+    // Tests to see if a message written as base64 reads back to the message, and if text that is not base64 is refused
+    fn test_base64_round_trip() {
+        let mut my_enigma = test_machine();
+        let encoded = my_enigma.transform_to_base64("HELLO WORLD");
+        assert!(encoded.chars().all(|chr| chr.is_ascii_alphanumeric() || "+/=".contains(chr)));
+        my_enigma.reset();
+        assert_eq!(Ok("HELLO WORLD".to_owned()), my_enigma.transform_from_base64(&encoded));
+        assert_eq!(Err(Base64Error::InvalidLength(5)), my_enigma.transform_from_base64("QMJID"));
+        assert_eq!(Err(Base64Error::InvalidUtf8), my_enigma.transform_from_base64("/w=="));
+    }
+
+    #[test]
+    // Tests to see if a decryption with the right key is accepted as language and one with the wrong key is refused
+    fn test_decrypt_verified() {
+        let plaintext = "THE ENEMY IS MOVING NORTH ALONG THE RIVER AND WILL REACH THE BRIDGE AT DAWN";
+        let mut sender = test_machine();
+        let ciphertext = sender.encrypt(plaintext);
+        let mut receiver = test_machine();
+        assert_eq!(Ok(plaintext.to_owned()), receiver.decrypt_verified(&ciphertext, -4.15));
+        receiver.set_rotor_positions(0, 0, 0);
+        match receiver.decrypt_verified(&ciphertext, -4.15) {
+            Err(VerifyError::LowScore { score, min_score }) => assert!(score < min_score),
+            result => panic!("wrong key decryption was accepted: {:?}", result)
+        }
+        assert!(receiver.decrypt_verified("AB", -4.15).is_err());
+    }
+
+    #[cfg(feature = "async")]
+    #[test]
+    // Tests to see if a stream that arrives a few bytes at a time, waiting between reads, transforms the same as the whole message
+    fn test_transform_async() {
+        use std::future::Future;
+        use std::task::{Context, Poll, Waker};
+
+        // Reads three bytes at a time, and is only ready on every other poll
+        struct Trickle<'a> { bytes: &'a [u8], ready: bool }
+        impl PollRead for Trickle<'_> {
+            fn poll_read(mut self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &mut [u8]) -> Poll<io::Result<usize>> {
+                self.ready = !self.ready;
+                if !self.ready {
+                    cx.waker().wake_by_ref();
+                    return Poll::Pending;
+                }
+                let count = buf.len().min(self.bytes.len()).min(3);
+                buf[..count].copy_from_slice(&self.bytes[..count]);
+                self.bytes = &self.bytes[count..];
+                Poll::Ready(Ok(count))
+            }
+        }
+
+        let message = "THE TIME HAS COME, THE WALRUS SAID, TO TALK OF MANY THINGS";
+        let mut my_enigma = test_machine();
+        let expected = my_enigma.transform(message);
+        let end = my_enigma.rotor_positions();
+        my_enigma.reset();
+        let mut written: Vec<u8> = vec![];
+        let mut future = Box::pin(my_enigma.transform_async(Trickle { bytes: message.as_bytes(), ready: false }, &mut written));
+        let mut cx = Context::from_waker(Waker::noop());
+        let total = loop {
+            if let Poll::Ready(result) = future.as_mut().poll(&mut cx) {
+                break result.unwrap();
+            }
+        };
+        drop(future);
+        assert_eq!(message.len() as u64, total);
+        assert_eq!(expected.as_bytes(), written);
+        assert_eq!(end, my_enigma.rotor_positions());
+    }
+
+    #[test]
+    // Tests to see if A lights every other letter a similar number of times over a full rotor cycle, and the state is restored
+    fn test_output_histogram() {
+        let mut my_enigma = test_machine();
+        let start = my_enigma.rotor_positions();
+        let histogram = my_enigma.output_histogram(26 * 25 * 26);
+        assert_eq!(start, my_enigma.rotor_positions());
+        assert_eq!(0, histogram[0]);
+        assert_eq!(26 * 25 * 26, histogram.iter().sum::<u32>());
+        assert!(histogram[1..].iter().all(|count| (550..=800).contains(count)), "{:?}", histogram);
+    }
+
+    #[test]
+    // Tests to see if threads sharing a machine each get their messages back, when each resets and transforms under one lock
+    fn test_sync_machine() {
+        let shared = std::sync::Arc::new(SyncEnigmaMachine::new(test_machine()));
+        let expected = test_machine().transform("THE TIME HAS COME");
+        let handles: Vec<_> = (0..8).map(|_| {
+            let shared = std::sync::Arc::clone(&shared);
+            let expected = expected.clone();
+            std::thread::spawn(move || {
+                for _ in 0..50 {
+                    shared.transform("NOISE");
+                    let mut machine = shared.lock();
+                    machine.reset();
+                    let ciphertext = machine.transform("THE TIME HAS COME");
+                    machine.reset();
+                    assert_eq!(expected, ciphertext);
+                    assert_eq!("THE TIME HAS COME", machine.transform(&ciphertext));
+                }
+            })
+        }).collect();
+        for handle in handles {
+            handle.join().unwrap();
+        }
+        shared.reset();
+        assert_eq!(expected, shared.transform("THE TIME HAS COME"));
+    }
+
+    #[test]
+    // Tests to see if transforming the fields of a record matches transforming them joined into one string, and reads back
+    fn test_transform_fields() {
+        let fields = ["ALICE", "", "MEET AT NOON", "BRING THE KEY"];
+        let mut my_enigma = test_machine();
+        let transformed = my_enigma.transform_fields(&fields);
+        my_enigma.reset();
+        let joined = my_enigma.transform(&fields.join("|"));
+        assert_eq!(joined.split('|').collect::<Vec<&str>>(), transformed);
+        my_enigma.reset();
+        let transformed: Vec<&str> = transformed.iter().map(String::as_str).collect();
+        assert_eq!(fields.to_vec(), my_enigma.transform_fields(&transformed));
+    }
+
+    #[test]
+    // Tests to see if each message of a batch is transformed from the ground setting, so each one reads back on its own
+    fn test_transform_batch() {
+        let messages = ["ATTACK AT DAWN", "", "HOLD THE LINE", "ATTACK AT DAWN"];
+        let mut my_enigma = test_machine();
+        my_enigma.set_ground(('W', 'Z', 'A'));
+        let transformed = my_enigma.transform_batch(&messages);
+        assert_eq!(transformed[0], transformed[3]);
+        my_enigma.reset();
+        let continued = my_enigma.transform_fields(&messages);
+        assert_eq!(continued[0], transformed[0]);
+        assert_ne!(continued[3], transformed[3]);
+        for (message, ciphertext) in messages.iter().zip(&transformed).rev() {
+            let mut receiver = test_machine();
+            receiver.set_ground(('W', 'Z', 'A'));
+            assert_eq!(*message, receiver.transform(ciphertext));
+        }
+        let transformed: Vec<&str> = transformed.iter().map(String::as_str).collect();
+        assert_eq!(messages.to_vec(), my_enigma.transform_batch(&transformed));
+    }
+
+    #[test]
+    // Tests to see if the contacts at either end of the alphabet light A and Z, and a contact beyond them is refused
+    fn test_contact_lamp() {
+        assert_eq!(Ok('A'), contact_lamp(1));
+        assert_eq!(Ok('Z'), contact_lamp(26));
+        assert_eq!(Ok('Z'), contact_lamp(0));
+        assert_eq!(Err(EnigmaError::InvalidContact(27)), contact_lamp(27));
+        assert_eq!("the signal left the machine at contact 27, which has no lamp", EnigmaError::InvalidContact(27).to_string());
+
+        // find the keys that light A and Z at the first press, and check both transforms give them
+        let mut stepped = test_machine();
+        stepped.step(1);
+        let substitution = stepped.substitution();
+        for lamp in ['A', 'Z'] {
+            let key = index_letter(substitution.iter().position(|entry| *entry as usize == letter_index(lamp)).unwrap());
+            assert_eq!(lamp.to_string(), test_machine().transform(&key.to_string()));
+            assert_eq!(Ok(lamp.to_string()), test_machine().try_transform_message(&key.to_string()));
+        }
+        let mut my_enigma = test_machine();
+        let message = "THE TIME HAS COME THE WALRUS SAID";
+        assert_eq!(Ok(test_machine().transform(message)), my_enigma.try_transform_message(message));
+        assert_eq!(test_machine().transform(message), test_machine().transform_message(message.to_owned()));
+    }
+
+    #[test]
+    // Tests to see if the quick constructor gives the well known output of rotors I, II and III at AAA, and reads its message back
+    fn test_default_wehrmacht() {
+        let mut my_enigma = EnigmaMachine::default_wehrmacht();
+        assert_eq!("BDZGO", my_enigma.transform("AAAAA"));
+        assert!(my_enigma.fixed_points().is_empty());
+        my_enigma.reset();
+        let ciphertext = my_enigma.transform("HELLO WORLD");
+        my_enigma.reset();
+        assert_eq!("HELLO WORLD", my_enigma.transform(&ciphertext));
+    }
+
+    #[test]
+    // Tests to see if current_lamp gives the lamp of the present position without stepping, while press_key steps first
+    fn test_current_lamp() {
+        let mut my_enigma = test_machine();
+        let start = my_enigma.rotor_positions();
+        let before = my_enigma.current_lamp('H');
+        assert_eq!(start, my_enigma.rotor_positions());
+        let pressed = my_enigma.press_key('H');
+        assert_ne!(before, pressed);
+        assert_eq!(pressed, my_enigma.current_lamp('H'));
+        assert_eq!(pressed, my_enigma.current_lamp('H'));
+        assert_eq!('7', my_enigma.current_lamp('7'));
+    }
+
+    #[test]
+    // Tests to see if an output stage changes the ciphertext but still decrypts, and if removing it gives the plain machine back
+    fn test_output_stage() {
+        let message = "THE TIME HAS COME";
+        let stage = EnigmaWheel::new("QWERTZUIOASDFGHJKPYXCVBNML".to_owned(), 3, 0);
+        let mut plain = test_machine();
+        let expected = plain.encrypt(message);
+        let mut layered = test_machine().with_output_stage(Some(stage));
+        let ciphertext = layered.encrypt(message);
+        assert_ne!(expected, ciphertext);
+        layered.reset();
+        assert_eq!(message, layered.decrypt(&ciphertext));
+        let mut removed = layered.with_output_stage(None);
+        removed.reset();
+        assert_eq!(expected, removed.encrypt(message));
+    }
+
+    #[test]
+    // Tests to see if stepping back undoes stepping, one press at a time and over the double-step of the middle rotor
+    fn test_step_back() {
+        let mut my_enigma = test_machine();
+        my_enigma.set_rotor_positions(20, 3, 0);
+        let mut history = vec![my_enigma.rotor_positions()];
+        for _ in 0..700 {
+            my_enigma.step(1);
+            history.push(my_enigma.rotor_positions());
+        }
+        while let Some(expected) = history.pop() {
+            assert_eq!(expected, my_enigma.rotor_positions());
+            my_enigma.step_back();
+        }
+
+        let mut wheel = EnigmaWheel::new("EKMFLGDQVZNTOWYHXUSPAIBRCJ".to_owned(), 6, 0);
+        wheel.set_triggers(vec![6]);
+        assert!(wheel.rotate_back());
+        assert_eq!(5, wheel.rotor_position());
+        assert!(!wheel.rotate_back());
+        wheel.set_rotor_position(0);
+        assert!(!wheel.rotate_back());
+        assert_eq!(25, wheel.rotor_position());
+    }
+
+    #[test]
+    // Tests to see if keying with no errors matches transform, and keying with errors changes the output but not its length much
+    fn test_transform_with_errors() {
+        let message = "WETTERVORHERSAGEBISKAYA";
+        let mut rng = XorShift::new(7);
+        let mut expected = test_machine();
+        let mut my_enigma = test_machine();
+        assert_eq!(expected.transform(message), my_enigma.transform_with_errors(message, 0.0, &mut rng));
+
+        expected.reset();
+        my_enigma.reset();
+        let clean = expected.transform(message);
+        let noisy = my_enigma.transform_with_errors(message, 0.5, &mut rng);
+        assert_ne!(clean, noisy);
+        assert!(noisy.len() <= message.len() && noisy.len() >= message.len() / 2);
+    }
+
+    #[test]
+    // Tests to see if a Latin-1 buffer has its letters transformed and its accented byte passed through untouched
+    fn test_transform_latin1() {
+        // "M\xdcNCHEN" in Latin-1, with \xdc for the U with umlaut
+        let input = b"M\xdcNCHEN";
+        let mut my_enigma = test_machine();
+        let output = my_enigma.transform_latin1(input);
+        assert_eq!(input.len(), output.len());
+        assert_eq!(0xDC, output[1]);
+        let mut expected = test_machine();
+        assert_eq!(expected.transform("M NCHEN").replace(' ', ""), String::from_utf8([&output[..1], &output[2..]].concat()).unwrap());
+        my_enigma.reset();
+        assert_eq!(input.to_vec(), my_enigma.transform_latin1(&output));
+    }
+
+    #[test]
+    // Tests to see if a field-wired reflector keeps the machine reciprocal at every position it is set to, on an M4 as well
+    fn test_set_reflector() {
+        let ukw_d = Reflector::from_pairs("AC BO DI EG FR HU JY KT LZ MX NW PQ SV").unwrap();
+        let message = "VONVONJLOOKSJHFFTTTEINSEINSDREIZWOYYQNNSNEUNINHALTXX";
+        let mut outputs = vec![];
+        for position in [0, 1, 7, 13, 25] {
+            let mut my_enigma = MachineBuilder::new().model(Model::M4).rotors(RotorId::II, RotorId::IV, RotorId::V)
+                .greek(RotorId::Beta, 0, 0).reflector(ReflectorId::BThin).build().unwrap();
+            my_enigma.set_reflector(&ukw_d, position);
+            let ciphertext = my_enigma.transform(message);
+            assert!(ciphertext.chars().zip(message.chars()).all(|(cipher, plain)| cipher != plain));
+            my_enigma.reset();
+            assert_eq!(message, my_enigma.transform(&ciphertext));
+            outputs.push(ciphertext);
+        }
+        outputs.dedup();
+        assert_eq!(5, outputs.len());
+    }
+
+    #[test]
+    // Tests to see if the press that first moves the middle rotor is predicted, including a double-step and a rotor with no notch
+    fn test_presses_until_middle_step() {
+        // rotor III turns over as its window leaves V, so from A the 22nd press moves the middle rotor
+        let mut my_enigma = MachineBuilder::new().build().unwrap();
+        assert_eq!(22, my_enigma.presses_until_middle_step());
+        my_enigma.step(21);
+        assert_eq!(0, my_enigma.rotor_positions().1);
+        assert_eq!(1, my_enigma.presses_until_middle_step());
+        my_enigma.step(1);
+        assert_eq!(1, my_enigma.rotor_positions().1);
+        assert_eq!(26, my_enigma.presses_until_middle_step());
+
+        // rotor II in the middle is at its turnover at E, so it double-steps on the next press
+        my_enigma.set_rotor_positions(10, 4, 0);
+        assert_eq!(1, my_enigma.presses_until_middle_step());
+
+        my_enigma.set_triggers(vec![], vec![], vec![]);
+        assert_eq!(usize::MAX, my_enigma.presses_until_middle_step());
+    }
+
+    #[test]
+    // Tests to see if a static slot never moves while the other slots step as usual, double-step included, on every stepping path
+    fn test_slot_stepping() {
+        let mut standard = test_machine();
+        let mut my_enigma = test_machine();
+        my_enigma.set_slot_stepping(true, true, false);
+        assert_eq!((true, true, false), my_enigma.slot_stepping());
+        let (_, _, left) = my_enigma.rotor_positions();
+        let mut history = vec![my_enigma.rotor_positions()];
+        let mut double_steps = 0;
+        for _ in 0..2000 {
+            let middle_before = my_enigma.rotor_positions().1;
+            let middle_steps_next = my_enigma.presses_until_middle_step() == 1;
+            standard.step(1);
+            my_enigma.step(1);
+            let (right, middle, static_left) = my_enigma.rotor_positions();
+            assert_eq!(left, static_left);
+            assert_eq!((standard.rotor_positions().0, standard.rotor_positions().1), (right, middle));
+            assert_eq!(middle_steps_next, middle != middle_before);
+            // the middle rotor moves on two presses in a row only by double-stepping
+            if middle != middle_before && history.len() > 1 && history[history.len() - 1].1 != history[history.len() - 2].1 {
+                double_steps += 1;
+            }
+            history.push((right, middle, static_left));
+        }
+        assert!(double_steps > 0);
+        assert_ne!(standard.rotor_positions().2, left);
+        while let Some(expected) = history.pop() {
+            assert_eq!(expected, my_enigma.rotor_positions());
+            my_enigma.step_back();
+        }
+
+        // the bulk path and the key-by-key path agree, and a static middle slot holds the slow rotor still as well
+        for (stepping, orientation) in [((true, true, false), Orientation::RightFast), ((false, true, true), Orientation::LeftFast),
+            ((true, false, true), Orientation::RightFast)] {
+            let mut slow_path = test_machine();
+            slow_path.set_orientation(orientation);
+            slow_path.set_slot_stepping(stepping.0, stepping.1, stepping.2);
+            let mut fast_path = slow_path.clone();
+            let message = "A".repeat(2000);
+            let expected = slow_path.transform(&message);
+            let mut output = vec![0u8; message.len()];
+            fast_path.transform_bulk(message.as_bytes(), &mut output);
+            assert_eq!(expected.as_bytes(), &output[..]);
+            assert_eq!(slow_path.rotor_positions(), fast_path.rotor_positions());
+        }
+        let mut held = test_machine();
+        held.set_slot_stepping(true, false, true);
+        held.step(2000);
+        assert_eq!((test_machine().rotor_positions().1, test_machine().rotor_positions().2),
+            (held.rotor_positions().1, held.rotor_positions().2));
+        assert_eq!(usize::MAX, held.presses_until_middle_step());
+    }
+
+    #[test]
+    // Tests to see if a four-rotor M4 with its third slot static never advances that rotor, while the Greek wheel in the fourth
+    // slot stays put and the first two slots step as usual, double-step included
+    fn test_slot_stepping_four_rotors() {
+        let m4 = || MachineBuilder::new().model(Model::M4).rotors(RotorId::II, RotorId::IV, RotorId::V)
+            .greek(RotorId::Beta, 0, 5).reflector(ReflectorId::BThin).positions([3, 8, 20]).build().unwrap();
+        let mut standard = m4();
+        let mut my_enigma = m4();
+        my_enigma.set_slot_stepping(true, true, false);
+        let greek_and_reflector = my_enigma.reflector.table();
+        let (_, _, left) = my_enigma.rotor_positions();
+        let mut middle_moves = vec![];
+        for _ in 0..700 {
+            let middle_before = my_enigma.rotor_positions().1;
+            standard.step(1);
+            my_enigma.step(1);
+            let (right, middle, static_left) = my_enigma.rotor_positions();
+            assert_eq!(left, static_left);
+            assert_eq!((standard.rotor_positions().0, standard.rotor_positions().1), (right, middle));
+            assert_eq!(greek_and_reflector, my_enigma.reflector.table());
+            middle_moves.push(middle != middle_before);
+        }
+        // rotor V carries into rotor IV at its 6th press, and rotor IV double-steps on the 7th
+        assert_eq!(&[false, false, false, false, false, true, true, false], &middle_moves[..8]);
+        assert_ne!(left, standard.rotor_positions().2);
+    }
+
+    #[test]
+    // Tests to see if setting the rotors between messages gives the same output as a machine built at those positions
+    fn test_set_rotor_positions_between_messages() {
+        let mut my_enigma = MachineBuilder::new().rings([4, 17, 9]).build().unwrap();
+        for (right, middle, left) in [(0, 0, 0), (21, 3, 0), (0, 0, 0), (21, 4, 25), (21, 3, 0)] {
+            my_enigma.set_rotor_positions(right, middle, left);
+            let expected = MachineBuilder::new().rings([4, 17, 9]).positions([left, middle, right]).build().unwrap()
+                .transform("WETTERVORHERSAGE");
+            assert_eq!(expected, my_enigma.transform("WETTERVORHERSAGE"));
+        }
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    // Tests to see if the JSON has an object for each letter, holding the same lamps and positions as the plain transform
+    fn test_transform_message_json() {
+        use crate::json::{parse, JsonValue};
+
+        let mut my_enigma = test_machine();
+        let json = my_enigma.transform_message_json("HELLO, WORLD");
+        let presses = match parse(&json) {
+            Ok(JsonValue::Array(presses)) => presses,
+            other => panic!("not a JSON array: {:?}", other)
+        };
+        assert_eq!(10, presses.len());
+        let end = my_enigma.window_position();
+        my_enigma.reset();
+        let expected = my_enigma.transform("HELLOWORLD");
+        let text = |press: &JsonValue, field: &str| match press.get(field) {
+            Some(JsonValue::Str(text)) => text.clone(),
+            other => panic!("field {} is not a string: {:?}", field, other)
+        };
+        assert_eq!("HELLOWORLD", presses.iter().map(|press| text(press, "input")).collect::<String>());
+        assert_eq!(expected, presses.iter().map(|press| text(press, "output")).collect::<String>());
+        assert_eq!(format!("{}{}{}", end.0, end.1, end.2), text(&presses[9], "positions"));
     }
 }
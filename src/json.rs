@@ -0,0 +1,230 @@
+/* The json module is a small reader for the JSON documents exported by other Enigma tools. It handles the whole JSON grammar, but
+   keeps numbers as f64 and objects as a list of fields in the order they were written, which is all the importers need. */
+
+/* A JsonValue is one parsed JSON value */
+#[derive(Clone, Debug, PartialEq)]
+pub enum JsonValue {
+    Null,
+    Bool(bool),
+    Number(f64),
+    Str(String),
+    Array(Vec<JsonValue>),
+    Object(Vec<(String, JsonValue)>)
+}
+
+impl JsonValue {
+    /* function: get
+       input: &str containing the name of a field
+       output: Option containing the value of the field, or None if this is not an object or has no such field */
+    pub fn get(&self, field: &str) -> Option<&JsonValue> {
+        match self {
+            JsonValue::Object(fields) => fields.iter().find(|(name, _)| name == field).map(|(_, value)| value),
+            _ => None
+        }
+    }
+}
+
+/* function: parse
+   input: &str containing a JSON document
+   output: Result containing the parsed JsonValue, or a String describing the first problem found and the byte offset it was found at
+   limitations: arrays and objects may nest at most MAX_DEPTH deep, so a hostile document cannot overflow the stack */
+pub fn parse(text: &str) -> Result<JsonValue, String> {
+    let mut parser = Parser { bytes: text.as_bytes(), at: 0, depth: 0 };
+    let value = parser.value()?;
+    parser.skip_whitespace();
+    if parser.at != parser.bytes.len() {
+        return Err(parser.error("unexpected trailing characters"));
+    }
+
+    Ok(value)
+}
+
+/* The deepest arrays and objects may nest, far beyond any exported settings file */
+const MAX_DEPTH: usize = 64;
+
+struct Parser<'a> {
+    bytes: &'a [u8],
+    at: usize,
+    depth: usize
+}
+
+impl Parser<'_> {
+    fn error(&self, message: &str) -> String {
+        format!("{} at offset {}", message, self.at)
+    }
+
+    fn skip_whitespace(&mut self) {
+        while self.at < self.bytes.len() && self.bytes[self.at].is_ascii_whitespace() {
+            self.at += 1;
+        }
+    }
+
+    fn expect(&mut self, byte: u8) -> Result<(), String> {
+        self.skip_whitespace();
+        if self.bytes.get(self.at) == Some(&byte) {
+            self.at += 1;
+            Ok(())
+        } else {
+            Err(self.error(&format!("expected '{}'", byte as char)))
+        }
+    }
+
+    fn literal(&mut self, word: &str, value: JsonValue) -> Result<JsonValue, String> {
+        if self.bytes[self.at..].starts_with(word.as_bytes()) {
+            self.at += word.len();
+            Ok(value)
+        } else {
+            Err(self.error("unexpected character"))
+        }
+    }
+
+    fn value(&mut self) -> Result<JsonValue, String> {
+        self.skip_whitespace();
+        match self.bytes.get(self.at) {
+            Some(b'{') => self.nested(Self::object),
+            Some(b'[') => self.nested(Self::array),
+            Some(b'"') => Ok(JsonValue::Str(self.string()?)),
+            Some(b't') => self.literal("true", JsonValue::Bool(true)),
+            Some(b'f') => self.literal("false", JsonValue::Bool(false)),
+            Some(b'n') => self.literal("null", JsonValue::Null),
+            Some(byte) if *byte == b'-' || byte.is_ascii_digit() => self.number(),
+            Some(_) => Err(self.error("unexpected character")),
+            None => Err(self.error("unexpected end of input"))
+        }
+    }
+
+    fn nested(&mut self, parse: fn(&mut Self) -> Result<JsonValue, String>) -> Result<JsonValue, String> {
+        if self.depth == MAX_DEPTH {
+            return Err(self.error("nesting too deep"));
+        }
+        self.depth += 1;
+        let value = parse(self);
+        self.depth -= 1;
+        value
+    }
+
+    fn object(&mut self) -> Result<JsonValue, String> {
+        let mut fields: Vec<(String, JsonValue)> = vec![];
+        self.expect(b'{')?;
+        self.skip_whitespace();
+        if self.bytes.get(self.at) == Some(&b'}') {
+            self.at += 1;
+            return Ok(JsonValue::Object(fields));
+        }
+        loop {
+            self.skip_whitespace();
+            let name = self.string()?;
+            self.expect(b':')?;
+            fields.push((name, self.value()?));
+            self.skip_whitespace();
+            match self.bytes.get(self.at) {
+                Some(b',') => self.at += 1,
+                Some(b'}') => {
+                    self.at += 1;
+                    return Ok(JsonValue::Object(fields));
+                }
+                _ => return Err(self.error("expected ',' or '}'"))
+            }
+        }
+    }
+
+    fn array(&mut self) -> Result<JsonValue, String> {
+        let mut items: Vec<JsonValue> = vec![];
+        self.expect(b'[')?;
+        self.skip_whitespace();
+        if self.bytes.get(self.at) == Some(&b']') {
+            self.at += 1;
+            return Ok(JsonValue::Array(items));
+        }
+        loop {
+            items.push(self.value()?);
+            self.skip_whitespace();
+            match self.bytes.get(self.at) {
+                Some(b',') => self.at += 1,
+                Some(b']') => {
+                    self.at += 1;
+                    return Ok(JsonValue::Array(items));
+                }
+                _ => return Err(self.error("expected ',' or ']'"))
+            }
+        }
+    }
+
+    fn string(&mut self) -> Result<String, String> {
+        if self.bytes.get(self.at) != Some(&b'"') {
+            return Err(self.error("expected '\"'"));
+        }
+        self.at += 1;
+        let mut text: Vec<u8> = vec![];
+        loop {
+            match self.bytes.get(self.at) {
+                Some(b'"') => {
+                    self.at += 1;
+                    return String::from_utf8(text).map_err(|_| self.error("invalid UTF-8 in string"));
+                }
+                Some(b'\\') => {
+                    self.at += 1;
+                    let escaped = match self.bytes.get(self.at) {
+                        Some(b'"') => '"',
+                        Some(b'\\') => '\\',
+                        Some(b'/') => '/',
+                        Some(b'b') => '\u{8}',
+                        Some(b'f') => '\u{c}',
+                        Some(b'n') => '\n',
+                        Some(b'r') => '\r',
+                        Some(b't') => '\t',
+                        Some(b'u') => {
+                            let digits = self.bytes.get(self.at + 1..self.at + 5).ok_or_else(|| self.error("truncated escape"))?;
+                            let code = std::str::from_utf8(digits).ok()
+                                .and_then(|digits| u32::from_str_radix(digits, 16).ok())
+                                .ok_or_else(|| self.error("invalid escape"))?;
+                            self.at += 4;
+                            char::from_u32(code).unwrap_or('\u{fffd}')
+                        }
+                        _ => return Err(self.error("invalid escape"))
+                    };
+                    let mut buffer = [0u8; 4];
+                    text.extend_from_slice(escaped.encode_utf8(&mut buffer).as_bytes());
+                    self.at += 1;
+                }
+                Some(byte) => {
+                    text.push(*byte);
+                    self.at += 1;
+                }
+                None => return Err(self.error("unterminated string"))
+            }
+        }
+    }
+
+    fn number(&mut self) -> Result<JsonValue, String> {
+        let start = self.at;
+        while self.at < self.bytes.len() && matches!(self.bytes[self.at], b'-' | b'+' | b'.' | b'e' | b'E' | b'0'..=b'9') {
+            self.at += 1;
+        }
+        std::str::from_utf8(&self.bytes[start..self.at]).ok()
+            .and_then(|digits| digits.parse::<f64>().ok())
+            .map(JsonValue::Number)
+            .ok_or_else(|| self.error("invalid number"))
+    }
+}
+
+#[test]
+// Tests to see if a document using each kind of value parses, and a malformed one is rejected
+fn test_parse() {
+    let value = parse(r#" {"name": "B!", "list": [1, -2.5e1, true, false, null], "empty": {}} "#).unwrap();
+    assert_eq!(Some(&JsonValue::Str("B!".to_owned())), value.get("name"));
+    assert_eq!(Some(&JsonValue::Array(vec![JsonValue::Number(1.0), JsonValue::Number(-25.0), JsonValue::Bool(true),
+        JsonValue::Bool(false), JsonValue::Null])), value.get("list"));
+    assert_eq!(Some(&JsonValue::Object(vec![])), value.get("empty"));
+    assert!(parse(r#"{"name": "B""#).is_err());
+    assert!(parse("[1, 2] 3").is_err());
+}
+
+#[test]
+// Tests to see if arrays and objects nested past MAX_DEPTH are rejected instead of overflowing the stack
+fn test_parse_depth() {
+    let nested = |depth: usize| "[".repeat(depth) + &"]".repeat(depth);
+    assert!(parse(&nested(MAX_DEPTH)).is_ok());
+    assert_eq!(Err("nesting too deep at offset 64".to_owned()), parse(&nested(MAX_DEPTH + 1)));
+    assert!(parse(&"[{\"a\":".repeat(100_000)).unwrap_err().starts_with("nesting too deep"));
+}
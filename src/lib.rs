@@ -1,7 +1,21 @@
+pub mod alphabet;
+pub mod analysis;
+#[cfg(feature = "async")]
+pub mod async_io;
+#[cfg(feature = "base64")]
+pub mod base64;
+pub mod config;
+pub mod enigma;
+mod json;
+pub mod numeric;
+pub mod presets;
+pub mod random;
+
+#[allow(clippy::module_inception)]
 pub mod lib {
     /* The Cipher trait provides methods for enciphering and deciphering the underlying object */
     pub trait Cipher {
         fn encipher(&self, message: &str) -> String;
         fn decipher(&self, message: &str) -> String;
     }
-}
\ No newline at end of file
+}
@@ -1,27 +1,47 @@
-mod enigma;
-mod lib;
-use crate::enigma::enigma_machine::EnigmaMachine;
+/* A small command line Enigma. The settings are given as arguments, in the order a key sheet lists them, and the text read from
+   standard input is transformed and written to standard output, e.g.:
+     echo "HELLO WORLD" | enigma II-I-III XMV ABL AM.FI.NV.PS.TU.WZ A
+   As the machine is reciprocal, running the ciphertext through again with the same settings gives back the plaintext. */
+use std::env;
+use std::io::{self, Read, Write};
+use std::process::ExitCode;
+use enigma::enigma::enigma_machine::EnigmaMachine;
 
-// TODO: Find some test material with plugboards, double-stepping and ring settings
+const USAGE: &str = "usage: enigma ROTORS RINGS POSITIONS [PLUGBOARD] REFLECTOR < input
 
-fn main() {
-    let mut my_enigma = EnigmaMachine::new(
-        "ABCDEFGHIJKLMNOPQRSTUVWXYZ".to_owned(),           // plugboard cipher
-        "BDFHJLCPRTXVZNYEIWGAKMUSQO".to_owned(), 10, 2,    // rightmost rotor cipher, offset and ring setting
-        "AJDKSIRUXBLHWTMCQGZNPYFVOE".to_owned(), 2, 9,     // middle rotor cipher, offset and ring setting
-        "EKMFLGDQVZNTOWYHXUSPAIBRCJ".to_owned(), 12, 7,    // leftmost rotor cipher, offset and ring setting
-        "YRUHQSLDPXNGOKMIEBFZCWVJAT".to_owned()            // reflector cipher
-    );
-    my_enigma.set_triggers(vec![22], vec![5], vec![17]);   // set rotor tunrover points
-    let transformed:String = my_enigma.transform_message("ENIGMA REVEALED".to_owned());
-    println!("{}", transformed);
-    my_enigma.set_rotor_positions(10, 2, 12);
-    let retransformed:String = my_enigma.transform_message(transformed);
-    println!("{}", retransformed);
-    my_enigma.set_rotor_positions(10, 2, 12);
-    let encoded:String = my_enigma.transform_message("THE TIME HAS COME THE WALRUS SAID TO TALK OF MANY THINGS OF SHOES AND SHIPS AND SEALING-WAX OF CABBAGES AND KINGS AND WHY THE SEA IS BOILING HOT AND WHETHER PIGS HAVE WINGS".to_owned());
-    println!("{}", encoded);
-    my_enigma.set_rotor_positions(10, 2, 12);
-    let decoded:String = my_enigma.transform_message(encoded);
-    println!("{}", decoded);
+  ROTORS     rotor names joined by dashes, leftmost first, e.g.: III-I-II, or four with the Greek rotor in front for an M4
+  RINGS      one ring setting letter per rotor, leftmost first, e.g.: FVN
+  POSITIONS  one start position letter per rotor, leftmost first, e.g.: QEV
+  PLUGBOARD  plugboard pairs joined by dots, e.g.: AB.CD.EF; leave it out if no plugs are used
+  REFLECTOR  reflector name, with or without the UKW- prefix, e.g.: B
+
+Standard input is transformed to standard output. Lowercase letters are read as uppercase, and anything else that is not a
+letter is copied through unchanged.";
+
+fn main() -> ExitCode {
+    let args: Vec<String> = env::args().skip(1).collect();
+    if args.len() != 4 && args.len() != 5 {
+        eprintln!("{}", USAGE);
+        return ExitCode::FAILURE;
+    }
+    let mut machine: EnigmaMachine = match args.join(" ").parse() {
+        Ok(machine) => machine,
+        Err(error) => {
+            eprintln!("enigma: {}\n\n{}", error, USAGE);
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let mut input = String::new();
+    if let Err(error) = io::stdin().read_to_string(&mut input) {
+        eprintln!("enigma: could not read standard input: {}", error);
+        return ExitCode::FAILURE;
+    }
+    let output = machine.transform(&input.to_ascii_uppercase());
+    if let Err(error) = io::stdout().write_all(output.as_bytes()) {
+        eprintln!("enigma: could not write standard output: {}", error);
+        return ExitCode::FAILURE;
+    }
+
+    ExitCode::SUCCESS
 }
@@ -0,0 +1,189 @@
+/* The numeric module holds a small Enigma that enciphers digits rather than letters, as some variants did for numeric traffic sent in
+   five digit groups. It works the same way as the EnigmaMachine, over the ten digits of Alphabet::DIGITS. */
+use crate::alphabet::Alphabet;
+use crate::lib::Cipher;
+
+/* A NumericWheel is a representation of a rotor wired between ten contacts. It contains the following:
+     An array of 10 u8s called wiring giving the contact each contact is wired to, with the wheel at position 0
+     An array of 10 u8s called inverse that undoes wiring
+     A usize called position giving the digit showing in the window
+   A NumericWheel has the following functions available to it:
+     new is a constructor that returns a NumericWheel given its wiring as a String of digits and its starting position
+     rotate is a function that advances the wheel one place and reports whether it has completed a revolution
+     position is a function that returns the digit showing in the window
+   NumericWheel implements the trait Cipher, treating its wiring at the current position as a digit substitution */
+#[derive(Clone, Debug)]
+pub struct NumericWheel {
+    wiring: [u8; 10],
+    inverse: [u8; 10],
+    position: usize
+}
+
+impl NumericWheel {
+    /* function: new
+       inputs: &str containing the wiring as an enciphered run of digits, e.g.: "3917508264"
+               usize giving the starting position, stored mod 10
+       output: NumericWheel with the given wiring and position
+       limitations: The wiring must be a permutation of the ten digits. Characters that are not digits are read as 0 */
+    pub fn new(wiring: &str, position: usize) -> NumericWheel {
+        let digits = Alphabet::DIGITS;
+        let mut wheel = NumericWheel { wiring: [0; 10], inverse: [0; 10], position: position % digits.len() };
+        for (contact, digit) in wiring.chars().take(digits.len()).enumerate() {
+            wheel.wiring[contact] = if digits.contains(digit) { digits.index(digit) as u8 } else { 0 };
+        }
+        for contact in (0..digits.len()).rev() {
+            wheel.inverse[wheel.wiring[contact] as usize] = contact as u8;
+        }
+
+        wheel
+    }
+
+    /* function: rotate
+       input: none
+       output: bool indicating whether the wheel has come back round to 0, which turns the next wheel over
+       limitations: none obvious at this time */
+    pub fn rotate(&mut self) -> bool {
+        self.position = (self.position + 1) % Alphabet::DIGITS.len();
+        self.position == 0
+    }
+
+    pub fn position(&self) -> usize {
+        self.position
+    }
+
+    // Traces a signal entering at contact through the wheel towards the reflector
+    fn forward(&self, contact: usize) -> usize {
+        let len = Alphabet::DIGITS.len();
+        (self.wiring[(contact + self.position) % len] as usize + len - self.position) % len
+    }
+
+    // Traces a signal coming back from the reflector through the wheel
+    fn backward(&self, contact: usize) -> usize {
+        let len = Alphabet::DIGITS.len();
+        (self.inverse[(contact + self.position) % len] as usize + len - self.position) % len
+    }
+}
+
+// The implementation of the Cipher trait for a NumericWheel object
+impl Cipher for NumericWheel {
+    /* function: encipher
+       input: &str containing the message to be enciphered
+       output: String containing the enciphered message
+       limitations: only digits are enciphered, and every other character is copied unchanged */
+    fn encipher(&self, message: &str) -> String {
+        let digits = Alphabet::DIGITS;
+        message.chars()
+            .map(|chr| if digits.contains(chr) { digits.symbol(self.forward(digits.index(chr))) } else { chr })
+            .collect()
+    }
+
+    /* function: decipher
+       input: &str containing the message to be deciphered
+       output: String containing the deciphered message
+       limitations: only digits are deciphered, and every other character is copied unchanged */
+    fn decipher(&self, message: &str) -> String {
+        let digits = Alphabet::DIGITS;
+        message.chars()
+            .map(|chr| if digits.contains(chr) { digits.symbol(self.backward(digits.index(chr))) } else { chr })
+            .collect()
+    }
+}
+
+/* A NumericMachine is a representation of an Enigma over the ten digits. It contains the following:
+     A Vec of NumericWheels called wheels, with the fast wheel first
+     An array of 10 u8s called reflector giving the digit each digit is paired with
+     A Vec of usizes called ground giving the starting position of each wheel, which reset returns to
+   A NumericMachine has the following functions available to it:
+     new is a constructor that returns a NumericMachine given its wheels, fast wheel first, and its reflector wiring
+     press_key is a function that steps the wheels and returns the digit a key lights
+     transform is a function that transforms a message by pressing each of its keys in turn
+     reset is a function that returns the wheels to their starting positions */
+#[derive(Clone, Debug)]
+pub struct NumericMachine {
+    wheels: Vec<NumericWheel>,
+    reflector: [u8; 10],
+    ground: Vec<usize>
+}
+
+impl NumericMachine {
+    /* function: new
+       inputs: Vec<NumericWheel> holding the wheels, fast wheel first
+               &str containing the reflector wiring as digits, e.g.: "5678901234" pairs 0 with 5, 1 with 6 and so on
+       output: NumericMachine with the given wheels and reflector
+       limitations: The reflector must pair each digit with a different digit, or the machine is not reciprocal. Characters that
+          are not digits are read as 0 */
+    pub fn new(wheels: Vec<NumericWheel>, reflector: &str) -> NumericMachine {
+        let digits = Alphabet::DIGITS;
+        let mut pairs = [0u8; 10];
+        for (contact, digit) in reflector.chars().take(digits.len()).enumerate() {
+            pairs[contact] = if digits.contains(digit) { digits.index(digit) as u8 } else { 0 };
+        }
+        let ground = wheels.iter().map(|wheel| wheel.position()).collect();
+
+        NumericMachine { wheels, reflector: pairs, ground }
+    }
+
+    /* function: press_key
+       inputs: char representing the key pressed
+       output: char representing the digit lit by the key press
+       limitations: Only digits step the wheels and are transformed. Any other character is returned unchanged
+       algorithm: the fast wheel steps on every key, and each wheel turns the next one over as it comes back round to 0, like an
+          odometer. The signal then passes through the wheels to the reflector and back */
+    pub fn press_key(&mut self, key: char) -> char {
+        let digits = Alphabet::DIGITS;
+        if !digits.contains(key) {
+            return key;
+        }
+        for wheel in self.wheels.iter_mut() {
+            if !wheel.rotate() {
+                break;
+            }
+        }
+        let mut contact = digits.index(key);
+        for wheel in &self.wheels {
+            contact = wheel.forward(contact);
+        }
+        contact = self.reflector[contact] as usize;
+        for wheel in self.wheels.iter().rev() {
+            contact = wheel.backward(contact);
+        }
+
+        digits.symbol(contact)
+    }
+
+    /* function: transform
+       inputs: &str containing the message to be transformed, either by encoding or decoding
+       output: String containing the message after encoding or decoding
+       limitations: None obvious as this time */
+    pub fn transform(&mut self, message: &str) -> String {
+        message.chars().map(|key| self.press_key(key)).collect()
+    }
+
+    pub fn reset(&mut self) {
+        for (wheel, position) in self.wheels.iter_mut().zip(&self.ground) {
+            wheel.position = *position;
+        }
+    }
+}
+
+#[test]
+// Tests to see if a numeric message round-trips through a two wheel machine and no digit enciphers to itself
+fn test_numeric_machine() {
+    let mut machine = NumericMachine::new(vec![NumericWheel::new("3917508264", 4), NumericWheel::new("8302716945", 7)], "5678901234");
+    let message = "31415 92653 58979 32384";
+    let ciphertext = machine.transform(message);
+    assert_eq!(message.len(), ciphertext.len());
+    assert!(message.chars().zip(ciphertext.chars()).all(|(plain, cipher)| plain == ' ' || plain != cipher));
+    machine.reset();
+    assert_eq!(message, machine.transform(&ciphertext));
+}
+
+#[test]
+// Tests to see if a wheel's substitution is undone by deciphering and moves with the wheel's position
+fn test_numeric_wheel() {
+    let mut wheel = NumericWheel::new("3917508264", 0);
+    assert_eq!("3917508264", wheel.encipher("0123456789"));
+    assert_eq!("0123456789", wheel.decipher(&wheel.encipher("0123456789")));
+    assert!(!wheel.rotate());
+    assert_eq!("8064971532", wheel.encipher("0123456789"));
+}
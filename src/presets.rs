@@ -0,0 +1,397 @@
+/* The presets module holds the wiring and turnover letters of the historical rotors and reflectors, so machines can be described
+   by the names an operator would use rather than by their wiring. */
+use std::collections::HashMap;
+use std::sync::OnceLock;
+use crate::alphabet::index_letter;
+use crate::config::MachineConfig;
+use crate::random::Rng;
+
+/* A RotorId names one of the historical Enigma rotors. Each rotor has the following available to it:
+     wiring is a function that returns the rotor's wiring as the enciphered alphabet, suitable for EnigmaWheel::new
+     turnovers is a function that returns the window letters at which the rotor turns over its neighbour
+     from_name is a function that returns the RotorId for a name such as "III", "Beta" or "I-K"
+     name is a function that returns that name
+   The wirings and turnover letters are read from rotors.csv, see by_name. ALL lists every rotor, in the order they are declared.
+   Beta and Gamma are the thin Greek rotors of the M4, which sit beside the reflector and never step. IK, IIK and IIIK are the
+   rotors of the Swiss K. */
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum RotorId {
+    I,
+    II,
+    III,
+    IV,
+    V,
+    VI,
+    VII,
+    VIII,
+    Beta,
+    Gamma,
+    IK,
+    IIK,
+    IIIK
+}
+
+impl RotorId {
+    pub const ALL: [RotorId; 13] = [RotorId::I, RotorId::II, RotorId::III, RotorId::IV, RotorId::V, RotorId::VI, RotorId::VII,
+        RotorId::VIII, RotorId::Beta, RotorId::Gamma, RotorId::IK, RotorId::IIK, RotorId::IIIK];
+
+    /* function: name
+       input: none
+       output: &'static str holding the name an operator would use for the rotor, e.g.: "III", "Beta" or "I-K"
+       limitations: none obvious at this time */
+    pub fn name(&self) -> &'static str {
+        match self {
+            RotorId::I => "I",
+            RotorId::II => "II",
+            RotorId::III => "III",
+            RotorId::IV => "IV",
+            RotorId::V => "V",
+            RotorId::VI => "VI",
+            RotorId::VII => "VII",
+            RotorId::VIII => "VIII",
+            RotorId::Beta => "Beta",
+            RotorId::Gamma => "Gamma",
+            RotorId::IK => "I-K",
+            RotorId::IIK => "II-K",
+            RotorId::IIIK => "III-K"
+        }
+    }
+
+    pub fn wiring(&self) -> &'static str {
+        self.definition().wiring
+    }
+
+    pub fn turnovers(&self) -> &'static str {
+        self.definition().notches
+    }
+
+    pub fn from_name(name: &str) -> Option<RotorId> {
+        RotorId::ALL.iter().find(|rotor| rotor.name() == name).copied()
+    }
+
+    // Looks the rotor up in the embedded table. Every RotorId has a row there, which test_rotor_table checks
+    fn definition(&self) -> RotorDef {
+        by_name(self.name()).unwrap_or_else(|| panic!("rotors.csv has no row for rotor {}", self.name()))
+    }
+}
+
+/* A RotorDef is one row of the embedded rotor table, rotors.csv. It contains the following:
+     A &'static str called name holding the name an operator would use for the rotor, e.g.: "III"
+     A &'static str called wiring holding the rotor's wiring as the enciphered alphabet, suitable for EnigmaWheel::new
+     A &'static str called notches holding the window letters at which the rotor turns over its neighbour, e.g.: "V" */
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RotorDef {
+    pub name: &'static str,
+    pub wiring: &'static str,
+    pub notches: &'static str
+}
+
+/* function: by_name
+   input: &str containing the name of a rotor, e.g.: "III" or "Beta"
+   output: Option containing the RotorDef of that name from the embedded table, or None if there is no such rotor
+   limitations: the names must match the table exactly, so "iii" is not found
+   algorithm: rotors.csv is compiled into the library, and is parsed into a map the first time any rotor is looked up. Lines
+      starting with # are comments, and the first line left is the header. The table is part of the source, so a malformed row is
+      a bug and panics rather than being skipped */
+pub fn by_name(name: &str) -> Option<RotorDef> {
+    static ROTORS: OnceLock<HashMap<&'static str, RotorDef>> = OnceLock::new();
+    ROTORS.get_or_init(|| {
+        include_str!("rotors.csv").lines()
+            .filter(|line| !line.trim().is_empty() && !line.starts_with('#'))
+            .skip(1)
+            .map(|line| {
+                let fields: Vec<&'static str> = line.trim().split(',').collect();
+                assert_eq!(3, fields.len(), "rotors.csv row '{}' does not have 3 fields", line);
+                (fields[0], RotorDef { name: fields[0], wiring: fields[1], notches: fields[2] })
+            })
+            .collect()
+    }).get(name).copied()
+}
+
+/* A ReflectorId names one of the historical Enigma reflectors (Umkehrwalzen). Each reflector has the following available to it:
+     wiring is a function that returns the reflector's wiring as the enciphered alphabet
+     is_thin is a function that reports whether the reflector is one of the thin reflectors made to sit beside an M4 Greek rotor
+     from_name is a function that returns the ReflectorId for a name such as "B", "UKW-B" or "B-thin" */
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum ReflectorId {
+    A,
+    B,
+    C,
+    BThin,
+    CThin,
+    K
+}
+
+impl ReflectorId {
+    pub fn wiring(&self) -> &'static str {
+        match self {
+            ReflectorId::A => "EJMZALYXVBWFCRQUONTSPIKHGD",
+            ReflectorId::B => "YRUHQSLDPXNGOKMIEBFZCWVJAT",
+            ReflectorId::C => "FVPJIAOYEDRZXWGCTKUQSBNMHL",
+            ReflectorId::BThin => "ENKQAUYWJICOPBLMDXZVFTHRGS",
+            ReflectorId::CThin => "RDOBJNTKVEHMLFCWZAXGYIPSUQ",
+            ReflectorId::K => "IMETCGFRAYSQBZXWLHKDVUPOJN"
+        }
+    }
+
+    pub fn is_thin(&self) -> bool {
+        matches!(self, ReflectorId::BThin | ReflectorId::CThin)
+    }
+
+    pub fn from_name(name: &str) -> Option<ReflectorId> {
+        match name.strip_prefix("UKW-").unwrap_or(name) {
+            "A" => Some(ReflectorId::A),
+            "B" => Some(ReflectorId::B),
+            "C" => Some(ReflectorId::C),
+            "B-thin" => Some(ReflectorId::BThin),
+            "C-thin" => Some(ReflectorId::CThin),
+            "K" => Some(ReflectorId::K),
+            _ => None
+        }
+    }
+}
+
+/* A Model names one of the historical Enigma models, each of which could only be fitted with certain rotors and reflectors:
+     EnigmaI is the Wehrmacht and Luftwaffe machine, taking three of rotors I to V and reflector A, B or C, with a plugboard
+     M3 is the Kriegsmarine machine, taking three of rotors I to VIII and reflector B or C, with a plugboard
+     M4 is the four rotor Kriegsmarine machine, taking three of rotors I to VIII beside a Beta or Gamma Greek rotor and a thin
+        reflector, with a plugboard
+     SwissK is the commercial machine used by the Swiss army, taking three of rotors I-K to III-K and reflector K. It has no
+        plugboard, and its entry wheel is wired in keyboard order rather than alphabetical order
+   A Model has the following functions available to it:
+     allows_rotor and allows_reflector report whether the model could be fitted with a rotor or reflector
+     needs_greek_rotor reports whether the model has a fourth, Greek, rotor
+     has_plugboard reports whether the model has a plugboard
+     entry_wheel returns the wiring of the model's entry wheel when it is not wired in alphabetical order */
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Model {
+    EnigmaI,
+    M3,
+    M4,
+    SwissK
+}
+
+impl Model {
+    pub fn allows_rotor(&self, rotor: RotorId) -> bool {
+        match self {
+            Model::EnigmaI => matches!(rotor, RotorId::I | RotorId::II | RotorId::III | RotorId::IV | RotorId::V),
+            Model::M3 | Model::M4 => matches!(rotor, RotorId::I | RotorId::II | RotorId::III | RotorId::IV | RotorId::V
+                | RotorId::VI | RotorId::VII | RotorId::VIII),
+            Model::SwissK => matches!(rotor, RotorId::IK | RotorId::IIK | RotorId::IIIK)
+        }
+    }
+
+    pub fn allows_reflector(&self, reflector: ReflectorId) -> bool {
+        match self {
+            Model::EnigmaI => matches!(reflector, ReflectorId::A | ReflectorId::B | ReflectorId::C),
+            Model::M3 => matches!(reflector, ReflectorId::B | ReflectorId::C),
+            Model::M4 => reflector.is_thin(),
+            Model::SwissK => reflector == ReflectorId::K
+        }
+    }
+
+    pub fn needs_greek_rotor(&self) -> bool {
+        *self == Model::M4
+    }
+
+    pub fn has_plugboard(&self) -> bool {
+        *self != Model::SwissK
+    }
+
+    /* function: entry_wheel
+       input: none
+       output: Option containing the entry wheel wiring as an enciphered alphabet (the contact each key is wired to), or None when
+          the keys are wired to the contacts in alphabetical order
+       limitations: none obvious at this time */
+    pub fn entry_wheel(&self) -> Option<&'static str> {
+        match self {
+            // the keys Q, W, E, R, ... are wired to contacts A, B, C, D, ... in keyboard order
+            Model::SwissK => Some("JWULCMNOHPQZYXIRADKEGVBTSF"),
+            _ => None
+        }
+    }
+}
+
+/* function: compatible_rotors
+   input: Model to list the rotors of
+   output: Vec<RotorId> of every rotor that model could be fitted with, in the order they are declared
+   limitations: none obvious at this time
+   algorithm: the rotors allows_rotor accepts for the three stepping slots, which are I to V for the Enigma I and I to VIII for the
+      M3 and M4, together with the Greek rotors Beta and Gamma for the M4, which only fit the fourth slot beside the reflector */
+pub fn compatible_rotors(model: Model) -> Vec<RotorId> {
+    RotorId::ALL.iter()
+        .filter(|rotor| model.allows_rotor(**rotor) || (model.needs_greek_rotor() && matches!(rotor, RotorId::Beta | RotorId::Gamma)))
+        .copied()
+        .collect()
+}
+
+/* function: generate_key_sheet
+   inputs: u32 giving the number of days to generate keys for, e.g.: 31 for a month
+           &mut Rng supplying the random choices, so a seeded generator gives the same sheet each time
+   output: Vec<MachineConfig> holding one daily key for each day, in order
+   limitations: The keys are for the Wehrmacht Enigma I, see generate_model_key_sheet for the other models. They are for
+      simulations and games only, the generator is not fit for choosing real keys
+   algorithm: as generate_model_key_sheet describes, for the Enigma I */
+pub fn generate_key_sheet<R: Rng>(days: u32, rng: &mut R) -> Vec<MachineConfig> {
+    generate_model_key_sheet(Model::EnigmaI, days, rng)
+}
+
+/* function: generate_model_key_sheet
+   inputs: Model to generate keys for
+           u32 giving the number of days to generate keys for
+           &mut Rng supplying the random choices
+   output: Vec<MachineConfig> holding one daily key for each day, in order
+   limitations: As generate_key_sheet. The Swiss K entry wheel is not part of a MachineConfig, so build its keys with
+      MachineBuilder::model to have it fitted
+   algorithm: each day three different rotors the model allows are drawn in order, along with one of its reflectors, and each ring
+      setting and start position is drawn from the 26 letters. Models with a plugboard get ten cables joining twenty different
+      letters, as the key sheets of the war usually listed, and the M4 also gets a Greek rotor with its own ring and position */
+pub fn generate_model_key_sheet<R: Rng>(model: Model, days: u32, rng: &mut R) -> Vec<MachineConfig> {
+    let reflectors: Vec<ReflectorId> = [ReflectorId::A, ReflectorId::B, ReflectorId::C, ReflectorId::BThin, ReflectorId::CThin, ReflectorId::K]
+        .into_iter()
+        .filter(|reflector| model.allows_reflector(*reflector))
+        .collect();
+    let greek_rotors = [RotorId::Beta, RotorId::Gamma];
+    (0..days).map(|_| {
+        let mut rotors: Vec<RotorId> = RotorId::ALL.into_iter().filter(|rotor| model.allows_rotor(*rotor)).collect();
+        shuffle(&mut rotors, rng);
+        let mut letters: Vec<char> = (0..26).map(index_letter).collect();
+        shuffle(&mut letters, rng);
+        let cables = if model.has_plugboard() { 10 } else { 0 };
+        let mut setting = || rng.below(26) as u16;
+        MachineConfig {
+            rotors: [rotors[0], rotors[1], rotors[2]],
+            rings: [setting(), setting(), setting()],
+            positions: [setting(), setting(), setting()],
+            plugboard: letters.chunks(2).take(cables).map(|pair| (pair[0], pair[1])).collect(),
+            reflector: reflectors[rng.below(reflectors.len() as u64) as usize],
+            greek: if model.needs_greek_rotor() {
+                Some((greek_rotors[rng.below(2) as usize], rng.below(26) as u16, rng.below(26) as u16))
+            } else {
+                None
+            }
+        }
+    }).collect()
+}
+
+// Puts the items in a random order, each order equally likely, by the Fisher-Yates shuffle
+fn shuffle<T, R: Rng>(items: &mut [T], rng: &mut R) {
+    for index in (1..items.len()).rev() {
+        items.swap(index, rng.below(index as u64 + 1) as usize);
+    }
+}
+
+/* WEHRMACHT_DEFAULT is the machine used by the sample program: rotors I, II and III from left to right with ring settings H, J and
+   C, starting at M, C and K, reflector B and no plugboard cables. It is a ready made starting point to copy and adjust, e.g.:
+     let mut config = WEHRMACHT_DEFAULT;
+     config.plugboard = vec![('A', 'V'), ('B', 'S')]; */
+pub const WEHRMACHT_DEFAULT: MachineConfig = MachineConfig {
+    rotors: [RotorId::I, RotorId::II, RotorId::III],
+    rings: [7, 9, 2],
+    positions: [12, 2, 10],
+    plugboard: Vec::new(),
+    reflector: ReflectorId::B,
+    greek: None
+};
+
+#[cfg(test)]
+use crate::alphabet::letter_index;
+#[cfg(test)]
+use crate::enigma::enigma_machine::EnigmaMachine;
+#[cfg(test)]
+use crate::enigma::enigma_wheel::EnigmaWheel;
+#[cfg(test)]
+use crate::random::XorShift;
+
+#[test]
+// Tests to see if a month of generated keys are all valid, and only use rotors and reflectors the model could be fitted with
+fn test_generate_key_sheet() {
+    let mut rng = XorShift::new(1939);
+    let sheet = generate_key_sheet(31, &mut rng);
+    assert_eq!(31, sheet.len());
+    for config in &sheet {
+        assert_eq!(Ok(()), config.validate());
+        assert!(config.rotors.iter().all(|rotor| Model::EnigmaI.allows_rotor(*rotor)));
+        assert!(config.rotors[0] != config.rotors[1] && config.rotors[1] != config.rotors[2] && config.rotors[0] != config.rotors[2]);
+        assert!(Model::EnigmaI.allows_reflector(config.reflector));
+        assert_eq!(10, config.plugboard.len());
+        assert_eq!(None, config.greek);
+    }
+    assert_ne!(sheet[0], sheet[1]);
+
+    for config in generate_model_key_sheet(Model::M4, 31, &mut rng) {
+        assert_eq!(Ok(()), config.validate());
+        assert!(config.reflector.is_thin());
+        assert!(matches!(config.greek, Some((RotorId::Beta | RotorId::Gamma, _, _))));
+    }
+    for config in generate_model_key_sheet(Model::SwissK, 31, &mut rng) {
+        assert_eq!(Ok(()), config.validate());
+        assert!(config.plugboard.is_empty());
+    }
+}
+
+#[test]
+// Tests to see if rotor and reflector names resolve to their wiring
+fn test_from_name() {
+    assert_eq!(Some(RotorId::III), RotorId::from_name("III"));
+    assert_eq!("BDFHJLCPRTXVZNYEIWGAKMUSQO", RotorId::from_name("III").unwrap().wiring());
+    assert_eq!(None, RotorId::from_name("IX"));
+    assert_eq!(Some(ReflectorId::B), ReflectorId::from_name("UKW-B"));
+    assert_eq!(Some(ReflectorId::B), ReflectorId::from_name("B"));
+    assert_eq!(None, ReflectorId::from_name("UKW-D"));
+    assert_eq!(Some(RotorId::Beta), RotorId::from_name("Beta"));
+    assert_eq!(Some(ReflectorId::BThin), ReflectorId::from_name("UKW-B-thin"));
+}
+
+#[test]
+// Tests to see if the Swiss K entry wheel sends each key of the keyboard row QWERTZ... to the contacts in order
+fn test_entry_wheel() {
+    let wiring: Vec<char> = Model::SwissK.entry_wheel().unwrap().chars().collect();
+    for (contact, key) in "QWERTZUIOASDFGHJKPYXCVBNML".chars().enumerate() {
+        assert_eq!(index_letter(contact), wiring[letter_index(key)]);
+    }
+    assert_eq!(None, Model::EnigmaI.entry_wheel());
+}
+
+#[test]
+// Tests to see if the default preset builds the sample program's machine, and deciphers the sample message with its rings at A
+fn test_wehrmacht_default() {
+    let mut sample = EnigmaMachine::new("ABCDEFGHIJKLMNOPQRSTUVWXYZ".to_owned(),
+        "BDFHJLCPRTXVZNYEIWGAKMUSQO".to_owned(), 10, 2,
+        "AJDKSIRUXBLHWTMCQGZNPYFVOE".to_owned(), 2, 9,
+        "EKMFLGDQVZNTOWYHXUSPAIBRCJ".to_owned(), 12, 7,
+        "YRUHQSLDPXNGOKMIEBFZCWVJAT".to_owned()
+    );
+    sample.set_triggers(vec![22], vec![5], vec![17]);
+    let ciphertext = sample.transform("ENIGMA REVEALED");
+    assert_eq!("ENIGMA REVEALED", WEHRMACHT_DEFAULT.build().transform(&ciphertext));
+
+    let mut config = WEHRMACHT_DEFAULT;
+    config.rings = [0, 0, 0];
+    assert_eq!("ENIGMA REVEALED", config.build().transform("QMJIDO MZWZJFJR"));
+}
+
+#[test]
+// Tests to see if each model lists the rotors it could historically be fitted with
+fn test_compatible_rotors() {
+    assert_eq!(vec![RotorId::I, RotorId::II, RotorId::III, RotorId::IV, RotorId::V], compatible_rotors(Model::EnigmaI));
+    assert_eq!(8, compatible_rotors(Model::M3).len());
+    let m4 = compatible_rotors(Model::M4);
+    assert_eq!(10, m4.len());
+    assert!(m4.contains(&RotorId::Beta) && m4.contains(&RotorId::Gamma));
+    assert_eq!(vec![RotorId::IK, RotorId::IIK, RotorId::IIIK], compatible_rotors(Model::SwissK));
+}
+
+#[test]
+// Tests to see if the embedded table resolves rotor III to its wiring and notch, and has a valid row for every rotor
+fn test_rotor_table() {
+    let rotor = by_name("III").unwrap();
+    assert_eq!(RotorDef { name: "III", wiring: "BDFHJLCPRTXVZNYEIWGAKMUSQO", notches: "V" }, rotor);
+    assert_eq!(None, by_name("IX"));
+    for rotor in RotorId::ALL {
+        assert_eq!(Ok(()), EnigmaWheel::check_wiring(rotor.wiring()));
+        assert_eq!(Some(rotor), RotorId::from_name(rotor.name()));
+    }
+    assert_eq!("ZM", RotorId::VI.turnovers());
+    assert_eq!("", RotorId::Beta.turnovers());
+}
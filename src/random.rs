@@ -0,0 +1,76 @@
+/* The random module holds a small source of pseudo-random numbers for simulations that need one, such as generating training
+   traffic. It is not suitable for choosing real keys. */
+
+/* The Rng trait provides a stream of pseudo-random numbers. Only next_u64 needs implementing, the other methods are built on it */
+pub trait Rng {
+    fn next_u64(&mut self) -> u64;
+
+    /* function: next_f64
+       input: none
+       output: f64 drawn evenly from 0.0 up to but not including 1.0
+       limitations: none obvious at this time
+       algorithm: the top 53 bits of the next number fill the mantissa of a double */
+    fn next_f64(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    /* function: below
+       input: u64 giving the number of possible values, which must not be 0
+       output: u64 from 0 up to but not including bound
+       limitations: The result is very slightly biased toward small values unless bound is a power of two, which does not matter for
+          the small bounds used here */
+    fn below(&mut self, bound: u64) -> u64 {
+        self.next_u64() % bound
+    }
+}
+
+/* A XorShift is a xorshift64* generator. It contains the following:
+     A u64 called state that is never 0
+   A XorShift has the following functions available to it:
+     new is a constructor that returns a XorShift given a seed, so the same seed always gives the same numbers
+   XorShift implements the trait Rng */
+#[derive(Clone, Debug)]
+pub struct XorShift {
+    state: u64
+}
+
+impl XorShift {
+    /* function: new
+       input: u64 seed
+       output: XorShift that starts from the seed
+       limitations: A seed of 0 would stick at 0, so it is replaced by a fixed non-zero seed */
+    pub fn new(seed: u64) -> XorShift {
+        XorShift { state: if seed == 0 { 0x9E37_79B9_7F4A_7C15 } else { seed } }
+    }
+}
+
+impl Rng for XorShift {
+    /* function: next_u64
+       input: none
+       output: u64 holding the next number in the stream
+       limitations: none obvious at this time
+       algorithm: shifts and xors the state, then scales it by an odd constant so the low bits mix as well as the high ones */
+    fn next_u64(&mut self) -> u64 {
+        self.state ^= self.state >> 12;
+        self.state ^= self.state << 25;
+        self.state ^= self.state >> 27;
+        self.state.wrapping_mul(0x2545_F491_4F6C_DD1D)
+    }
+}
+
+#[test]
+// Tests to see if the same seed gives the same numbers and the derived draws stay in range
+fn test_xorshift() {
+    let mut first = XorShift::new(42);
+    let mut second = XorShift::new(42);
+    for _ in 0..100 {
+        assert_eq!(first.next_u64(), second.next_u64());
+    }
+    let mut zero = XorShift::new(0);
+    assert_ne!(zero.next_u64(), zero.next_u64());
+    for _ in 0..1000 {
+        let fraction = first.next_f64();
+        assert!((0.0..1.0).contains(&fraction));
+        assert!(first.below(26) < 26);
+    }
+}
@@ -0,0 +1,45 @@
+/* Tests of the command line entry point, running the built binary with the settings as arguments and the text piped in. */
+use std::io::Write;
+use std::process::{Command, Output, Stdio};
+
+// Runs the enigma binary with the given arguments, writing the input to its standard input
+fn run(args: &[&str], input: &str) -> Output {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_enigma"))
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+    // the binary may exit on bad arguments before reading anything, closing the pipe, so a failed write is not an error here
+    let _ = child.stdin.take().unwrap().write_all(input.as_bytes());
+
+    child.wait_with_output().unwrap()
+}
+
+#[test]
+// Tests to see if the Enigma I operating manual example of 1930 is decrypted from standard input, and encrypted back again
+fn test_cli_decrypts_stdin() {
+    let settings = ["II-I-III", "XMV", "ABL", "AM.FI.NV.PS.TU.WZ", "A"];
+    let output = run(&settings, "GCDSE AHUGW TQGRK VLFGX UCALX VYMIG\n");
+    assert!(output.status.success());
+    assert_eq!("FEIND LIQEI NFANT ERIEK OLONN EBEOB\n", String::from_utf8(output.stdout).unwrap());
+
+    let output = run(&settings, "feind liqei nfant eriek olonn ebeob\n");
+    assert_eq!("GCDSE AHUGW TQGRK VLFGX UCALX VYMIG\n", String::from_utf8(output.stdout).unwrap());
+}
+
+#[test]
+// Tests to see if missing or invalid settings print the usage message and exit with a failure status
+fn test_cli_rejects_invalid_args() {
+    let output = run(&[], "HELLO");
+    assert!(!output.status.success());
+    assert!(String::from_utf8(output.stderr).unwrap().starts_with("usage: enigma"));
+
+    let output = run(&["II-I-XI", "XMV", "ABL", "A"], "HELLO");
+    assert!(!output.status.success());
+    let message = String::from_utf8(output.stderr).unwrap();
+    assert!(message.contains("unknown rotor 'XI'"));
+    assert!(message.contains("usage: enigma"));
+    assert!(output.stdout.is_empty());
+}
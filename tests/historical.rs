@@ -0,0 +1,81 @@
+/* Known answer tests using published Enigma messages. Each entry of the table gives the settings the message was sent with, the
+   ciphertext as it was transmitted, and the plaintext it decrypts to. Between them they cover a plugboard, nonzero ring settings,
+   the double-step of the middle rotor, and rotors with two notches. A message sent on a four rotor M4 is tested on its own. */
+use enigma::alphabet::letter_index;
+use enigma::config::MachineBuilder;
+use enigma::presets::{Model, ReflectorId, RotorId};
+
+// The settings of a published message, with the rotors listed leftmost first and the rings and positions as window letters
+struct HistoricalMessage {
+    rotors: [&'static str; 3],
+    reflector: &'static str,
+    rings: &'static str,
+    positions: &'static str,
+    plugs: &'static str,
+    ciphertext: &'static str,
+    plaintext: &'static str
+}
+
+const MESSAGES: [HistoricalMessage; 3] = [
+    // Enigma I operating manual example, 1930
+    HistoricalMessage { rotors: ["II", "I", "III"], reflector: "A", rings: "XMV", positions: "ABL", plugs: "AM FI NV PS TU WZ",
+        ciphertext: "GCDSE AHUGW TQGRK VLFGX UCALX VYMIG MMNMF DXTGN VHVRM MEVOU YFZSL RHDRR XFJWC FHUHM UNZEF RDISI KBGPM YVXUZ",
+        plaintext: "FEIND LIQEI NFANT ERIEK OLONN EBEOB AQTET XANFA NGSUE DAUSG ANGBA ERWAL DEXEN DEDRE IKMOS TWAER TSNEU STADT" },
+    // Operation Barbarossa, 7 July 1941, first part
+    HistoricalMessage { rotors: ["II", "IV", "V"], reflector: "B", rings: "BUL", positions: "BLA", plugs: "AV BS CG DL FU HZ IN KM OW RX",
+        ciphertext: "EDPUD NRGYS ZRCXN UYTPO MRMBO FKTBZ REZKM LXLVE FGUEY SIOZV EQMIK UBPMM YLKLT TDEIS MDICA GYKUA CTCDO MOHWX MUUIA \
+         UBSTS LRNBZ SZWNR FXWFY SSXJZ VIJHI DISHP RKLKA YUPAD TXQSP INQMA TLPIF SVKDA SCTAC DPBOP VHJK",
+        plaintext: "AUFKL XABTE ILUNG XVONX KURTI NOWAX KURTI NOWAX NORDW ESTLX SEBEZ XSEBE ZXUAF FLIEG ERSTR ASZER IQTUN GXDUB ROWKI \
+         XDUBR OWKIX OPOTS CHKAX OPOTS CHKAX UMXEI NSAQT DREIN ULLXU HRANG ETRET ENXAN GRIFF XINFX RGTX" },
+    // Scharnhorst, 26 December 1943, sent on an M3; the middle rotor double-steps on the first letter
+    HistoricalMessage { rotors: ["III", "VI", "VIII"], reflector: "B", rings: "AHM", positions: "UZV", plugs: "AN EZ HK IJ LR MQ OT PV SW UX",
+        ciphertext: "YKAE NZAP MSCH ZBFO CUVM RMDP YCOF HADZ IZME FXTH FLOL PZLF GGBO TGOX GRET DWTJ IQHL MXVJ WKZU ASTR",
+        plaintext: "STEU EREJ TANA FJOR DJAN STAN DORT QUAA ACCC VIER NEUN NEUN ZWOF AHRT ZWON ULSM XXSC HARN HORS THCO" }
+];
+
+// Converts a string of window letters such as "XMV" into rotor settings where 0 is A
+fn settings(letters: &str) -> [u16; 3] {
+    let codes: Vec<u16> = letters.chars().map(|chr| letter_index(chr) as u16).collect();
+    [codes[0], codes[1], codes[2]]
+}
+
+#[test]
+// Tests to see if each published message decrypts to its published plaintext
+fn test_historical_messages() {
+    for message in MESSAGES {
+        let rotors: Vec<RotorId> = message.rotors.iter().map(|name| RotorId::from_name(name).unwrap()).collect();
+        let pairs: Vec<(char, char)> = message.plugs.split(' ').map(|pair| (pair.chars().next().unwrap(), pair.chars().nth(1).unwrap())).collect();
+        let mut my_enigma = MachineBuilder::new()
+            .rotors(rotors[0], rotors[1], rotors[2])
+            .reflector(ReflectorId::from_name(message.reflector).unwrap())
+            .rings(settings(message.rings))
+            .positions(settings(message.positions))
+            .plugs(&pairs)
+            .build()
+            .unwrap();
+        assert_eq!(message.plaintext, my_enigma.transform_message(message.ciphertext.to_owned()));
+    }
+}
+
+#[test]
+// Tests to see if a message sent by U-534 on a four rotor M4 in 1945 decrypts to its published plaintext
+fn test_m4_message() {
+    let ciphertext = "NCZW VUSX PNYM INHZ XMQX SFWX WLKJ AHSH NMCO CCAK UQPM KCSM HKSE INJU SBLK IOSX CKUB HMLL XCSJ USRR DVKO HULX WCCB GVLI \
+        YXEO AHXR HKKF VDRE WEZL XOBA FGYU JQUK GRTV UKAM EURB VEKS UHHV OYHA BCJW MAKL FKLM YFVN RIZR VVRT KOFD ANJM OLBG FFLE \
+        OPRG TFLV RHOW OPBE KVWM UQFM PWPA RMFH AGKX IIBG";
+    let plaintext = "VONV ONJL OOKS JHFF TTTE INSE INSD REIZ WOYY QNNS NEUN INHA LTXX BEIA NGRI FFUN TERW ASSE RGED RUEC KTYW ABOS XLET ZTER \
+        GEGN ERST ANDN ULAC HTDR EINU LUHR MARQ UANT ONJO TANE UNAC HTSE YHSD REIY ZWOZ WONU LGRA DYAC HTSM YSTO SSEN ACHX EKNS \
+        VIER MBFA ELLT YNNN NNNO OOVI ERYS ICHT EINS NULL";
+    let pairs = [('A', 'T'), ('B', 'L'), ('D', 'F'), ('G', 'J'), ('H', 'M'), ('N', 'W'), ('O', 'P'), ('Q', 'Y'), ('R', 'Z'), ('V', 'X')];
+    let mut my_enigma = MachineBuilder::new()
+        .model(Model::M4)
+        .greek(RotorId::Beta, 0, 21)
+        .rotors(RotorId::II, RotorId::IV, RotorId::I)
+        .reflector(ReflectorId::BThin)
+        .rings(settings("AAV"))
+        .positions(settings("JNA"))
+        .plugs(&pairs)
+        .build()
+        .unwrap();
+    assert_eq!(plaintext, my_enigma.transform_message(ciphertext.to_owned()));
+}